@@ -0,0 +1,49 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+/// Semantic description of an element that has been drawn.
+///
+/// The nodes are collected with `DrawContext::push_accessibility` if the collection has been
+/// enabled with `SharedDrawContext::enable_accessibility`, and can be passed to the
+/// accessibility API of the platform, for example to a screen reader.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccessibilityNode {
+    /// What kind of element this is.
+    pub role: AccessibilityRole,
+
+    /// Text that describes the element. Can be empty.
+    pub label: String,
+
+    /// Smallest rectangle of the viewport that contains the element, in the same format as
+    /// `DrawContext::bounding_box`.
+    pub bounds: [f32; 4],
+}
+
+/// Kind of element described by an `AccessibilityNode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AccessibilityRole {
+    /// Some text.
+    Label,
+    /// An image that is only used for decoration.
+    Image,
+    /// An element that can be clicked.
+    Button,
+    /// An element that can be checked or unchecked.
+    CheckBox,
+    /// An element that chooses a value within a range.
+    Slider,
+    /// An element in which the user can type some text.
+    TextInput,
+    /// An element that scrolls some content.
+    ScrollBar,
+    /// An element that shows the progress of an operation.
+    ProgressBar,
+    /// A menu or a menu bar.
+    Menu,
+}
@@ -10,6 +10,142 @@
 use std::time::Duration;
 use std::time::SystemTime;
 
+use Matrix;
+
+/// Describes a transformation that is applied to an element during an animation.
+///
+/// While an `Interpolation` describes how an animation progresses over time, an `Animation`
+/// describes what happens to the element. Use `DrawContext::animate` to combine both.
+pub trait Animation {
+    /// Takes the progress of the animation, where `0.0` is the start and `1.0` is the end, and
+    /// returns the matrix to apply to the element.
+    ///
+    /// The matrix is in the coordinates of the animated context, where `[-1.0, -1.0]` is the
+    /// bottom-left hand corner and `[1.0, 1.0]` the top-right hand corner. It should be the
+    /// identity matrix at `1.0`, so that the element ends up at its normal position.
+    fn animate(&self, percent: f32) -> Matrix;
+}
+
+/// The element moves from an offset towards its normal position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Translation {
+    /// Offset of the element at the start of the animation. This is in the coordinates of the
+    /// animated context, which means that `[2.0, 0.0]` is an offset of exactly its width.
+    pub initial_offset: [f32; 2],
+}
+
+impl Animation for Translation {
+    #[inline]
+    fn animate(&self, percent: f32) -> Matrix {
+        let remaining = 1.0 - percent;
+        Matrix::translate(self.initial_offset[0] * remaining, self.initial_offset[1] * remaining)
+    }
+}
+
+/// The element is scaled uniformly from an initial scale towards its normal size. The center of
+/// the element doesn't move.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Zoom {
+    /// Scale of the element at the start of the animation. For example `0.5` means that the
+    /// element starts at half of its size.
+    pub initial_zoom: f32,
+}
+
+impl Animation for Zoom {
+    #[inline]
+    fn animate(&self, percent: f32) -> Matrix {
+        Matrix::scale(self.initial_zoom + (1.0 - self.initial_zoom) * percent)
+    }
+}
+
+/// The element is scaled from an initial scale towards its normal size, independently on each
+/// axis. The center of the element doesn't move.
+///
+/// Contrary to `Zoom`, this can be used to squash or stretch an element.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stretch {
+    /// Horizontal and vertical scales of the element at the start of the animation.
+    pub initial_scale: [f32; 2],
+}
+
+impl Animation for Stretch {
+    #[inline]
+    fn animate(&self, percent: f32) -> Matrix {
+        Matrix::scale_wh(self.initial_scale[0] + (1.0 - self.initial_scale[0]) * percent,
+                         self.initial_scale[1] + (1.0 - self.initial_scale[1]) * percent)
+    }
+}
+
+impl<A: ?Sized> Animation for Box<A> where A: Animation {
+    #[inline]
+    fn animate(&self, percent: f32) -> Matrix {
+        (**self).animate(percent)
+    }
+}
+
+/// A sequence of animations that are played one after the other.
+///
+/// Use `DrawContext::animate_timeline` to apply it to a context.
+#[derive(Default)]
+pub struct Timeline {
+    /// The segments of the timeline, in order.
+    pub segments: Vec<TimelineSegment>,
+}
+
+/// A segment of a `Timeline`: its duration, the animation to play and its interpolation.
+pub type TimelineSegment = (Duration, Box<dyn Animation>, Box<dyn Interpolation>);
+
+impl Timeline {
+    /// Builds an empty `Timeline`.
+    #[inline]
+    pub fn new() -> Timeline {
+        Timeline {
+            segments: Vec::new(),
+        }
+    }
+
+    /// Adds a segment at the end of the timeline.
+    #[inline]
+    pub fn push<A, I>(&mut self, duration: Duration, animation: A, interpolation: I)
+        where A: Animation + 'static, I: Interpolation + 'static
+    {
+        self.segments.push((duration, Box::new(animation), Box::new(interpolation)));
+    }
+
+    /// Returns the total duration of the timeline.
+    pub fn duration(&self) -> Duration {
+        self.segments.iter().fold(Duration::new(0, 0), |total, segment| total + segment.0)
+    }
+
+    /// Returns the matrix to apply to the element when the given time has elapsed since the start
+    /// of the timeline.
+    ///
+    /// Returns the identity matrix once the last segment is over.
+    pub fn matrix_at(&self, elapsed: Duration) -> Matrix {
+        let mut segment_start = Duration::new(0, 0);
+
+        for &(duration, ref animation, ref interpolation) in &self.segments {
+            if elapsed < segment_start + duration {
+                let local = duration_secs(elapsed - segment_start) / duration_secs(duration);
+                return animation.animate(interpolation.from_progress(local) as f32);
+            }
+
+            segment_start += duration;
+        }
+
+        Matrix::identity()
+    }
+}
+
+/// Converts a duration to a number of seconds.
+#[inline]
+fn duration_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1000000000.0
+}
+
 /// Describes how an animation should be interpolated.
 pub trait Interpolation {
     /// Takes a number representing the number of animation cycles that have elapsed, and returns
@@ -18,6 +154,7 @@ pub trait Interpolation {
     /// Note that we're using `f64` instead of `f32` like in the rest of the library, because it
     /// is common to start an animation at `UNIX_EPOCH` which is far away enough to cause precision
     /// issues.
+    #[allow(clippy::wrong_self_convention)]
     fn from_progress(&self, anim_progress: f64) -> f64;
 
     /// Takes an instance representing the current point in time, an instant representing the
@@ -27,21 +164,63 @@ pub trait Interpolation {
     /// Implementations typically return `0.0` when `now < start` and `1.0` when
     /// `now > start + duration_ns`.
     fn calculate(&self, now: SystemTime, start: SystemTime, duration: Duration) -> f64 {
-        let now_minus_start_ms = {
-            let (dur, neg) = match now.duration_since(start) {
-                Ok(d) => (d, false),
-                Err(err) => (err.duration(), true)
-            };
-
-            let val = dur.as_secs() as f64 * 1000000.0 + dur.subsec_nanos() as f64 / 1000.0;
-            if neg { -val } else { val }
-        };
+        self.from_progress(cycles_elapsed(now, start, duration))
+    }
+
+    /// Evaluates the curve at the given progress, where `0.0` is the start and `1.0` is the end
+    /// of the first cycle.
+    ///
+    /// This doesn't involve any notion of time, which means that you can use the curve to remap
+    /// any value, for example a scroll position. `calculate` is equivalent to calling this
+    /// function with the number of cycles that have elapsed. Interpolations that depend on the
+    /// duration of the animation, like `Hold`, are sampled as if the animation lasted for one
+    /// second.
+    #[inline]
+    fn sample(&self, progress: f32) -> f32 {
+        self.from_progress(progress as f64) as f32
+    }
 
-        let duration_ms = duration.as_secs() as f64 * 1000000.0 +
-                          duration.subsec_nanos() as f64 / 1000.0;
+    /// Samples the first cycle of the curve at `steps + 1` regularly-spaced points, and returns
+    /// them as `[progress, value]` pairs going from a progress of `0.0` to `1.0`.
+    ///
+    /// This is meant for tools that plot the curve, for example as a polyline. If `steps` is 0,
+    /// only the start of the curve is returned.
+    fn sample_curve(&self, steps: usize) -> Vec<[f32; 2]> {
+        if steps == 0 {
+            return vec![[0.0, self.sample(0.0)]];
+        }
 
-        let anim_progress = now_minus_start_ms / duration_ms;
-        self.from_progress(anim_progress)
+        (0 ..= steps).map(|step| {
+            let progress = step as f32 / steps as f32;
+            [progress, self.sample(progress)]
+        }).collect()
+    }
+
+    /// Takes a number representing the number of animation cycles that have elapsed, and returns
+    /// true if the animation is still running.
+    ///
+    /// The default implementation returns true if the first cycle isn't over yet, which includes
+    /// the period before the animation starts. Interpolations that repeat forever always return
+    /// true.
+    #[inline]
+    fn is_running(&self, anim_progress: f64) -> bool {
+        anim_progress < 1.0
+    }
+
+    /// Returns the length of a cycle of the interpolation, as a multiple of `duration`, the
+    /// duration of the animation.
+    ///
+    /// This is `1.0` for most interpolations, and is used by `Repeated` and `AlternateRepeated`
+    /// in order to know when to start the next cycle.
+    #[inline]
+    fn cycle_length(&self, _duration: Duration) -> f64 {
+        1.0
+    }
+
+    /// Keeps the final value of the interpolation after the end of each cycle, during `hold`.
+    #[inline]
+    fn hold(self, hold: Duration) -> Hold<Self> where Self: Sized {
+        Hold::new(self, hold)
     }
 
     /// Reverses an interpolation. The element will start at its final position and go towards
@@ -62,14 +241,77 @@ pub trait Interpolation {
     fn alternate_repeat(self) -> AlternateRepeated<Self> where Self: Sized {
         AlternateRepeated::new(self)
     }
+
+    /// Applies a function to the output of an interpolation. For example
+    /// `EaseOut::default().map(|x| 1.0 - x)` goes from `1.0` to `0.0`.
+    #[inline]
+    fn map(self, f: fn(f32) -> f32) -> Mapped<Self> where Self: Sized {
+        Mapped::new(self, f)
+    }
+}
+
+impl<I: ?Sized> Interpolation for Box<I> where I: Interpolation {
+    #[inline]
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        (**self).from_progress(anim_progress)
+    }
+
+    #[inline]
+    fn calculate(&self, now: SystemTime, start: SystemTime, duration: Duration) -> f64 {
+        (**self).calculate(now, start, duration)
+    }
+
+    #[inline]
+    fn sample(&self, progress: f32) -> f32 {
+        (**self).sample(progress)
+    }
+
+    #[inline]
+    fn sample_curve(&self, steps: usize) -> Vec<[f32; 2]> {
+        (**self).sample_curve(steps)
+    }
+
+    #[inline]
+    fn is_running(&self, anim_progress: f64) -> bool {
+        (**self).is_running(anim_progress)
+    }
+
+    #[inline]
+    fn cycle_length(&self, duration: Duration) -> f64 {
+        (**self).cycle_length(duration)
+    }
+}
+
+/// Returns the number of animation cycles that have elapsed between `start` and `now`, given the
+/// duration of a cycle.
+///
+/// The value is negative if `now` is before `start`. This is the value that is passed to
+/// `Interpolation::from_progress`.
+pub fn cycles_elapsed(now: SystemTime, start: SystemTime, duration: Duration) -> f64 {
+    let now_minus_start_ms = {
+        let (dur, neg) = match now.duration_since(start) {
+            Ok(d) => (d, false),
+            Err(err) => (err.duration(), true)
+        };
+
+        let val = dur.as_secs() as f64 * 1000000.0 + dur.subsec_nanos() as f64 / 1000.0;
+        if neg { -val } else { val }
+    };
+
+    let duration_ms = duration.as_secs() as f64 * 1000000.0 +
+                      duration.subsec_nanos() as f64 / 1000.0;
+
+    now_minus_start_ms / duration_ms
 }
 
 /// A linear animation. The animation progresses at a constant rate.
 #[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Linear;
 
 impl Interpolation for Linear {
     #[inline]
+    #[allow(clippy::manual_clamp)]
     fn from_progress(&self, anim_progress: f64) -> f64 {
         if anim_progress >= 1.0 {
             1.0
@@ -81,9 +323,25 @@ impl Interpolation for Linear {
     }
 }
 
+/// An ease-in animation. The animation starts slowly and then speeds up until it reaches its final
+/// position.
+#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EaseIn;
+
+impl Interpolation for EaseIn {
+    #[inline]
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        let progress = Linear.from_progress(anim_progress);
+        progress * progress * progress
+    }
+}
+
 /// An ease-out animation. The animation progresses quickly and then slows down before reaching its
 /// final position.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct EaseOut {
     /// The formula is `1.0 - exp(-linear_progress * factor)`.
     ///
@@ -94,6 +352,7 @@ pub struct EaseOut {
 impl EaseOut {
     /// Builds a `EaseOut` object.
     #[inline]
+    #[allow(clippy::redundant_field_names)]
     pub fn new(factor: f64) -> EaseOut {
         EaseOut {
             factor: factor,
@@ -115,9 +374,134 @@ impl Interpolation for EaseOut {
     }
 }
 
+/// An ease-in-out animation. The animation starts slowly, speeds up, and then slows down before
+/// reaching its final position.
+#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EaseInOut;
+
+impl Interpolation for EaseInOut {
+    #[inline]
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        if anim_progress >= 1.0 {
+            1.0
+        } else if anim_progress <= 0.0 {
+            0.0
+        } else if anim_progress < 0.5 {
+            4.0 * anim_progress * anim_progress * anim_progress
+        } else {
+            let inv = 2.0 - 2.0 * anim_progress;
+            1.0 - inv * inv * inv / 2.0
+        }
+    }
+}
+
+/// The element reaches its final position quickly, then bounces back a few times like a ball
+/// falling on the ground.
+#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Bounce;
+
+impl Interpolation for Bounce {
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        let p = Linear.from_progress(anim_progress);
+
+        if p < 1.0 / 2.75 {
+            7.5625 * p * p
+        } else if p < 2.0 / 2.75 {
+            let p = p - 1.5 / 2.75;
+            7.5625 * p * p + 0.75
+        } else if p < 2.5 / 2.75 {
+            let p = p - 2.25 / 2.75;
+            7.5625 * p * p + 0.9375
+        } else {
+            let p = p - 2.625 / 2.75;
+            7.5625 * p * p + 0.984375
+        }
+    }
+}
+
+/// An animation that follows a custom curve.
+///
+/// The function receives the linear progress of the animation between `0.0` and `1.0`, and
+/// returns the progress of the element. The result is clamped between `0.0` and `1.0`. For
+/// example `Custom(|t| t * t)` is a quadratic ease-in.
+#[derive(Copy, Clone, Debug)]
+pub struct Custom(pub fn(f32) -> f32);
+
+impl Interpolation for Custom {
+    #[inline]
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        let progress = (self.0)(Linear.from_progress(anim_progress) as f32);
+        Linear.from_progress(progress as f64)
+    }
+}
+
+/// One of the common interpolations of this module, chosen at runtime.
+///
+/// This is useful when the interpolation is loaded from a configuration file, as it can be
+/// obtained from its name with `from_name` and doesn't require boxing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Easing {
+    /// See `Linear`.
+    Linear,
+    /// See `EaseIn`.
+    EaseIn,
+    /// See `EaseOut`. Uses the default factor.
+    EaseOut,
+    /// See `EaseInOut`.
+    EaseInOut,
+    /// See `Bounce`.
+    Bounce,
+}
+
+impl Easing {
+    /// Returns the easing that corresponds to the given name, or `None` if the name is unknown.
+    ///
+    /// The names are the same as in CSS: `linear`, `ease-in`, `ease-out`, `ease-in-out`, plus
+    /// `bounce`.
+    pub fn from_name(name: &str) -> Option<Easing> {
+        match name {
+            "linear" => Some(Easing::Linear),
+            "ease-in" => Some(Easing::EaseIn),
+            "ease-out" => Some(Easing::EaseOut),
+            "ease-in-out" => Some(Easing::EaseInOut),
+            "bounce" => Some(Easing::Bounce),
+            _ => None
+        }
+    }
+
+    /// Returns the name of the easing, as accepted by `from_name`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Easing::Linear => "linear",
+            Easing::EaseIn => "ease-in",
+            Easing::EaseOut => "ease-out",
+            Easing::EaseInOut => "ease-in-out",
+            Easing::Bounce => "bounce",
+        }
+    }
+}
+
+impl Interpolation for Easing {
+    #[inline]
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        match *self {
+            Easing::Linear => Linear.from_progress(anim_progress),
+            Easing::EaseIn => EaseIn.from_progress(anim_progress),
+            Easing::EaseOut => EaseOut::default().from_progress(anim_progress),
+            Easing::EaseInOut => EaseInOut.from_progress(anim_progress),
+            Easing::Bounce => Bounce.from_progress(anim_progress),
+        }
+    }
+}
+
 /// Wraps around an interpolation and reverses it. The element will start at its final position
 /// and go towards the start.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Reversed<I> {
     inner: I
 }
@@ -125,6 +509,7 @@ pub struct Reversed<I> {
 impl<I> Reversed<I> where I: Interpolation {
     /// Builds a `Reversed` object.
     #[inline]
+    #[allow(clippy::redundant_field_names)]
     pub fn new(inner: I) -> Reversed<I> {
         Reversed {
             inner: inner,
@@ -137,10 +522,24 @@ impl<I> Interpolation for Reversed<I> where I: Interpolation {
     fn from_progress(&self, anim_progress: f64) -> f64 {
         self.inner.from_progress(1.0 - anim_progress)
     }
+
+    #[inline]
+    fn is_running(&self, anim_progress: f64) -> bool {
+        self.inner.is_running(anim_progress)
+    }
+
+    #[inline]
+    fn cycle_length(&self, duration: Duration) -> f64 {
+        self.inner.cycle_length(duration)
+    }
 }
 
 /// Wraps around an interpolation and repeats the interpolation multiple times.
+///
+/// A new cycle starts every `cycle_length` of the inner interpolation, which includes for example
+/// the pause added by `Hold`.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Repeated<I> {
     inner: I
 }
@@ -148,25 +547,100 @@ pub struct Repeated<I> {
 impl<I> Repeated<I> where I: Interpolation {
     /// Builds a `Repeated` object.
     #[inline]
+    #[allow(clippy::redundant_field_names)]
     pub fn new(inner: I) -> Repeated<I> {
         Repeated {
             inner: inner,
         }
     }
+
+    /// Same as `from_progress`, with cycles that last for `length`.
+    #[inline]
+    fn value_with_cycle_length(&self, anim_progress: f64, length: f64) -> f64 {
+        let progress = if anim_progress < 0.0 { length + anim_progress % length }
+                       else { anim_progress % length };
+        self.inner.from_progress(progress)
+    }
 }
 
 impl<I> Interpolation for Repeated<I> where I: Interpolation {
     #[inline]
     fn from_progress(&self, anim_progress: f64) -> f64 {
-        let progress = if anim_progress < 0.0 { 1.0 + anim_progress % 1.0 }
-                       else { anim_progress % 1.0 };
-        self.inner.from_progress(progress)
+        let length = self.inner.cycle_length(Duration::from_secs(1));
+        self.value_with_cycle_length(anim_progress, length)
+    }
+
+    #[inline]
+    fn calculate(&self, now: SystemTime, start: SystemTime, duration: Duration) -> f64 {
+        let length = self.inner.cycle_length(duration);
+        self.value_with_cycle_length(cycles_elapsed(now, start, duration), length)
+    }
+
+    #[inline]
+    fn is_running(&self, _: f64) -> bool {
+        true
+    }
+}
+
+/// Wraps around an interpolation and keeps its final value for some time after the end of the
+/// animation.
+///
+/// The interpolation itself still lasts for the duration of the animation, and the pause of
+/// `hold` is added after it. The pause is only visible when the interpolation is repeated, for
+/// example `Linear.hold(Duration::from_secs(1)).repeat()` with a duration of two seconds
+/// animates, pauses for a second, and starts again.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Hold<I> {
+    inner: I,
+    hold: Duration,
+}
+
+impl<I> Hold<I> where I: Interpolation {
+    /// Builds a `Hold` object.
+    #[inline]
+    pub fn new(inner: I, hold: Duration) -> Hold<I> {
+        Hold {
+            inner,
+            hold,
+        }
+    }
+}
+
+impl<I> Interpolation for Hold<I> where I: Interpolation {
+    #[inline]
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        if anim_progress >= 1.0 {
+            1.0
+        } else {
+            self.inner.from_progress(anim_progress)
+        }
+    }
+
+    #[inline]
+    fn is_running(&self, anim_progress: f64) -> bool {
+        self.inner.is_running(anim_progress)
+    }
+
+    #[inline]
+    fn cycle_length(&self, duration: Duration) -> f64 {
+        let secs = duration_secs(duration);
+        if secs > 0.0 {
+            self.inner.cycle_length(duration) + duration_secs(self.hold) / secs
+        } else {
+            self.inner.cycle_length(duration)
+        }
     }
 }
 
 /// Wraps around an interpolation and repeats the interpolation multiple times. Each uneven cycle
 /// the animation is reversed.
+///
+/// Like with `Repeated`, a cycle lasts for `cycle_length` of the inner interpolation. With
+/// `Hold`, the final value is kept at the end of the even cycles and the initial value at the
+/// end of the uneven ones.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AlternateRepeated<I> {
     inner: I
 }
@@ -174,17 +648,571 @@ pub struct AlternateRepeated<I> {
 impl<I> AlternateRepeated<I> where I: Interpolation {
     /// Builds a `AlternateRepeated` object.
     #[inline]
+    #[allow(clippy::redundant_field_names)]
     pub fn new(inner: I) -> AlternateRepeated<I> {
         AlternateRepeated {
             inner: inner,
         }
     }
+
+    /// Same as `from_progress`, with cycles that last for `length`.
+    #[inline]
+    fn value_with_cycle_length(&self, anim_progress: f64, length: f64) -> f64 {
+        let progress = anim_progress.abs() % (2.0 * length);
+
+        if progress < length {
+            self.inner.from_progress(progress)
+        } else if progress < length + 1.0 {
+            self.inner.from_progress(length + 1.0 - progress)
+        } else {
+            self.inner.from_progress(0.0)
+        }
+    }
 }
 
 impl<I> Interpolation for AlternateRepeated<I> where I: Interpolation {
     #[inline]
     fn from_progress(&self, anim_progress: f64) -> f64 {
-        let progress = 1.0 - ((anim_progress.abs() % 2.0) - 1.0).abs();
-        self.inner.from_progress(progress)
+        let length = self.inner.cycle_length(Duration::from_secs(1));
+        self.value_with_cycle_length(anim_progress, length)
+    }
+
+    #[inline]
+    fn calculate(&self, now: SystemTime, start: SystemTime, duration: Duration) -> f64 {
+        let length = self.inner.cycle_length(duration);
+        self.value_with_cycle_length(cycles_elapsed(now, start, duration), length)
+    }
+
+    #[inline]
+    fn is_running(&self, _: f64) -> bool {
+        true
+    }
+}
+
+/// Chains two interpolations. The first one is used until `split`, and the second one after it.
+///
+/// The progress of each sub-range is remapped to the `0.0` to `1.0` range of its interpolation,
+/// and the outputs are scaled so that the first interpolation goes from `0.0` to `split` and the
+/// second one from `split` to `1.0`. The curve is therefore continuous at the split point, as
+/// long as both interpolations start at `0.0` and end at `1.0`.
+///
+/// For example `Splice::new(EaseOut::default(), Linear, 0.8)` quickly covers most of the
+/// distance, then slowly settles.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Splice<A, B> {
+    /// The interpolation used before the split point.
+    pub a: A,
+    /// The interpolation used after the split point.
+    pub b: B,
+    /// The progress at which the second interpolation takes over, between `0.0` and `1.0`.
+    pub split: f32,
+}
+
+impl<A, B> Splice<A, B> where A: Interpolation, B: Interpolation {
+    /// Builds a `Splice` object.
+    #[inline]
+    pub fn new(a: A, b: B, split: f32) -> Splice<A, B> {
+        Splice {
+            a,
+            b,
+            split,
+        }
+    }
+}
+
+impl<A, B> Interpolation for Splice<A, B> where A: Interpolation, B: Interpolation {
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        let split = self.split as f64;
+
+        if split.is_nan() || split <= 0.0 {
+            self.b.from_progress(anim_progress)
+        } else if split >= 1.0 {
+            self.a.from_progress(anim_progress)
+        } else if anim_progress < split {
+            split * self.a.from_progress(anim_progress / split)
+        } else {
+            let progress = (anim_progress - split) / (1.0 - split);
+            split + (1.0 - split) * self.b.from_progress(progress)
+        }
+    }
+}
+
+/// Wraps around an interpolation and applies a function to its output.
+///
+/// Contrary to `Custom`, the result isn't clamped, which makes it possible to remap the curve
+/// into any range.
+#[derive(Copy, Clone, Debug)]
+pub struct Mapped<I> {
+    inner: I,
+    f: fn(f32) -> f32,
+}
+
+impl<I> Mapped<I> where I: Interpolation {
+    /// Builds a `Mapped` object.
+    #[inline]
+    pub fn new(inner: I, f: fn(f32) -> f32) -> Mapped<I> {
+        Mapped {
+            inner,
+            f,
+        }
+    }
+}
+
+impl<I> Interpolation for Mapped<I> where I: Interpolation {
+    #[inline]
+    fn from_progress(&self, anim_progress: f64) -> f64 {
+        (self.f)(self.inner.from_progress(anim_progress) as f32) as f64
+    }
+
+    #[inline]
+    fn calculate(&self, now: SystemTime, start: SystemTime, duration: Duration) -> f64 {
+        (self.f)(self.inner.calculate(now, start, duration) as f32) as f64
+    }
+
+    #[inline]
+    fn is_running(&self, anim_progress: f64) -> bool {
+        self.inner.is_running(anim_progress)
+    }
+
+    #[inline]
+    fn cycle_length(&self, duration: Duration) -> f64 {
+        self.inner.cycle_length(duration)
+    }
+}
+
+/// Describes a transition between two colors.
+///
+/// Contrary to the interpolations, which describe how an animation progresses over time, this
+/// describes what is being animated. Use `DrawContext::animate_tint` to apply it to a context.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ColorAnimation {
+    /// The color at the start of the animation, in RGBA format.
+    pub from: [f32; 4],
+    /// The color at the end of the animation, in RGBA format.
+    pub to: [f32; 4],
+}
+
+impl ColorAnimation {
+    /// Builds a `ColorAnimation` object.
+    #[inline]
+    pub fn new(from: [f32; 4], to: [f32; 4]) -> ColorAnimation {
+        ColorAnimation {
+            from,
+            to,
+        }
+    }
+
+    /// Returns the color at the given percentage of the animation, where `0.0` is the start and
+    /// `1.0` the end.
+    #[inline]
+    pub fn animate_color(&self, percent: f32) -> [f32; 4] {
+        #[inline]
+        fn lerp(a: f32, b: f32, f: f32) -> f32 { a + (b - a) * f }
+
+        [
+            lerp(self.from[0], self.to[0], percent),
+            lerp(self.from[1], self.to[1], percent),
+            lerp(self.from[2], self.to[2], percent),
+            lerp(self.from[3], self.to[3], percent),
+        ]
+    }
+}
+
+/// Combines an animation of the transformation of an element with an animation of its opacity
+/// and optionally of its color.
+///
+/// Use `DrawContext::animate_full` to apply it to a context. All the properties are driven by
+/// the same interpolation, which is handy for example for a notification that slides and fades
+/// in at the same time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FullAnimation<A> {
+    /// The animation of the transformation.
+    pub transform: A,
+    /// The opacity at the start of the animation. The opacity is `1.0` at the end.
+    pub initial_opacity: f32,
+    /// The animation of the color, if any. The color is multiplied with the opacity.
+    pub color: Option<ColorAnimation>,
+}
+
+impl<A> FullAnimation<A> where A: Animation {
+    /// Builds a `FullAnimation` that doesn't animate the color.
+    #[inline]
+    pub fn new(transform: A, initial_opacity: f32) -> FullAnimation<A> {
+        FullAnimation {
+            transform,
+            initial_opacity,
+            color: None,
+        }
+    }
+
+    /// Takes the progress of the animation, where `0.0` is the start and `1.0` is the end, and
+    /// returns the matrix to apply to the element and the color to multiply it with.
+    pub fn animate(&self, percent: f32) -> (Matrix, [f32; 4]) {
+        let opacity = self.initial_opacity + (1.0 - self.initial_opacity) * percent;
+        let color = match self.color {
+            Some(ref color) => color.animate_color(percent),
+            None => [1.0, 1.0, 1.0, 1.0],
+        };
+
+        (self.transform.animate(percent), [color[0], color[1], color[2], color[3] * opacity])
+    }
+}
+
+/// A value that follows a target smoothly, like if it was attached to it with a spring.
+///
+/// Contrary to the other animations of this module, a `Tween` doesn't need to know in advance
+/// when the animation starts and where it ends. Call `step` once per frame with the current
+/// target, and read `current`. This is useful to animate any numeric state of the UI, like the
+/// width of a panel or the value shown by a label.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tween {
+    /// The current value.
+    pub current: f32,
+    /// The speed at which the value changes, in units per second.
+    pub velocity: f32,
+}
+
+impl Tween {
+    /// Builds a `Tween` that doesn't move and whose value is `current`.
+    #[inline]
+    pub fn new(current: f32) -> Tween {
+        Tween {
+            current,
+            velocity: 0.0,
+        }
+    }
+
+    /// Moves the value towards `target`, given that `dt` has elapsed since the previous step.
+    ///
+    /// The spring is critically damped, which means that the value reaches the target as fast as
+    /// possible without bouncing around it. The higher the stiffness, the faster the value moves.
+    /// The result is exact no matter the duration, so irregular frame rates are fine.
+    pub fn step(&mut self, target: f32, dt: Duration, stiffness: f32) {
+        if stiffness <= 0.0 {
+            return;
+        }
+
+        let omega = stiffness.sqrt();
+        let t = duration_secs(dt) as f32;
+        let offset = self.current - target;
+        let decay = (-omega * t).exp();
+        let tmp = (self.velocity + omega * offset) * t;
+
+        self.current = target + (offset + tmp) * decay;
+        self.velocity = (self.velocity - omega * tmp) * decay;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use animations::Animation;
+    use animations::ColorAnimation;
+    use animations::Bounce;
+    use animations::Custom;
+    use animations::EaseIn;
+    use animations::EaseInOut;
+    use animations::Easing;
+    use animations::EaseOut;
+    use animations::FullAnimation;
+    use animations::Hold;
+    use animations::Interpolation;
+    use animations::Linear;
+    use animations::Splice;
+    use animations::Stretch;
+    use animations::Timeline;
+    use animations::Translation;
+    use animations::Tween;
+    use animations::Zoom;
+    use Matrix;
+
+    #[test]
+    fn color_animation_midpoint() {
+        let anim = ColorAnimation::new([0.5, 0.5, 0.5, 1.0], [0.0, 0.0, 1.0, 0.0]);
+
+        assert_eq!(anim.animate_color(0.0), [0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(anim.animate_color(0.5), [0.25, 0.25, 0.75, 0.5]);
+        assert_eq!(anim.animate_color(1.0), [0.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn full_animation_midpoint() {
+        let anim = FullAnimation::new(Translation { initial_offset: [2.0, 0.0] }, 0.0);
+        let (matrix, tint) = anim.animate(0.5);
+        assert_eq!(matrix, Matrix::translate(1.0, 0.0));
+        assert_eq!(tint, [1.0, 1.0, 1.0, 0.5]);
+
+        let anim = FullAnimation {
+            color: Some(ColorAnimation::new([1.0, 0.0, 0.0, 1.0], [1.0, 1.0, 1.0, 1.0])),
+            .. anim
+        };
+        assert_eq!(anim.animate(0.5).1, [1.0, 0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn tween_converges() {
+        let mut tween = Tween::new(0.0);
+        let mut previous = tween.current;
+
+        for _ in 0 .. 120 {
+            tween.step(10.0, Duration::from_millis(16), 100.0);
+            assert!(tween.current >= previous);
+            assert!(tween.current <= 10.0);
+            previous = tween.current;
+        }
+
+        assert!((tween.current - 10.0).abs() < 0.01);
+        assert!(tween.velocity.abs() < 0.01);
+
+        // A single large step doesn't overshoot either.
+        let mut tween = Tween::new(0.0);
+        tween.step(-5.0, Duration::from_secs(10), 100.0);
+        assert!((tween.current + 5.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn custom_curve() {
+        let anim = Custom(|t| t * t);
+
+        for &progress in &[0.0, 0.25, 0.5, 0.75, 1.0] {
+            assert!((anim.from_progress(progress) - progress * progress).abs() < 0.0001);
+        }
+
+        // The result is clamped.
+        assert_eq!(anim.from_progress(2.0), 1.0);
+        assert_eq!(Custom(|t| t - 1.0).from_progress(0.5), 0.0);
+
+        let start = UNIX_EPOCH;
+        let now = start + Duration::from_millis(500);
+        assert!((anim.calculate(now, start, Duration::from_secs(1)) - 0.25).abs() < 0.0001);
+    }
+
+    #[test]
+    fn stretch() {
+        let anim = Stretch { initial_scale: [2.0, 0.5] };
+
+        assert_eq!(anim.animate(0.0), Matrix::scale_wh(2.0, 0.5));
+        assert_eq!(anim.animate(0.5), Matrix::scale_wh(1.5, 0.75));
+        assert_eq!(anim.animate(1.0), Matrix::identity());
+    }
+
+    #[test]
+    fn sample_matches_calculate() {
+        fn check<I: Interpolation>(interpolation: I) {
+            let start = UNIX_EPOCH + Duration::from_secs(1000);
+            let duration = Duration::from_secs(2);
+
+            for &(progress, elapsed) in &[(0.0, 0), (0.5, 1000), (1.0, 2000)] {
+                let now = start + Duration::from_millis(elapsed);
+                let calculated = interpolation.calculate(now, start, duration) as f32;
+                assert!((interpolation.sample(progress) - calculated).abs() < 0.0001);
+            }
+        }
+
+        check(Linear);
+        check(EaseOut::default());
+        check(Linear.reverse());
+        check(EaseOut::new(3.0).alternate_repeat());
+        check(EaseInOut);
+        check(Easing::Bounce);
+
+        assert_eq!(Linear.sample(0.5), 0.5);
+    }
+
+    #[test]
+    fn easing_from_name() {
+        let easing = Easing::from_name("ease-out").unwrap();
+        for &progress in &[-0.5, 0.0, 0.1, 0.5, 0.9, 1.0, 2.0] {
+            assert_eq!(easing.from_progress(progress), EaseOut::default().from_progress(progress));
+        }
+
+        for &easing in &[Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut,
+                         Easing::Bounce]
+        {
+            assert_eq!(Easing::from_name(easing.name()), Some(easing));
+        }
+
+        assert_eq!(Easing::from_name("wobble"), None);
+        assert_eq!(EaseIn.sample(1.0), 1.0);
+        assert!((Bounce.sample(1.0) - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn boxed() {
+        let animations: Vec<Box<dyn Animation>> = vec![
+            Box::new(Translation { initial_offset: [1.0, 0.0] }),
+            Box::new(Zoom { initial_zoom: 0.5 }),
+            Box::new(Stretch { initial_scale: [2.0, 0.5] }),
+        ];
+
+        for animation in &animations {
+            assert_eq!(animation.animate(1.0), Matrix::identity());
+        }
+
+        let boxed: Box<dyn Interpolation> = Box::new(Linear.reverse());
+        assert_eq!(boxed.sample(0.25), 0.75);
+        assert!(Box::new(boxed).repeat().is_running(5.0));
+    }
+
+    #[test]
+    fn timeline() {
+        let mut timeline = Timeline::new();
+        timeline.push(Duration::from_secs(1), Translation { initial_offset: [1.0, 0.0] }, Linear);
+        timeline.push(Duration::from_secs(2), Zoom { initial_zoom: 0.5 }, Linear);
+        assert_eq!(timeline.duration(), Duration::from_secs(3));
+
+        assert_eq!(timeline.matrix_at(Duration::from_millis(0)), Matrix::translate(1.0, 0.0));
+        assert_eq!(timeline.matrix_at(Duration::from_millis(500)), Matrix::translate(0.5, 0.0));
+        assert_eq!(timeline.matrix_at(Duration::from_millis(1000)),
+                   Zoom { initial_zoom: 0.5 }.animate(0.0));
+        assert_eq!(timeline.matrix_at(Duration::from_millis(2000)),
+                   Zoom { initial_zoom: 0.5 }.animate(0.5));
+        assert_eq!(timeline.matrix_at(Duration::from_millis(3000)), Matrix::identity());
+        assert_eq!(timeline.matrix_at(Duration::from_secs(60)), Matrix::identity());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        use serde_json;
+
+        let translation = Translation { initial_offset: [0.5, -1.0] };
+        let json = serde_json::to_string(&translation).unwrap();
+        assert_eq!(serde_json::from_str::<Translation>(&json).unwrap(), translation);
+
+        let anim = ColorAnimation::new([1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 0.5]);
+        let json = serde_json::to_string(&anim).unwrap();
+        assert_eq!(serde_json::from_str::<ColorAnimation>(&json).unwrap(), anim);
+
+        // Missing fields take their default value.
+        let ease_out: EaseOut = serde_json::from_str("{}").unwrap();
+        assert_eq!(ease_out.factor, EaseOut::default().factor);
+
+        assert_eq!(serde_json::to_string(&Easing::EaseInOut).unwrap(), "\"ease-in-out\"");
+        assert_eq!(serde_json::from_str::<Easing>("\"bounce\"").unwrap(), Easing::Bounce);
+    }
+
+    #[test]
+    fn hold_then_repeat() {
+        fn at<I: Interpolation>(interpolation: &I, ms: u64) -> f64 {
+            let now = UNIX_EPOCH + Duration::from_millis(ms);
+            interpolation.calculate(now, UNIX_EPOCH, Duration::from_secs(1))
+        }
+
+        let hold = Hold::new(Linear, Duration::from_millis(500));
+        assert!((hold.cycle_length(Duration::from_secs(1)) - 1.5).abs() < 0.0001);
+        assert!((hold.cycle_length(Duration::from_secs(2)) - 1.25).abs() < 0.0001);
+        assert!((at(&hold, 500) - 0.5).abs() < 0.0001);
+        assert_eq!(at(&hold, 1200), 1.0);
+        assert_eq!(Hold::new(Linear, Duration::new(0, 0)).cycle_length(Duration::from_secs(1)),
+                   1.0);
+
+        // A cycle lasts for 1.5 seconds.
+        let repeated = Linear.hold(Duration::from_millis(500)).repeat();
+        assert!((at(&repeated, 250) - 0.25).abs() < 0.0001);
+        assert_eq!(at(&repeated, 1000), 1.0);
+        assert_eq!(at(&repeated, 1250), 1.0);
+        assert_eq!(at(&repeated, 1499), 1.0);
+        assert!(at(&repeated, 1500).abs() < 0.0001);
+        assert!((at(&repeated, 1750) - 0.25).abs() < 0.0001);
+        assert!((at(&repeated, 3250) - 0.25).abs() < 0.0001);
+
+        // The value goes up, stays at the top, goes down, and stays at the bottom.
+        let alternate = Linear.hold(Duration::from_millis(500)).alternate_repeat();
+        assert!((at(&alternate, 250) - 0.25).abs() < 0.0001);
+        assert_eq!(at(&alternate, 1250), 1.0);
+        assert!((at(&alternate, 1750) - 0.75).abs() < 0.0001);
+        assert!((at(&alternate, 2250) - 0.25).abs() < 0.0001);
+        assert_eq!(at(&alternate, 2750), 0.0);
+        assert!((at(&alternate, 3250) - 0.25).abs() < 0.0001);
+
+        // The hold doesn't depend on the duration of the animation.
+        let repeated = Linear.hold(Duration::from_millis(500)).repeat();
+        let now = UNIX_EPOCH + Duration::from_millis(2400);
+        assert_eq!(repeated.calculate(now, UNIX_EPOCH, Duration::from_secs(2)), 1.0);
+        let now = UNIX_EPOCH + Duration::from_millis(2750);
+        let value = repeated.calculate(now, UNIX_EPOCH, Duration::from_secs(2));
+        assert!((value - 0.125).abs() < 0.0001);
+    }
+
+    #[test]
+    fn held_repeat_sample_matches_calculate() {
+        // The interpolations are sampled as if the animation lasted for one second.
+        fn check<I: Interpolation>(interpolation: I) {
+            let duration = Duration::from_secs(1);
+            for step in 0 .. 40u64 {
+                let now = UNIX_EPOCH + Duration::from_millis(step * 150);
+                let calculated = interpolation.calculate(now, UNIX_EPOCH, duration) as f32;
+                let sampled = interpolation.sample(step as f32 * 0.15);
+                assert!((calculated - sampled).abs() < 0.0001);
+            }
+        }
+
+        check(Linear.hold(Duration::from_millis(500)).repeat());
+        check(EaseOut::default().hold(Duration::from_millis(250)).alternate_repeat());
+
+        let points = Linear.hold(Duration::from_secs(1)).repeat().sample_curve(4);
+        assert_eq!(points[2], [0.5, 0.5]);
+    }
+
+    #[test]
+    fn sample_curve() {
+        let points = Linear.sample_curve(8);
+        assert_eq!(points.len(), 9);
+        assert_eq!(points[0], [0.0, 0.0]);
+        assert_eq!(points[8], [1.0, 1.0]);
+        for point in &points {
+            assert!((point[0] - point[1]).abs() < 0.0001);
+        }
+
+        assert_eq!(Linear.sample_curve(0), vec![[0.0, 0.0]]);
+
+        let boxed: Box<dyn Interpolation> = Box::new(EaseOut::default());
+        assert_eq!(boxed.sample_curve(4), EaseOut::default().sample_curve(4));
+    }
+
+    #[test]
+    fn map() {
+        let half = Linear.map(|x| x * 0.5);
+        for step in 0 .. 11 {
+            let progress = step as f64 / 10.0;
+            assert!((half.from_progress(progress) - progress * 0.5).abs() < 0.0001);
+        }
+
+        let now = UNIX_EPOCH + Duration::from_millis(1500);
+        let repeated = Linear.repeat().map(|x| 1.0 - x);
+        assert!((repeated.calculate(now, UNIX_EPOCH, Duration::from_secs(1)) - 0.5).abs() < 0.0001);
+        assert!(repeated.is_running(2.0));
+    }
+
+    #[test]
+    fn splice() {
+        let splice = Splice::new(EaseOut::default(), EaseInOut, 0.25);
+
+        assert_eq!(splice.from_progress(0.0), 0.0);
+        assert_eq!(splice.from_progress(1.0), 1.0);
+        assert!((splice.from_progress(0.25) - 0.25).abs() < 0.0001);
+
+        let before = splice.from_progress(0.25 - 0.00001);
+        let after = splice.from_progress(0.25 + 0.00001);
+        assert!((before - 0.25).abs() < 0.001);
+        assert!((after - 0.25).abs() < 0.001);
+        assert!(before < after);
+
+        // Each half is a scaled copy of its interpolation.
+        let a = EaseOut::default().from_progress(0.5);
+        assert!((splice.from_progress(0.125) - 0.25 * a).abs() < 0.0001);
+        let b = EaseInOut.from_progress(0.5);
+        assert!((splice.from_progress(0.625) - (0.25 + 0.75 * b)).abs() < 0.0001);
+
+        // Without a valid split point, only one of the interpolations is used.
+        assert_eq!(Splice::new(Linear, EaseIn, 0.0).from_progress(0.5), EaseIn.from_progress(0.5));
+        assert_eq!(Splice::new(Linear, EaseIn, 1.5).from_progress(0.5), 0.5);
     }
 }
@@ -0,0 +1,59 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+/// Returns opaque black or opaque white, whichever is the most readable on top of `background`.
+///
+/// The color is chosen according to the relative luminance of the background, as defined by
+/// WCAG. The components of `background` are sRGB values between `0.0` and `1.0`, and its alpha
+/// is ignored. The result can be passed to `DrawContext::with_tint` before drawing some text.
+pub fn contrast_color(background: [f32; 4]) -> [f32; 4] {
+    let luminance = 0.2126 * linearize(background[0]) +
+                    0.7152 * linearize(background[1]) +
+                    0.0722 * linearize(background[2]);
+
+    // Above this luminance, black text has a better contrast ratio than white text.
+    if luminance > 0.179 {
+        [0.0, 0.0, 0.0, 1.0]
+    } else {
+        [1.0, 1.0, 1.0, 1.0]
+    }
+}
+
+/// Converts an sRGB component to a linear one.
+#[inline]
+fn linearize(component: f32) -> f32 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::contrast_color;
+
+    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    #[test]
+    fn dark_background() {
+        assert_eq!(contrast_color([0.0, 0.0, 0.0, 1.0]), WHITE);
+        assert_eq!(contrast_color([0.1, 0.1, 0.4, 1.0]), WHITE);
+        assert_eq!(contrast_color([0.8, 0.0, 0.0, 1.0]), WHITE);
+    }
+
+    #[test]
+    fn light_background() {
+        assert_eq!(contrast_color([1.0, 1.0, 1.0, 1.0]), BLACK);
+        assert_eq!(contrast_color([1.0, 1.0, 0.6, 0.5]), BLACK);
+
+        // Green contributes much more to the luminance than blue.
+        assert_eq!(contrast_color([0.0, 0.8, 0.0, 1.0]), BLACK);
+        assert_eq!(contrast_color([0.0, 0.0, 0.8, 1.0]), WHITE);
+    }
+}
@@ -20,14 +20,17 @@ pub trait Draw {
     ///
     /// To do so, draw a triangle whose coordinatges are `[-1.0, 1.0]`, `[-1.0, -1.0]`
     /// and `[1.0, 1.0]`, then pre-muliplty these coordinates with the matrix given as parameter.
-    /// If you use Vulkan or DirectX, you have to perform an additional step. Pre-multiply that
-    /// result with a matrix that inverts the `y` coordinate (ie. an identity matrix but whose
-    /// value at the second row of the second column is `-1.0`).
+    /// If you use Vulkan or DirectX and the y axis is `YAxis::Up`, which is the default, you have
+    /// to perform an additional step. Pre-multiply that result with a matrix that inverts the `y`
+    /// coordinate (ie. an identity matrix but whose value at the second row of the second column
+    /// is `-1.0`). If the y axis has been set to `YAxis::Down` with `UiState::y_axis`, the matrix
+    /// already does this and you must not do it again.
     ///
     /// The UV coordinates passed as parameter are respectively the texture coordinates at the
     /// top-left, bottom-left and top-right corners. `[0.0, 0.0]` is the bottom-left hand corner
     /// of the texture, and `[1.0, 1.0]` is the top-right hand corner. If you use OpenGL, you can
     /// pass through the values. If you use DirectX or Vulkan, you must do `y = 1.0 - y` somewhere.
+    /// This doesn't depend on the direction of the y axis of the viewport.
     fn draw_triangle(&mut self, texture: &Self::ImageResource, matrix: &Matrix,
                      uv_coords: [[f32; 2]; 3]);
 
@@ -49,7 +52,8 @@ pub trait Draw {
     ///
     /// Contrary to `draw_image`, this library allows one to specify UV coordinates of the four
     /// borders. Coordinates `[0.0, 0.0]` correspond to the bottom-left hand corner of the
-    /// image, and `[1.0, 1.0]` correspond to the top-right hand corner.
+    /// image, and `[1.0, 1.0]` correspond to the top-right hand corner. See `draw_triangle` for
+    /// how to handle the matrix and the UV coordinates with Vulkan or DirectX.
     #[inline]
     fn draw_image_uv(&mut self, name: &Self::ImageResource, matrix: &Matrix, top_left: [f32; 2],
                      top_right: [f32; 2], bottom_right: [f32; 2], bottom_left: [f32; 2])
@@ -60,6 +64,18 @@ pub trait Draw {
         self.draw_triangle(name, &(*matrix * invert), [bottom_right, top_right, bottom_left]);
     }
 
+    /// Draws a straight line between two points, in OpenGL viewport coordinates.
+    ///
+    /// `thickness_px` is the width of the line in pixels, and the line must be centered on the
+    /// segment between `from` and `to`. The color is in RGBA format, and must be multiplied with
+    /// the tint like images and glyphs.
+    ///
+    /// The default implementation ignores the line.
+    #[inline]
+    fn draw_line(&mut self, from: [f32; 2], to: [f32; 2], thickness_px: f32, color: [f32; 4]) {
+        let _ = (from, to, thickness_px, color);
+    }
+
     /// Given an image, this functions returns its width divided by its height.
     fn get_image_width_per_height(&mut self, name: &Self::ImageResource) -> f32;
 
@@ -84,6 +100,43 @@ pub trait Draw {
     /// negative values moves the second character next to the first one. The value must be a
     /// multiple of 1 em. When in doubt, you can simply return `0.0`.
     fn kerning(&self, text_style: &Self::TextStyle, first_char: char, second_char: char) -> f32;
+
+    /// Sets the color that the images and glyphs drawn afterwards must be multiplied with.
+    ///
+    /// The color is in RGBA format, where each component is between `0.0` and `1.0`. Initially
+    /// the tint is `[1.0, 1.0, 1.0, 1.0]`, which means that things are drawn unmodified.
+    ///
+    /// The default implementation ignores the tint.
+    #[inline]
+    fn set_tint(&mut self, tint: [f32; 4]) {
+        let _ = tint;
+    }
+
+    /// Sets the layer in which the images and glyphs drawn afterwards must be drawn.
+    ///
+    /// Elements with a higher z-index must appear above elements with a lower z-index, even if
+    /// they have been drawn before. Elements with the same z-index must appear in the order in
+    /// which they have been drawn. Initially the z-index is `0`.
+    ///
+    /// The default implementation ignores the z-index, in which case elements simply appear in
+    /// the order in which they have been drawn.
+    #[inline]
+    fn set_z_index(&mut self, z_index: i32) {
+        let _ = z_index;
+    }
+
+    /// Sets the rectangle of the viewport outside of which the images and glyphs drawn afterwards
+    /// must not be visible.
+    ///
+    /// The rectangle is in the `[min_x, min_y, max_x, max_y]` format, in OpenGL viewport
+    /// coordinates. `None` means that nothing is clipped, which is the initial state. The
+    /// rectangle can be empty, in which case nothing is visible.
+    ///
+    /// The default implementation ignores the clip rectangle.
+    #[inline]
+    fn set_clip(&mut self, clip: Option<[f32; 4]>) {
+        let _ = clip;
+    }
 }
 
 /// Information about a single glyph.
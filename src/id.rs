@@ -6,7 +6,7 @@
 // copied, modified, or distributed except according to those terms.
 
 /// Represents an identifier of a widget. See the documentation of `DrawContext`.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct WidgetId(usize);
 
 impl From<usize> for WidgetId {
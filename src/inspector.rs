@@ -0,0 +1,60 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+/// Kind and bounds of an element that has been drawn, and of the elements drawn inside of it.
+///
+/// The nodes are recorded with `DrawContext::record_layout` and `DrawContext::layout_group` if
+/// the recording has been enabled with `SharedDrawContext::enable_layout_recording`. With the
+/// `serde` feature, the tree can be serialized and compared against a snapshot in order to test
+/// the layout of a UI.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LayoutNode {
+    /// What kind of element this is, for example `"button"` or `"label"`.
+    pub kind: String,
+
+    /// Smallest rectangle of the viewport that contains the element, in the same format as
+    /// `DrawContext::bounding_box`.
+    pub bounds: [f32; 4],
+
+    /// Nodes that have been recorded inside of this one, in draw order.
+    pub children: Vec<LayoutNode>,
+}
+
+/// Node that has been recorded during a frame, before the tree is built.
+pub struct RecordedNode {
+    /// Index in the list of the recorded nodes of the parent of this node.
+    pub parent: Option<usize>,
+    pub node: LayoutNode,
+}
+
+/// Builds the tree of nodes from the list of the nodes recorded during a frame.
+///
+/// The parent of a node must come before it in the list.
+pub fn build_tree(nodes: Vec<RecordedNode>) -> Vec<LayoutNode> {
+    let mut nodes: Vec<_> = nodes.into_iter().map(|n| (n.parent, Some(n.node))).collect();
+    let mut roots = Vec::new();
+
+    // Going backwards, all the children of a node have been moved into it when it is reached.
+    for index in (0 .. nodes.len()).rev() {
+        let node = nodes[index].1.take().unwrap();
+        match nodes[index].0 {
+            Some(parent) => nodes[parent].1.as_mut().unwrap().children.push(node),
+            None => roots.push(node),
+        }
+    }
+
+    fn reverse(nodes: &mut [LayoutNode]) {
+        nodes.reverse();
+        for node in nodes.iter_mut() {
+            reverse(&mut node.children);
+        }
+    }
+
+    reverse(&mut roots);
+    roots
+}
@@ -0,0 +1,34 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+/// An event coming from the keyboard.
+///
+/// You are expected to push the events that you receive from your windowing system in the
+/// `keyboard_events` field of the `UiState`. Widgets remove the events that they process.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KeyboardEvent {
+    /// A character has been typed.
+    Character(char),
+    /// The backspace key has been pressed.
+    Backspace,
+    /// The delete key has been pressed.
+    Delete,
+    /// The enter key has been pressed.
+    Enter,
+    /// The escape key has been pressed.
+    Escape,
+    /// The tab key has been pressed.
+    Tab,
+    /// The left arrow key has been pressed.
+    Left,
+    /// The right arrow key has been pressed.
+    Right,
+    /// The up arrow key has been pressed.
+    Up,
+    /// The down arrow key has been pressed.
+    Down,
+}
@@ -8,19 +8,36 @@
 use std::cell::Cell;
 use std::cell::RefCell;
 use std::cell::RefMut;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::mem;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
+use AccessibilityNode;
+use AccessibilityRole;
 use Draw;
+use KeyboardEvent;
+use LayoutNode;
 use Matrix;
+use UiState;
 use WidgetId;
 
+use animations;
+use animations::Animation;
+use animations::ColorAnimation;
+use animations::FullAnimation;
 use animations::Interpolation;
+use animations::Linear;
+use animations::Timeline;
+use inspector;
 
 /// Start drawing your UI.
 ///
@@ -30,10 +47,39 @@ pub fn draw() -> SharedDrawContext {
         shared1: Arc::new(Shared1 {
             next_widget_id: AtomicUsize::new(1),
             cursor_hovered_widget: AtomicBool::new(false),
+            seen_widgets: Mutex::new(HashSet::new()),
+            needs_redraw: AtomicBool::new(false),
+            hovered_widgets: Mutex::new(Vec::new()),
+            accessibility: Mutex::new(None),
+            layout: Mutex::new(None),
+            click_consumed: AtomicBool::new(false),
+            flip_cursor_y: AtomicBool::new(false),
+            first_appearances: Mutex::new(HashSet::new()),
+            frame_time: Mutex::new(None),
+            viewport_size: Mutex::new([0.0, 0.0]),
+            widget_bounds: Mutex::new(HashMap::new()),
+            bounds_recording: AtomicBool::new(false),
+            focusables: Mutex::new(Vec::new()),
         })
     }
 }
 
+/// Returns the smallest rectangle that contains all the given rectangles, or `None` if the list
+/// is empty.
+///
+/// The rectangles are in the `[min_x, min_y, max_x, max_y]` format returned by
+/// `DrawContext::bounding_box`.
+pub fn union_bounding_boxes(boxes: &[[f32; 4]]) -> Option<[f32; 4]> {
+    let first = match boxes.first() {
+        Some(b) => *b,
+        None => return None,
+    };
+
+    Some(boxes[1 ..].iter().fold(first, |a, b| {
+        [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])]
+    }))
+}
+
 /// A context shared between all draw contexts.
 pub struct SharedDrawContext {
     shared1: Arc<Shared1>,
@@ -44,22 +90,73 @@ impl SharedDrawContext {
     /// The cursor coordinates, if any, must be in OpenGL viewport coordinates. In other words,
     /// `[-1.0, -1.0]` corresponds to the bottom-left hand corner of the screen, and `[1.0, 1.0]`
     /// to the top-right hand corner.
+    ///
+    /// If your windowing system gives you coordinates where the y axis points downwards, call
+    /// `flip_cursor_y` before calling this function instead of negating them yourself. If your
+    /// renderer also has its y axis pointing downwards, set `UiState::y_axis` instead.
+    ///
+    /// The cursor pressure, if any, is the pressure of a stylus between `0.0` and `1.0`. It is
+    /// purely informative and can be read by widgets with `DrawContext::cursor_pressure`.
+    ///
+    /// The settings of the frame, like `UiState::ui_scale` or `UiState::y_axis`, are read from
+    /// `ui_state`.
+    ///
+    /// `now` is the time at which the frame is drawn. All the animations and time-dependant
+    /// widgets use this value instead of reading the system clock, which makes it possible to
+    /// drive them with a custom clock. Most of the time you should pass `SystemTime::now()`.
+    #[allow(clippy::too_many_arguments, clippy::redundant_field_names)]
     pub fn draw<'b, D: ?Sized + Draw + 'b>(&self, width: f32, height: f32, draw: &'b mut D,
-                                           cursor: Option<[f32; 2]>, cursor_was_pressed: bool,
-                                           cursor_was_released: bool) -> DrawContext<'b, D>
+                                           ui_state: &UiState, cursor: Option<[f32; 2]>,
+                                           cursor_was_pressed: bool, cursor_was_released: bool,
+                                           cursor_pressure: Option<f32>, now: SystemTime)
+                                           -> DrawContext<'b, D>
     {
+        let cursor = if self.shared1.flip_cursor_y.load(Ordering::Relaxed) {
+            cursor.map(|c| [c[0], -c[1]])
+        } else {
+            cursor
+        };
+
+        if cursor_was_pressed || cursor_was_released {
+            // The widgets are likely to change their state in reaction to the input, which is
+            // only visible at the next frame.
+            self.shared1.needs_redraw.store(true, Ordering::Relaxed);
+        }
+
+        *self.shared1.frame_time.lock().unwrap() = Some(now);
+        *self.shared1.viewport_size.lock().unwrap() = [width, height];
+
+        let matrix = if ui_state.y_axis == YAxis::Down {
+            Matrix::scale_wh(1.0, -1.0)
+        } else {
+            Matrix::identity()
+        };
+
         DrawContext {
-            matrix: Matrix::identity(),
+            matrix,
             width: width,
             height: height,
             animation: None,
             cursor: cursor,
             cursor_was_pressed: cursor_was_pressed,
             cursor_was_released: cursor_was_released,
+            tint: [1.0, 1.0, 1.0, 1.0],
+            z_index: 0,
+            clip: None,
+            hover_suppressed: false,
+            layout_parent: None,
+            visual: Matrix::identity(),
             shared1: self.shared1.clone(),
             shared2: Rc::new(Shared2 {
                 draw: RefCell::new(draw),
-                cursor_hovered_widget: Cell::new(false),
+                cursor_hovered_widget: Cell::new(None),
+                current_tint: Cell::new([1.0, 1.0, 1.0, 1.0]),
+                current_z_index: Cell::new(0),
+                current_clip: Cell::new(None),
+                cursor_pressure,
+                now,
+                viewport_size: [width, height],
+                ui_scale: valid_ui_scale(ui_state.ui_scale),
             }),
         }
     }
@@ -77,11 +174,411 @@ impl SharedDrawContext {
     pub fn cursor_hovered_widget(&self) -> bool {
         self.shared1.cursor_hovered_widget.load(Ordering::Relaxed)
     }
+
+    /// Returns the list of widgets that were under the mouse cursor during the frame, in the
+    /// order in which they were drawn.
+    ///
+    /// Contrary to `cursor_hovered_widget`, this contains every widget that has called
+    /// `DrawContext::note_hover`, including the ones that are hidden by other widgets. The last
+    /// element is the widget that was drawn last, which is usually the one on top.
+    #[inline]
+    pub fn hovered_widgets(&self) -> Vec<WidgetId> {
+        self.shared1.hovered_widgets.lock().unwrap().clone()
+    }
+
+    /// Indicates that the cursor coordinates passed to `draw` have their y axis pointing
+    /// downwards, in other words that `-1.0` is the top of the viewport and `1.0` the bottom.
+    /// The y coordinate is then negated before being used.
+    ///
+    /// Must be called before `draw`.
+    #[inline]
+    pub fn flip_cursor_y(&self) {
+        self.shared1.flip_cursor_y.store(true, Ordering::Relaxed);
+    }
+
+    /// Enables the recording of the bounds of all the widgets for this frame. See
+    /// `DrawContext::was_cursor_over_last_frame`.
+    ///
+    /// The recording is disabled by default, so that drawing a widget doesn't have to calculate
+    /// its bounding box. Only the bounds of the widgets passed to `DrawContext::record_bounds`
+    /// are recorded in that case.
+    #[inline]
+    pub fn enable_bounds_recording(&self) {
+        self.shared1.bounds_recording.store(true, Ordering::Relaxed);
+    }
+
+    /// Enables the collection of accessibility nodes for this frame. See `accessibility_tree`.
+    ///
+    /// The collection is disabled by default, so that the widgets don't have to allocate their
+    /// labels when no accessibility API is used.
+    #[inline]
+    pub fn enable_accessibility(&self) {
+        let mut nodes = self.shared1.accessibility.lock().unwrap();
+        if nodes.is_none() {
+            *nodes = Some(Vec::new());
+        }
+    }
+
+    /// Enables the recording of the layout of this frame. See `UiState::layout`.
+    ///
+    /// The recording is disabled by default. It is meant for inspecting the layout of a UI, for
+    /// example in order to compare it against a snapshot in a test.
+    #[inline]
+    pub fn enable_layout_recording(&self) {
+        let mut nodes = self.shared1.layout.lock().unwrap();
+        if nodes.is_none() {
+            *nodes = Some(Vec::new());
+        }
+    }
+
+    /// Returns the semantic description of the elements that have been drawn during the frame,
+    /// in the order in which they were drawn.
+    ///
+    /// The widgets of this library register themselves with `DrawContext::push_accessibility`.
+    /// Returns an empty list if `enable_accessibility` hasn't been called before drawing.
+    #[inline]
+    pub fn accessibility_tree(&self) -> Vec<AccessibilityNode> {
+        self.shared1.accessibility.lock().unwrap().clone().unwrap_or_default()
+    }
+
+    /// Returns true if the UI needs to be drawn again even if no event happens, for example
+    /// because an animation is in progress or because an input has been processed.
+    ///
+    /// If you only redraw your UI when an event happens, you should check this value at the end
+    /// of each frame and schedule a new frame if it is true.
+    #[inline]
+    pub fn needs_redraw(&self) -> bool {
+        self.shared1.needs_redraw.load(Ordering::Relaxed)
+    }
+
+    /// Returns the time elapsed between the previous frame and this one.
+    ///
+    /// This is the difference between the `now` passed to `draw` and the one of the last frame
+    /// that has been passed to `finish` with the same `UiState`. Returns zero if `draw` hasn't
+    /// been called yet, for the first frame, or if the clock went backwards.
+    #[inline]
+    pub fn delta_time(&self, ui_state: &UiState) -> Duration {
+        let now = *self.shared1.frame_time.lock().unwrap();
+        delta_time(now, ui_state.last_frame_time)
+    }
+
+    /// Performs the bookkeeping at the end of a frame. You should call this function once per
+    /// frame, after you have finished drawing your UI.
+    ///
+    /// This function:
+    ///
+    /// - Moves the focus between the widgets registered with `register_focusable_grid`
+    ///   according to the arrow keys that no widget has processed.
+    /// - Clears the keyboard events and the scrolling that no widget has processed, so that they
+    ///   don't leak to the next frame.
+    /// - Clears the active and the focused widgets if they haven't been drawn during this frame.
+    ///   This happens for example if a button stops being drawn while the user is pressing it.
+    /// - Forgets when the widgets that haven't been drawn during this frame were last hovered and
+    ///   first drawn.
+    /// - Stores the recorded layout of the frame in `ui_state.layout`.
+    /// - Stores the time of the frame in `ui_state.last_frame_time`, for `delta_time`.
+    /// - Stores the recorded bounds of the widgets in `ui_state.last_frame_bounds`.
+    ///
+    /// Returns a summary of what happened during the frame.
+    pub fn finish(self, ui_state: &mut UiState) -> FrameSummary {
+        {
+            let focusables = self.shared1.focusables.lock().unwrap();
+            let bounds = self.shared1.widget_bounds.lock().unwrap();
+            let viewport = *self.shared1.viewport_size.lock().unwrap();
+            let y_axis_down = ui_state.y_axis == YAxis::Down;
+            for event in ui_state.keyboard_events.iter() {
+                let focused = ui_state.focused_widget.as_ref();
+                if let Some(id) = next_focus(&focusables, &bounds, viewport, y_axis_down, focused,
+                                             event)
+                {
+                    ui_state.focused_widget = Some(id);
+                }
+            }
+        }
+
+        ui_state.keyboard_events.clear();
+
+        {
+            let seen_widgets = self.shared1.seen_widgets.lock().unwrap();
+            let stale = match ui_state.active_widget {
+                Some(ref id) => !seen_widgets.contains(id),
+                None => false,
+            };
+
+            if stale {
+                ui_state.active_widget = None;
+            }
+
+            let stale = match ui_state.focused_widget {
+                Some(ref id) => !seen_widgets.contains(id),
+                None => false,
+            };
+
+            if stale {
+                ui_state.focused_widget = None;
+            }
+
+            ui_state.hover_times.retain(|id, _| seen_widgets.contains(id));
+            ui_state.appear_times.retain(|id, _| seen_widgets.contains(id));
+        }
+
+        ui_state.scroll_delta = [0.0, 0.0];
+
+        if let Some(now) = *self.shared1.frame_time.lock().unwrap() {
+            ui_state.last_frame_time = Some(now);
+        }
+
+        ui_state.last_frame_bounds = mem::take(&mut *self.shared1.widget_bounds.lock().unwrap());
+
+        ui_state.layout = match self.shared1.layout.lock().unwrap().take() {
+            Some(nodes) => inspector::build_tree(nodes),
+            None => Vec::new(),
+        };
+
+        FrameSummary {
+            cursor_hovered_widget: self.cursor_hovered_widget(),
+            needs_redraw: self.needs_redraw(),
+        }
+    }
+}
+
+/// Summary of a frame. Returned by `SharedDrawContext::finish`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameSummary {
+    /// True if one of the elements that has been drawn was under the mouse cursor. See
+    /// `SharedDrawContext::cursor_hovered_widget`.
+    pub cursor_hovered_widget: bool,
+
+    /// True if the UI needs to be drawn again. See `SharedDrawContext::needs_redraw`.
+    pub needs_redraw: bool,
 }
 
 struct Shared1 {
     next_widget_id: AtomicUsize,
     cursor_hovered_widget: AtomicBool,
+
+    /// List of the widgets that have been drawn during the frame.
+    seen_widgets: Mutex<HashSet<WidgetId>>,
+
+    /// True if the UI must be drawn again, even if no event happens.
+    needs_redraw: AtomicBool,
+
+    /// List of the widgets that were under the cursor during the frame, in draw order.
+    hovered_widgets: Mutex<Vec<WidgetId>>,
+
+    /// If the collection of accessibility nodes is enabled, contains the nodes of the frame.
+    accessibility: Mutex<Option<Vec<AccessibilityNode>>>,
+
+    /// If the recording of the layout is enabled, contains the nodes of the frame in draw order.
+    layout: Mutex<Option<Vec<inspector::RecordedNode>>>,
+
+    /// True if a widget has claimed the press of the cursor during the frame.
+    click_consumed: AtomicBool,
+
+    /// True if the y coordinate of the cursor passed to `draw` must be negated.
+    flip_cursor_y: AtomicBool,
+
+    /// List of the widgets that have been drawn for the first time during the frame.
+    first_appearances: Mutex<HashSet<WidgetId>>,
+
+    /// Time passed to the last call to `draw`.
+    frame_time: Mutex<Option<SystemTime>>,
+
+    /// Width and height in pixels passed to the last call to `draw`.
+    viewport_size: Mutex<[f32; 2]>,
+
+    /// Bounding box of the widgets that have been drawn during the frame and whose bounds have
+    /// been recorded.
+    widget_bounds: Mutex<HashMap<WidgetId, [f32; 4]>>,
+
+    /// True if `reserve_widget_id` records the bounds of all the widgets.
+    bounds_recording: AtomicBool,
+
+    /// Widgets between which the arrow keys move the focus, in draw order.
+    focusables: Mutex<Vec<WidgetId>>,
+}
+
+/// Returns the widget that receives the focus when the given key is pressed, if any.
+///
+/// Only the arrow keys move the focus. If no widget is focused, the first widget gets the focus.
+/// Otherwise the focus goes to the closest widget whose center is in the direction of the key,
+/// where moving sideways costs twice as much as moving in the direction of the key.
+///
+/// The bounds are in viewport coordinates, whose y axis points downwards if `y_axis_down` is
+/// true. The distances are measured in pixels, using the size of the viewport, so that they
+/// don't depend on its aspect ratio.
+fn next_focus(focusables: &[WidgetId], bounds: &HashMap<WidgetId, [f32; 4]>, viewport: [f32; 2],
+              y_axis_down: bool, focused: Option<&WidgetId>, event: &KeyboardEvent)
+              -> Option<WidgetId>
+{
+    let direction = match *event {
+        KeyboardEvent::Left => [-1.0, 0.0],
+        KeyboardEvent::Right => [1.0, 0.0],
+        KeyboardEvent::Up => [0.0, 1.0],
+        KeyboardEvent::Down => [0.0, -1.0],
+        _ => return None,
+    };
+
+    // Turns the viewport coordinates into pixels where the y axis points upwards.
+    let scale = [viewport[0] * 0.5, if y_axis_down { -0.5 } else { 0.5 } * viewport[1]];
+    let center = |id: &WidgetId| {
+        bounds.get(id).map(|b| [(b[0] + b[2]) * 0.5 * scale[0], (b[1] + b[3]) * 0.5 * scale[1]])
+    };
+
+    let from = match focused.and_then(&center) {
+        Some(c) => c,
+        None => return focusables.first().cloned(),
+    };
+
+    let mut best: Option<(f32, &WidgetId)> = None;
+    for id in focusables {
+        if Some(id) == focused {
+            continue;
+        }
+
+        let to = match center(id) {
+            Some(c) => c,
+            None => continue,
+        };
+
+        let dx = to[0] - from[0];
+        let dy = to[1] - from[1];
+        let forward = dx * direction[0] + dy * direction[1];
+        let sideways = (dx * direction[1] - dy * direction[0]).abs();
+        if forward <= 0.01 {
+            continue;
+        }
+
+        let score = forward + 2.0 * sideways;
+        if best.map(|(s, _)| score < s).unwrap_or(true) {
+            best = Some((score, id));
+        }
+    }
+
+    best.map(|(_, id)| id.clone())
+}
+
+/// Returns the time elapsed between two frames, or zero if one of them is missing or if the
+/// clock went backwards.
+#[inline]
+fn delta_time(now: Option<SystemTime>, previous: Option<SystemTime>) -> Duration {
+    match (now, previous) {
+        (Some(now), Some(previous)) => now.duration_since(previous).unwrap_or_default(),
+        _ => Duration::new(0, 0),
+    }
+}
+
+/// Calculates whether the point is in a rectangle multiplied by a matrix.
+///
+/// Most contexts are only translated and scaled, in which case a cheaper bounding box test is
+/// performed.
+#[inline]
+fn is_point_in_rectangle(matrix: &Matrix, point: &[f32; 2]) -> bool {
+    if is_axis_aligned(matrix) {
+        is_point_in_axis_aligned_rectangle(matrix, point)
+    } else {
+        is_point_in_transformed_rectangle(matrix, point)
+    }
+}
+
+/// Returns true if the matrix doesn't contain any rotation or skew.
+#[inline]
+fn is_axis_aligned(matrix: &Matrix) -> bool {
+    matrix.0[0][1].abs() < 1e-6 && matrix.0[1][0].abs() < 1e-6
+}
+
+/// Same as `is_point_in_transformed_rectangle`, but the matrix must be axis-aligned.
+#[inline]
+fn is_point_in_axis_aligned_rectangle(matrix: &Matrix, point: &[f32; 2]) -> bool {
+    let half_width = matrix.0[0][0].abs();
+    let half_height = matrix.0[1][1].abs();
+    let center = matrix.0[2];
+
+    point[0] >= center[0] - half_width && point[0] <= center[0] + half_width &&
+    point[1] >= center[1] - half_height && point[1] <= center[1] + half_height
+}
+
+/// Returns the positions of the corners of a fullscreen rectangle multiplied by a matrix, in the
+/// top-left, top-right, bottom-right, bottom-left order.
+#[inline]
+fn transformed_corners(matrix: &Matrix) -> [[f32; 2]; 4] {
+    let corner = |x: f32, y: f32| {
+        let output = *matrix * [x, y, 1.0];
+        [output[0] / output[2], output[1] / output[2]]
+    };
+
+    [corner(-1.0, 1.0), corner(1.0, 1.0), corner(1.0, -1.0), corner(-1.0, -1.0)]
+}
+
+/// Calculates whether the point is in a rectangle multiplied by a matrix. Works with any matrix.
+fn is_point_in_transformed_rectangle(matrix: &Matrix, point: &[f32; 2]) -> bool {
+    // We start by calculating the positions of the four corners of the shape in viewport
+    // coordinates, so that they can be compared with the point which is already in
+    // viewport coordinates.
+    let [top_left, top_right, bot_right, bot_left] = transformed_corners(matrix);
+
+    // The point is within our rectangle if and only if it is on the right side of each
+    // border of the rectangle (taken in the right order).
+    //
+    // To check this, we calculate the dot product of the vector `point - corner` with
+    // `next_corner - corner`. If the value is positive, then the angle is inferior to
+    // 90°. If the the value is negative, the angle is superior to 90° and we know that
+    // the cursor is outside of the rectangle.
+
+    if (point[0] - top_left[0]) * (top_right[0] - top_left[0]) +
+       (point[1] - top_left[1]) * (top_right[1] - top_left[1]) < 0.0
+    {
+        return false;
+    }
+
+    if (point[0] - top_right[0]) * (bot_right[0] - top_right[0]) +
+       (point[1] - top_right[1]) * (bot_right[1] - top_right[1]) < 0.0
+    {
+        return false;
+    }
+
+    if (point[0] - bot_right[0]) * (bot_left[0] - bot_right[0]) +
+       (point[1] - bot_right[1]) * (bot_left[1] - bot_right[1]) < 0.0
+    {
+        return false;
+    }
+
+    if (point[0] - bot_left[0]) * (top_left[0] - bot_left[0]) +
+       (point[1] - bot_left[1]) * (top_left[1] - bot_left[1]) < 0.0
+    {
+        return false;
+    }
+
+    true
+}
+
+/// Returns the size of a strip of `strip_px` pixels, as a percentage of `total_px`. The result is
+/// between `0.0` and `1.0`.
+#[inline]
+fn strip_percent(strip_px: f32, total_px: f32) -> f32 {
+    if total_px <= 0.0 || strip_px >= total_px {
+        1.0
+    } else if strip_px <= 0.0 {
+        0.0
+    } else {
+        strip_px / total_px
+    }
+}
+
+/// Replaces NaN and infinite values with `0.0`.
+///
+/// Used for the percentages and the weights passed by the user, so that a division by zero on
+/// their side produces an empty context instead of a matrix full of NaNs.
+#[inline]
+fn finite_or_zero(value: f32) -> f32 {
+    if value.is_finite() { value } else { 0.0 }
+}
+
+/// Returns the UI scale to use for the given `UiState::ui_scale`, which is `1.0` if it is NaN,
+/// infinite or not positive.
+#[inline]
+fn valid_ui_scale(scale: f32) -> f32 {
+    if scale.is_finite() && scale > 0.0 { scale } else { 1.0 }
 }
 
 /// Contains everything required to draw a widget.
@@ -106,27 +603,95 @@ pub struct DrawContext<'b, D: ?Sized + Draw + 'b> {
 
     cursor_was_pressed: bool,
     cursor_was_released: bool,
+
+    /// Color that everything drawn with this context is multiplied with.
+    tint: [f32; 4],
+
+    /// Layer in which everything drawn with this context is drawn.
+    z_index: i32,
+
+    /// Rectangle of the viewport outside of which nothing drawn with this context is visible.
+    clip: Option<[f32; 4]>,
+
+    /// If true, `set_cursor_hovered_widget` does nothing.
+    hover_suppressed: bool,
+
+    /// Index in the recorded layout of the node that contains what is drawn with this context.
+    layout_parent: Option<usize>,
+
+    /// Transformation in viewport coordinates that is applied when drawing, but not when
+    /// testing whether the cursor hovers the context.
+    visual: Matrix,
 }
 
 struct Shared2<'a, D: ?Sized + Draw + 'a> {
     draw: RefCell<&'a mut D>,
 
-    /// True if the cursor is over an element of the UI.
-    cursor_hovered_widget: Cell<bool>,
+    /// If the cursor is over an element of the UI, contains the highest z-index of these
+    /// elements.
+    cursor_hovered_widget: Cell<Option<i32>>,
+
+    /// Tint that was last passed to the `draw` object.
+    current_tint: Cell<[f32; 4]>,
+
+    /// Z-index that was last passed to the `draw` object.
+    current_z_index: Cell<i32>,
+
+    /// Clip rectangle that was last passed to the `draw` object.
+    current_clip: Cell<Option<[f32; 4]>>,
+
+    /// Pressure of the stylus, if available.
+    cursor_pressure: Option<f32>,
+
+    /// Time at which the frame is drawn.
+    now: SystemTime,
+
+    /// Width and height of the viewport in pixels.
+    viewport_size: [f32; 2],
+
+    /// Factor by which the sizes in pixels are multiplied.
+    ui_scale: f32,
 }
 
 impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
     /// UNSTABLE. Obtains the underlying `draw` object.
     #[inline]
     #[doc(hidden)]
+    #[allow(mismatched_lifetime_syntaxes)]
     pub fn draw(&self) -> RefMut<&'b mut D> {
-        self.shared2.draw.borrow_mut()
+        let mut draw = self.shared2.draw.borrow_mut();
+
+        if self.shared2.current_tint.get() != self.tint {
+            draw.set_tint(self.tint);
+            self.shared2.current_tint.set(self.tint);
+        }
+
+        if self.shared2.current_z_index.get() != self.z_index {
+            draw.set_z_index(self.z_index);
+            self.shared2.current_z_index.set(self.z_index);
+        }
+
+        if self.shared2.current_clip.get() != self.clip {
+            draw.set_clip(self.clip);
+            self.shared2.current_clip.set(self.clip);
+        }
+
+        draw
     }
 
     /// Returns a matrix that turns a fullscreen rectangle into a rectangle that covers only the
     /// context's area.
     #[inline]
     pub fn matrix(&self) -> Matrix {
+        if self.visual == Matrix::identity() {
+            self.logical_matrix()
+        } else {
+            self.visual * self.logical_matrix()
+        }
+    }
+
+    /// Same as `matrix()`, but ignores the transformations that are only visual.
+    fn logical_matrix(&self) -> Matrix {
         if let Some((matrix, percent)) = self.animation {
             // TODO: correct decomposition with https://drafts.csswg.org/css-transforms/#decomposing-a-2d-matrix
 
@@ -147,12 +712,57 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
         }
     }
 
+    /// Returns the color that everything drawn with this context is multiplied with.
+    ///
+    /// The color is in RGBA format. It is `[1.0, 1.0, 1.0, 1.0]` unless you called `with_tint`.
+    #[inline]
+    pub fn tint(&self) -> [f32; 4] {
+        self.tint
+    }
+
+    /// Returns the opacity of everything drawn with this context, which is the alpha component
+    /// of the tint.
+    ///
+    /// A context whose opacity is almost zero is invisible, and the cursor can't hover it. This
+    /// makes widgets that are fading out click-through.
+    #[inline]
+    pub fn opacity(&self) -> f32 {
+        self.tint[3]
+    }
+
+    /// Returns true if the context is so transparent that it can't be seen.
+    #[inline]
+    fn is_invisible(&self) -> bool {
+        self.tint[3] < 0.01
+    }
+
     /// Returns true if the cursor went from up to down in the current frame.
     ///
-    /// This is the value that was passed when constructing the context.
+    /// This is the value that was passed when constructing the context, unless a widget has
+    /// already claimed the press with `consume_click`, in which case this returns false.
     #[inline]
     pub fn cursor_was_pressed(&self) -> bool {
-        self.cursor_was_pressed
+        self.cursor_was_pressed && !self.shared1.click_consumed.load(Ordering::Relaxed)
+    }
+
+    /// Claims the press of the cursor of the current frame, so that the widgets drawn afterwards
+    /// see `cursor_was_pressed()` as false.
+    ///
+    /// The widgets of this library call this function when they become the active widget. This
+    /// ensures that a single widget reacts to a press, for example that pressing a button
+    /// doesn't also start dragging what is under it.
+    #[inline]
+    pub fn consume_click(&self) {
+        self.shared1.click_consumed.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the scrolling that hasn't been processed yet and resets `ui_state.scroll_delta`,
+    /// so that the widgets drawn afterwards don't scroll as well.
+    #[inline]
+    pub fn consume_scroll(&self, ui_state: &mut UiState) -> [f32; 2] {
+        let delta = ui_state.scroll_delta;
+        ui_state.scroll_delta = [0.0, 0.0];
+        delta
     }
 
     /// Returns true if the cursor went from down to up in the current frame.
@@ -163,136 +773,529 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
         self.cursor_was_released
     }
 
+    /// Returns the pressure of the stylus between `0.0` and `1.0`, or `None` if it is not
+    /// available.
+    ///
+    /// This is the value that was passed when constructing the context.
+    #[inline]
+    pub fn cursor_pressure(&self) -> Option<f32> {
+        self.shared2.cursor_pressure
+    }
+
+    /// Returns the time at which the frame is drawn.
+    ///
+    /// This is the value that was passed when constructing the context. Widgets should use this
+    /// instead of `SystemTime::now()`.
+    #[inline]
+    pub fn now(&self) -> SystemTime {
+        self.shared2.now
+    }
+
+    /// Returns the time elapsed since the previous frame. See `SharedDrawContext::delta_time`.
+    ///
+    /// Widgets that integrate a movement over time, like springs, should use this value so that
+    /// they all advance by the same amount.
+    #[inline]
+    pub fn delta_time(&self, ui_state: &UiState) -> Duration {
+        delta_time(Some(self.now()), ui_state.last_frame_time)
+    }
+
     /// Returns true if one of the elements that has been drawn is under the mouse cursor.
     ///
     /// When you create the context, this value is initally false. Each widget that you draw can
     /// call `set_cursor_hovered_widget` to pass this value to true.
     #[inline]
     pub fn cursor_hovered_widget(&self) -> bool {
-        self.shared2.cursor_hovered_widget.get()
+        self.shared2.cursor_hovered_widget.get().is_some()
     }
 
     /// Signals the context that the cursor is currently hovering it. This can be later retreived
     /// with `cursor_hovered_widget()`.
+    ///
+    /// Does nothing if the context was built with `suppress_hover`, or if its opacity is almost
+    /// zero.
     #[inline]
     pub fn set_cursor_hovered_widget(&self) {
+        if self.hover_suppressed || self.is_invisible() {
+            return;
+        }
+
         self.shared1.cursor_hovered_widget.store(true, Ordering::Relaxed);
-        self.shared2.cursor_hovered_widget.set(true);
+
+        let z_index = match self.shared2.cursor_hovered_widget.get() {
+            Some(z) if z > self.z_index => z,
+            _ => self.z_index,
+        };
+        self.shared2.cursor_hovered_widget.set(Some(z_index));
     }
 
-    /// Reserves a new ID for a widget. Calling this function multiple times always returns
-    /// a different id.
-    #[inline]
-    pub fn reserve_widget_id(&self) -> WidgetId {
-        self.shared1.next_widget_id.fetch_add(1, Ordering::Relaxed).into()
+    /// Describes the area of this context for the accessibility APIs of the platform.
+    ///
+    /// Does nothing unless `SharedDrawContext::enable_accessibility` has been called. The bounds
+    /// of the node are the `bounding_box` of the context. The labels drawn over a button are
+    /// separate nodes that follow the node of the button.
+    pub fn push_accessibility(&self, role: AccessibilityRole, label: &str) {
+        let mut nodes = self.shared1.accessibility.lock().unwrap();
+        if let Some(ref mut nodes) = *nodes {
+            nodes.push(AccessibilityNode {
+                role,
+                label: label.to_owned(),
+                bounds: self.bounding_box(),
+            });
+        }
     }
 
-    /// Returns true if the cursor is currently hovering this part of the viewport.
+    /// Records an element of the given kind whose bounds are the `bounding_box` of this context.
+    /// See `SharedDrawContext::enable_layout_recording`.
     ///
-    /// This is equivalent to `cursor_hover_coordinates().is_some()`, except more optimized.
+    /// The node is a child of the last group of this context, see `layout_group`. Does nothing
+    /// unless the recording is enabled.
     #[inline]
-    pub fn is_cursor_hovering(&self) -> bool {
-        /// Calculates whether the point is in a rectangle multiplied by a matrix.
-        fn test(matrix: &Matrix, point: &[f32; 2]) -> bool {
-            // We start by calculating the positions of the four corners of the shape in viewport
-            // coordinates, so that they can be compared with the point which is already in
-            // viewport coordinates.
-
-            let top_left = *matrix * [-1.0, 1.0, 1.0];
-            let top_left = [top_left[0] / top_left[2], top_left[1] / top_left[2]];
-
-            let top_right = *matrix * [1.0, 1.0, 1.0];
-            let top_right = [top_right[0] / top_right[2], top_right[1] / top_right[2]];
-
-            let bot_left = *matrix * [-1.0, -1.0, 1.0];
-            let bot_left = [bot_left[0] / bot_left[2], bot_left[1] / bot_left[2]];
-
-            let bot_right = *matrix * [1.0, -1.0, 1.0];
-            let bot_right = [bot_right[0] / bot_right[2], bot_right[1] / bot_right[2]];
-
-            // The point is within our rectangle if and only if it is on the right side of each
-            // border of the rectangle (taken in the right order).
-            //
-            // To check this, we calculate the dot product of the vector `point - corner` with
-            // `next_corner - corner`. If the value is positive, then the angle is inferior to
-            // 90°. If the the value is negative, the angle is superior to 90° and we know that
-            // the cursor is outside of the rectangle.
-
-            if (point[0] - top_left[0]) * (top_right[0] - top_left[0]) +
-               (point[1] - top_left[1]) * (top_right[1] - top_left[1]) < 0.0
-            {
-                return false;
-            }
-
-            if (point[0] - top_right[0]) * (bot_right[0] - top_right[0]) +
-               (point[1] - top_right[1]) * (bot_right[1] - top_right[1]) < 0.0
-            {
-                return false;
-            }
-
-            if (point[0] - bot_right[0]) * (bot_left[0] - bot_right[0]) +
-               (point[1] - bot_right[1]) * (bot_left[1] - bot_right[1]) < 0.0
-            {
-                return false;
-            }
-
-            if (point[0] - bot_left[0]) * (top_left[0] - bot_left[0]) +
-               (point[1] - bot_left[1]) * (top_left[1] - bot_left[1]) < 0.0
-            {
-                return false;
-            }
+    pub fn record_layout(&self, kind: &str) {
+        self.push_layout(kind);
+    }
 
-            true
+    /// Records an element of the given kind, like `record_layout`, and builds a new draw context
+    /// identical to this one except that the elements recorded with it are children of this
+    /// element.
+    ///
+    /// This is useful for widgets that contain other widgets, like a panel.
+    pub fn layout_group(&self, kind: &str) -> DrawContext<'b, D> {
+        let mut ctxt = self.clone();
+        if let Some(index) = self.push_layout(kind) {
+            ctxt.layout_parent = Some(index);
         }
+        ctxt
+    }
 
-        if let Some(cursor) = self.cursor {
-            test(&self.matrix(), &cursor)
+    /// Records a layout node and returns its index, or returns `None` if the recording is
+    /// disabled.
+    fn push_layout(&self, kind: &str) -> Option<usize> {
+        let mut nodes = self.shared1.layout.lock().unwrap();
+        if let Some(ref mut nodes) = *nodes {
+            nodes.push(inspector::RecordedNode {
+                parent: self.layout_parent,
+                node: LayoutNode {
+                    kind: kind.to_owned(),
+                    bounds: self.bounding_box(),
+                    children: Vec::new(),
+                },
+            });
+            Some(nodes.len() - 1)
         } else {
-            false
+            None
         }
     }
 
-    /// If the cursor is hovering the context, returns the coordinates of the cursor within the
-    /// context.
+    /// Builds a new draw context in which `set_cursor_hovered_widget` does nothing.
     ///
-    /// The result is in OpenGL-like coordinates. In other words, (-1,-1) is the bottom-left hand
-    /// corner and (1,1) is the top-right hand corner.
-    pub fn cursor_hover_coordinates(&self) -> Option<[f32; 2]> {
-        // we compute the inverse of the matrix
-        let m = match self.matrix().invert() {
-            Some(m) => m,
-            None => return None,
-        };
-
-        // and use it to calculate the position of the cursor within the current context
-        let in_pos = match self.cursor {
-            Some(p) => p,
-            None => return None,
-        };
+    /// This is useful for composite widgets that want to decide themselves whether they count as
+    /// hovered, instead of letting each of their images and labels report it. The widget can
+    /// call `set_cursor_hovered_widget` on the original context.
+    #[inline]
+    pub fn suppress_hover(&self) -> DrawContext<'b, D> {
+        let mut ctxt = self.clone();
+        ctxt.hover_suppressed = true;
+        ctxt
+    }
 
-        let output_mouse = [
-            in_pos[0]*m[0][0] + in_pos[1]*m[1][0] + m[2][0],
-            in_pos[0]*m[0][1] + in_pos[1]*m[1][1] + m[2][1],
-            in_pos[0]*m[0][2] + in_pos[1]*m[1][2] + m[2][2],
-        ];
+    /// Builds a new draw context in which the cursor is at the given position, in viewport
+    /// coordinates, or absent if `None`.
+    ///
+    /// Everything drawn through the new context reacts to this position instead of the real
+    /// cursor, for example when drawing a preview of an element that is being dragged. The
+    /// presses and releases of the cursor, and the rest of the state of the frame, are shared
+    /// with the original context.
+    #[inline]
+    pub fn with_cursor(&self, cursor: Option<[f32; 2]>) -> DrawContext<'b, D> {
+        let mut ctxt = self.clone();
+        ctxt.cursor = cursor;
+        ctxt
+    }
 
-        let output_mouse = [output_mouse[0] / output_mouse[2], output_mouse[1] / output_mouse[2]];
+    /// Returns whether the cursor is hovering the context, and if so whether another element
+    /// that is above this one has been hovered.
+    ///
+    /// An element is considered above this one if it has a higher z-index and has called
+    /// `set_cursor_hovered_widget`. Calling `set_cursor_hovered_widget` on this context or on
+    /// another element with the same z-index doesn't occlude it. Since only the elements that
+    /// have already been drawn are known, widgets that are below others should be drawn after
+    /// them, or use a higher z-index for the elements above.
+    ///
+    /// Widgets can for example show that they are hovered when they are `HoveredOccluded`, but
+    /// only react to clicks when they are `HoveredTop`.
+    pub fn hover_status(&self) -> HoverStatus {
+        if !self.is_cursor_hovering() {
+            return HoverStatus::NotHovered;
+        }
 
-        if output_mouse[0] < -1.0 || output_mouse[0] > 1.0 || output_mouse[0] != output_mouse[0] ||
-           output_mouse[1] < -1.0 || output_mouse[1] > 1.0 || output_mouse[1] != output_mouse[1]
-        {
-            return None;
+        match self.shared2.cursor_hovered_widget.get() {
+            Some(z) if z > self.z_index => HoverStatus::HoveredOccluded,
+            _ => HoverStatus::HoveredTop,
         }
+    }
 
-        Some(output_mouse)
+    /// Signals the context that the cursor is hovering the widget with the given id. The widget
+    /// can later be found in `SharedDrawContext::hovered_widgets()`.
+    ///
+    /// Widgets should call this function in addition to `set_cursor_hovered_widget`. Calling it
+    /// multiple times with the same id has no additional effect.
+    pub fn note_hover(&self, id: &WidgetId) {
+        let mut hovered_widgets = self.shared1.hovered_widgets.lock().unwrap();
+        if !hovered_widgets.contains(id) {
+            hovered_widgets.push(id.clone());
+        }
     }
 
-    /// Returns the ratio of the width of the surface divided by its height.
+    /// Signals that the UI needs to be redrawn, for example because something is moving. This can
+    /// be later retreived with `SharedDrawContext::needs_redraw()`.
+    ///
+    /// The animation functions of this context automatically call this function while the
+    /// animation is running.
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.shared1.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
+    /// Reserves a new ID for a widget. Calling this function multiple times always returns
+    /// a different id.
+    ///
+    /// If `SharedDrawContext::enable_bounds_recording` has been called, the bounding box of the
+    /// context is recorded as the bounds of the widget, see `was_cursor_over_last_frame`.
+    #[inline]
+    pub fn reserve_widget_id(&self) -> WidgetId {
+        let id: WidgetId = self.shared1.next_widget_id.fetch_add(1, Ordering::Relaxed).into();
+        self.shared1.seen_widgets.lock().unwrap().insert(id.clone());
+        if self.shared1.bounds_recording.load(Ordering::Relaxed) {
+            self.record_bounds(&id);
+        }
+        id
+    }
+
+    /// Records the bounding box of this context as the bounds of the given widget, replacing
+    /// the ones recorded by `reserve_widget_id`, if any.
+    ///
+    /// The bounds are recorded even if `SharedDrawContext::enable_bounds_recording` hasn't been
+    /// called, which makes it possible to only pay for the widgets that need it. Widgets that
+    /// reserve their id with a context that is larger than the area they cover should also call
+    /// this function with the context of that area.
+    #[inline]
+    pub fn record_bounds(&self, id: &WidgetId) {
+        let bounds = self.bounding_box();
+        self.shared1.widget_bounds.lock().unwrap().insert(id.clone(), bounds);
+    }
+
+    /// Registers a widget between which and the other registered widgets the focus can be moved
+    /// with the arrow keys, and records the bounds of this context as its bounds.
+    ///
+    /// The focus is stored in `ui_state.focused_widget`, and is moved by
+    /// `SharedDrawContext::finish` according to the arrow keys that no widget has processed.
+    /// The new focus is therefore visible at the next frame. The widgets are compared by the
+    /// centers of their bounds, which makes this suitable for grids of icons.
+    #[inline]
+    pub fn register_focusable_grid(&self, id: &WidgetId) {
+        self.record_bounds(id);
+        self.shared1.focusables.lock().unwrap().push(id.clone());
+    }
+
+    /// Returns true if the cursor is over the bounds that the widget with the given id had
+    /// during the previous frame.
+    ///
+    /// Since the id of a widget is only known once it is drawn, this makes it possible to route
+    /// an event to the widget that was under the cursor before the layout of the current frame
+    /// changes, for example when a press causes the widget to move. The bounds are the ones
+    /// stored by `SharedDrawContext::finish` in `ui_state.last_frame_bounds`, and are
+    /// axis-aligned even if the widget was rotated. Returns false if there is no cursor or if
+    /// the bounds of the widget weren't recorded, see `SharedDrawContext::enable_bounds_recording`.
+    pub fn was_cursor_over_last_frame(&self, ui_state: &UiState, id: &WidgetId) -> bool {
+        let cursor = match self.cursor {
+            Some(c) => c,
+            None => return false,
+        };
+
+        match ui_state.last_frame_bounds.get(id) {
+            Some(b) => cursor[0] >= b[0] && cursor[0] <= b[2] &&
+                       cursor[1] >= b[1] && cursor[1] <= b[3],
+            None => false,
+        }
+    }
+
+    /// Returns true if the cursor is hovering this part of the viewport, or if it was hovering
+    /// the widget with the given id less than `ui_state.hover_grace` ago.
+    ///
+    /// Contrary to `is_cursor_hovering`, this function remembers when the widget was last
+    /// hovered. Widgets that should stay highlighted or open for a short while after the cursor
+    /// leaves them should use this function.
+    pub fn is_hovered(&self, ui_state: &mut UiState, id: &WidgetId) -> bool {
+        let now = self.now();
+
+        if self.is_cursor_hovering() {
+            ui_state.hover_times.insert(id.clone(), now);
+            return true;
+        }
+
+        let last_hovered = match ui_state.hover_times.get(id) {
+            Some(time) => *time,
+            None => return false,
+        };
+
+        let within_grace = match now.duration_since(last_hovered) {
+            Ok(elapsed) => elapsed < ui_state.hover_grace,
+            Err(_) => true,
+        };
+
+        if within_grace {
+            // The hover state must be cleared once the grace period is over.
+            self.request_redraw();
+        } else {
+            ui_state.hover_times.remove(id);
+        }
+
+        within_grace
+    }
+
+    /// Returns true if the cursor is currently hovering this part of the viewport.
+    ///
+    /// This is equivalent to `cursor_hover_coordinates().is_some()`, except more optimized.
+    /// Always returns false if the context is empty or if its opacity is almost zero.
+    #[inline]
+    pub fn is_cursor_hovering(&self) -> bool {
+        if self.is_empty() || self.is_invisible() {
+            return false;
+        }
+
+        if let Some(cursor) = self.cursor {
+            is_point_in_rectangle(&self.logical_matrix(), &cursor)
+        } else {
+            false
+        }
+    }
+
+    /// If the cursor is hovering the context, returns the coordinates of the cursor within the
+    /// context.
+    ///
+    /// The result is in OpenGL-like coordinates. In other words, (-1,-1) is the bottom-left hand
+    /// corner and (1,1) is the top-right hand corner.
+    pub fn cursor_hover_coordinates(&self) -> Option<[f32; 2]> {
+        if self.is_invisible() {
+            return None;
+        }
+
+        let in_pos = self.cursor?;
+        let output_mouse = self.viewport_to_local(in_pos)?;
+
+        if output_mouse[0] < -1.0 || output_mouse[0] > 1.0 || output_mouse[0].is_nan() ||
+           output_mouse[1] < -1.0 || output_mouse[1] > 1.0 || output_mouse[1].is_nan()
+        {
+            return None;
+        }
+
+        Some(output_mouse)
+    }
+
+    /// Returns true if the cursor is hovering the ellipse inscribed in this context.
+    ///
+    /// Round widgets like knobs should use this instead of `is_cursor_hovering`, so that the
+    /// corners of the context aren't clickable.
+    #[inline]
+    pub fn is_cursor_hovering_ellipse(&self) -> bool {
+        match self.cursor_hover_coordinates() {
+            Some(c) => c[0] * c[0] + c[1] * c[1] <= 1.0,
+            None => false,
+        }
+    }
+
+    /// If the cursor is hovering the ellipse inscribed in this context, returns the angle in
+    /// radians between the center of the context and the cursor.
+    ///
+    /// The angle is in local coordinates and between `-π` and `π`. An angle of `0.0` means that
+    /// the cursor is to the right of the center, and `π/2` means that it is above the center.
+    pub fn cursor_angle(&self) -> Option<f32> {
+        if !self.is_cursor_hovering_ellipse() {
+            return None;
+        }
+
+        self.cursor_hover_coordinates().map(|c| c[1].atan2(c[0]))
+    }
+
+    /// Returns the coordinates of the cursor within the context, even if the cursor is outside of
+    /// the context.
+    ///
+    /// Contrary to `cursor_hover_coordinates`, the result can be outside of the `-1.0` to `1.0`
+    /// range. Returns `None` if there is no cursor or if the context is degenerate.
+    #[inline]
+    pub fn cursor_local_coordinates(&self) -> Option<[f32; 2]> {
+        match self.cursor {
+            Some(cursor) => self.viewport_to_local(cursor),
+            None => None,
+        }
+    }
+
+    /// Returns true if the given widget is the active widget and the cursor is available.
+    ///
+    /// Widgets that can be dragged, like sliders or scrollbars, should keep reacting to the
+    /// cursor while this function returns true, even if the cursor is outside of their context.
+    /// They are also responsible for clearing `ui_state.active_widget` when
+    /// `cursor_was_released()` is true.
+    #[inline]
+    pub fn is_dragging_active(&self, ui_state: &UiState, id: &WidgetId) -> bool {
+        self.cursor.is_some() && ui_state.active_widget.as_ref() == Some(id)
+    }
+
+    /// Handles the press and the release of the cursor for the given widget, and returns true if
+    /// it has been clicked.
+    ///
+    /// A press while the cursor hovers the context makes the widget active. A release then
+    /// returns true only if the widget is still the active widget and if the cursor hovers the
+    /// context, so that a press on a widget followed by a release on another one doesn't count
+    /// as a click for either of them. The active widget is cleared on release in both cases.
+    ///
+    /// The id must be stable across frames, which is the case for the ids returned by
+    /// `reserve_widget_id` as long as the widgets are drawn in the same order. This function
+    /// doesn't signal that the cursor is hovering the context, see `set_cursor_hovered_widget`.
+    pub fn clicked_strict(&self, ui_state: &mut UiState, id: &WidgetId) -> bool {
+        let hovering = self.is_cursor_hovering();
+
+        if hovering && self.cursor_was_pressed() {
+            ui_state.active_widget = Some(id.clone());
+            self.consume_click();
+            return false;
+        }
+
+        if self.cursor_was_released() && ui_state.active_widget.as_ref() == Some(id) {
+            ui_state.active_widget = None;
+            return hovering;
+        }
+
+        false
+    }
+
+    /// Turns a point in the coordinates of this context into a point in the coordinates of the
+    /// viewport.
+    ///
+    /// Both are in OpenGL-like coordinates. In other words, (-1,-1) is the bottom-left hand corner
+    /// and (1,1) is the top-right hand corner of respectively the context and the viewport.
+    #[inline]
+    pub fn local_to_viewport(&self, point: [f32; 2]) -> [f32; 2] {
+        let output = self.logical_matrix() * [point[0], point[1], 1.0];
+        [output[0] / output[2], output[1] / output[2]]
+    }
+
+    /// Returns the positions of the four corners of the context in the coordinates of the
+    /// viewport, in the top-left, top-right, bottom-right, bottom-left order.
+    ///
+    /// The coordinates are the same as `local_to_viewport`. This is useful for drawing a custom
+    /// shape that follows the context, even if it is rotated.
+    #[inline]
+    pub fn corners(&self) -> [[f32; 2]; 4] {
+        transformed_corners(&self.logical_matrix())
+    }
+
+    /// Draws a border of `thickness_px` pixels along the four edges of the context, with
+    /// `Draw::draw_line`.
+    ///
+    /// The border is entirely inside the context, and follows it if it is rotated. The color is
+    /// in RGBA format and is multiplied with the tint of the context.
+    pub fn draw_border(&self, thickness_px: f32, color: [f32; 4]) {
+        if self.is_empty() || thickness_px.is_nan() || thickness_px <= 0.0 {
+            return;
+        }
+
+        // The lines are centered on the edges of a context that is shrunk by half the thickness.
+        let thickness_px = thickness_px * self.ui_scale();
+        let half = thickness_px / 2.0;
+        let inner = self.margin(half / self.height, half / self.width, half / self.height,
+                                half / self.width);
+
+        // Contrary to `corners`, this includes the transformations that are only visual.
+        let corners = transformed_corners(&inner.matrix());
+
+        let mut draw = self.draw();
+        for n in 0 .. 4 {
+            draw.draw_line(corners[n], corners[(n + 1) % 4], thickness_px, color);
+        }
+    }
+
+    /// Returns the smallest axis-aligned rectangle of the viewport that contains the context, in
+    /// the `[min_x, min_y, max_x, max_y]` format.
+    ///
+    /// The coordinates are the same as `local_to_viewport`. If the context is rotated, the
+    /// rectangle contains its four corners. Use `union_bounding_boxes` to obtain the bounds of
+    /// multiple contexts, for example to draw a selection rectangle around them.
+    pub fn bounding_box(&self) -> [f32; 4] {
+        let corners = self.corners();
+
+        let mut bounds = [corners[0][0], corners[0][1], corners[0][0], corners[0][1]];
+        for corner in &corners[1 ..] {
+            bounds[0] = bounds[0].min(corner[0]);
+            bounds[1] = bounds[1].min(corner[1]);
+            bounds[2] = bounds[2].max(corner[0]);
+            bounds[3] = bounds[3].max(corner[1]);
+        }
+        bounds
+    }
+
+    /// Turns a point in the coordinates of the viewport into a point in the coordinates of this
+    /// context. This is the inverse of `local_to_viewport`.
+    ///
+    /// Returns `None` if the context is degenerate, for example if its width or height is zero.
+    /// Contrary to `cursor_hover_coordinates`, the result can be outside of the `-1.0` to `1.0`
+    /// range.
+    pub fn viewport_to_local(&self, point: [f32; 2]) -> Option<[f32; 2]> {
+        // we compute the inverse of the matrix
+        let m = self.logical_matrix().invert()?;
+
+        // and use it to calculate the position of the point within the current context
+        let output = [
+            point[0]*m[0][0] + point[1]*m[1][0] + m[2][0],
+            point[0]*m[0][1] + point[1]*m[1][1] + m[2][1],
+            point[0]*m[0][2] + point[1]*m[1][2] + m[2][2],
+        ];
+
+        Some([output[0] / output[2], output[1] / output[2]])
+    }
+
+    /// Returns the factor by which the sizes in pixels are multiplied. See `UiState::ui_scale`.
+    ///
+    /// Custom widgets that have sizes in pixels should multiply them with this value.
+    #[inline]
+    pub fn ui_scale(&self) -> f32 {
+        self.shared2.ui_scale
+    }
+
+    /// Returns the width of the context in pixels.
+    #[inline]
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Returns the height of the context in pixels.
+    #[inline]
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Returns the ratio of the width of the surface divided by its height.
+    ///
+    /// If the context is empty, returns `1.0` so that no NaN or infinite value propagates.
     #[inline]
     pub fn width_per_height(&self) -> f32 {
+        if self.is_empty() {
+            return 1.0;
+        }
+
         self.width / self.height
     }
 
+    /// Returns true if the width or the height of the context is zero.
+    ///
+    /// This can happen for example if you split a context with a weight of zero. Nothing that is
+    /// drawn in an empty context is visible, and widgets usually don't draw anything at all.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
     /// Builds a new draw context containing a subarea of the current context, but with a margin.
     ///
     /// The margin is expressed in percentage of the surface (between 0.0 and 1.0).
@@ -309,6 +1312,12 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
             cursor: self.cursor,
             cursor_was_pressed: self.cursor_was_pressed,
             cursor_was_released: self.cursor_was_released,
+            tint: self.tint,
+            z_index: self.z_index,
+            clip: self.clip,
+            hover_suppressed: self.hover_suppressed,
+            layout_parent: self.layout_parent,
+            visual: self.visual,
         }
     }
 
@@ -372,15 +1381,102 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
         }
     }
 
+    /// Builds a new draw context that is centered in the current context, with the given width per
+    /// height ratio and with at least the given margin on all sides.
+    ///
+    /// This is a shortcut for calling `uniform_margin` with the same margin on all sides, then
+    /// `enforce_aspect_ratio_downscale` with a centered alignment. This is typically used for
+    /// dialogs and cards.
+    #[inline]
+    pub fn centered_content(&self, margin: f32, width_per_height: f32) -> DrawContext<'b, D> {
+        self.uniform_margin(margin, margin, margin, margin)
+            .enforce_aspect_ratio_downscale(width_per_height, &Alignment::center())
+    }
+
+    /// Splits the context in two horizontally. The first context has the height of the current
+    /// context and the given width per height ratio, and is placed on the given side. The second
+    /// context contains the rest of the space.
+    ///
+    /// This is typically used to draw an avatar or an icon next to a text. If the current context
+    /// is too narrow, the first context takes the whole width and the second one is empty. With
+    /// a centered alignment the first context is centered and the second one is the whole
+    /// context.
+    pub fn split_off_aspect(&self, width_per_height: f32, side: &HorizontalAlignment)
+                            -> (DrawContext<'b, D>, DrawContext<'b, D>)
+    {
+        let scale = if self.width <= 0.0 {
+            1.0
+        } else {
+            let scale = width_per_height * self.height / self.width;
+            scale.clamp(0.0, 1.0)
+        };
+
+        let fitted = self.horizontal_rescale(scale, side);
+        let remainder = match *side {
+            HorizontalAlignment::Left => {
+                self.horizontal_rescale(1.0 - scale, &HorizontalAlignment::Right)
+            },
+            HorizontalAlignment::Right => {
+                self.horizontal_rescale(1.0 - scale, &HorizontalAlignment::Left)
+            },
+            HorizontalAlignment::Center => self.horizontal_rescale(1.0, side),
+        };
+
+        (fitted, remainder)
+    }
+
+    /// Builds a new draw context that is moved so that it doesn't go outside of the viewport.
+    ///
+    /// This is useful for dropdowns, tooltips or context menus that are positioned near the
+    /// cursor or near another element, and that could otherwise overflow the window. The context
+    /// is only translated and is never rescaled. If it is larger than the viewport, its top-left
+    /// hand corner is kept visible.
+    pub fn clamp_into_parent(&self) -> DrawContext<'b, D> {
+        let corners = self.corners();
+
+        let min_x = corners.iter().fold(corners[0][0], |a, c| a.min(c[0]));
+        let max_x = corners.iter().fold(corners[0][0], |a, c| a.max(c[0]));
+        let min_y = corners.iter().fold(corners[0][1], |a, c| a.min(c[1]));
+        let max_y = corners.iter().fold(corners[0][1], |a, c| a.max(c[1]));
+
+        let dx = if min_x < -1.0 || max_x - min_x > 2.0 {
+            -1.0 - min_x
+        } else if max_x > 1.0 {
+            1.0 - max_x
+        } else {
+            0.0
+        };
+
+        let dy = if max_y > 1.0 || max_y - min_y > 2.0 {
+            1.0 - max_y
+        } else if min_y < -1.0 {
+            -1.0 - min_y
+        } else {
+            0.0
+        };
+
+        let translation = Matrix::translate(dx, dy);
+
+        let mut ctxt = self.clone();
+        ctxt.matrix = translation * self.matrix;
+        ctxt.animation = self.animation.map(|(m, p)| (translation * m, p));
+        ctxt
+    }
+
     /// Builds a new draw context containing a subarea of the current context. The width of the new
     /// viewport will be the same as the current one, but its new height will be multipled by
     /// the value of `scale`.
     ///
     /// The alignment is used to determine the position of the new viewport inside the old one.
+    ///
+    /// If `scale` is NaN or infinite, it is treated as `0.0` and the new context is empty.
     #[inline]
+    #[allow(clippy::match_ref_pats)]
     pub fn vertical_rescale(&self, scale: f32, alignment: &VerticalAlignment)
                             -> DrawContext<'b, D>
     {
+        let scale = finite_or_zero(scale);
+
         let y = match alignment {
             &VerticalAlignment::Center => 0.0,
             &VerticalAlignment::Bottom => scale - 1.0,
@@ -397,6 +1493,12 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
             cursor: self.cursor,
             cursor_was_pressed: self.cursor_was_pressed,
             cursor_was_released: self.cursor_was_released,
+            tint: self.tint,
+            z_index: self.z_index,
+            clip: self.clip,
+            hover_suppressed: self.hover_suppressed,
+            layout_parent: self.layout_parent,
+            visual: self.visual,
         }
     }
 
@@ -405,10 +1507,15 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
     /// the value of `scale`.
     ///
     /// The alignment is used to determine the position of the new viewport inside the old one.
+    ///
+    /// If `scale` is NaN or infinite, it is treated as `0.0` and the new context is empty.
     #[inline]
+    #[allow(clippy::match_ref_pats)]
     pub fn horizontal_rescale(&self, scale: f32, alignment: &HorizontalAlignment)
                               -> DrawContext<'b, D>
     {
+        let scale = finite_or_zero(scale);
+
         let x = match alignment {
             &HorizontalAlignment::Center => 0.0,
             &HorizontalAlignment::Left => scale - 1.0,
@@ -425,6 +1532,12 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
             cursor: self.cursor,
             cursor_was_pressed: self.cursor_was_pressed,
             cursor_was_released: self.cursor_was_released,
+            tint: self.tint,
+            z_index: self.z_index,
+            clip: self.clip,
+            hover_suppressed: self.hover_suppressed,
+            layout_parent: self.layout_parent,
+            visual: self.visual,
         }
     }
 
@@ -437,6 +1550,10 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
 
     /// Same as `vertical_split`, but attributes a weight to each chunk. For example a chunk of
     /// weight 2 will have twice the size of a chunk of weight 1.
+    ///
+    /// The iterator must yield exactly as many weights as its `len()` reports, otherwise the
+    /// chunks are misplaced. This is checked in debug builds. Weights that are NaN or infinite
+    /// are treated as `0.0`, and produce empty chunks.
     #[inline]
     pub fn vertical_split_weights<'a, I>(&'a self, weights: I) -> SplitsIter<'a, 'b, I::IntoIter, D>
         where I: IntoIterator<Item = f32>, I::IntoIter: ExactSizeIterator + Clone
@@ -451,8 +1568,58 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
         self.horizontal_split_weights(iter)
     }
 
+    /// Splits the viewport in `count` horizontal chunks of equal size, and calls `f` with the
+    /// index and the context of each chunk, from left to right.
+    ///
+    /// This is the same as iterating over `horizontal_split`, but is more convenient for drawing
+    /// a row of identical elements. Nothing happens if `count` is zero.
+    #[inline]
+    pub fn repeat_horizontal<F>(&self, count: usize, mut f: F)
+        where F: FnMut(usize, &DrawContext<'b, D>)
+    {
+        if count == 0 {
+            return;
+        }
+
+        for (index, ctxt) in self.horizontal_split(count).enumerate() {
+            f(index, &ctxt);
+        }
+    }
+
+    /// Same as `repeat_horizontal`, except that the chunks are vertical and go from top to
+    /// bottom.
+    #[inline]
+    pub fn repeat_vertical<F>(&self, count: usize, mut f: F)
+        where F: FnMut(usize, &DrawContext<'b, D>)
+    {
+        if count == 0 {
+            return;
+        }
+
+        for (index, ctxt) in self.vertical_split(count).enumerate() {
+            f(index, &ctxt);
+        }
+    }
+
+    /// Same as `horizontal_split`, but also yields the index of each chunk and the position of its
+    /// center between `0.0` (left) and `1.0` (right).
+    ///
+    /// The position of the center can be used for example to offset the animation of each chunk.
+    #[inline]
+    pub fn horizontal_split_enumerate<'a>(&'a self, splits: usize)
+        -> impl Iterator<Item = (usize, f32, DrawContext<'b, D>)> + 'a
+    {
+        self.horizontal_split(splits).enumerate().map(move |(index, ctxt)| {
+            (index, (index as f32 + 0.5) / splits as f32, ctxt)
+        })
+    }
+
     /// Same as `horizontal_split`, but attributes a weight to each chunk. For example a chunk of
     /// weight 2 will have twice the size of a chunk of weight 1.
+    ///
+    /// The iterator must yield exactly as many weights as its `len()` reports, otherwise the
+    /// chunks are misplaced. This is checked in debug builds. Weights that are NaN or infinite
+    /// are treated as `0.0`, and produce empty chunks.
     #[inline]
     pub fn horizontal_split_weights<'a, I>(&'a self, weights: I) -> SplitsIter<'a, 'b, I::IntoIter, D>
         where I: IntoIterator<Item = f32>, I::IntoIter: ExactSizeIterator + Clone
@@ -460,329 +1627,2634 @@ impl<'b, D: ?Sized + Draw + 'b> DrawContext<'b, D> {
         self.split_weights(weights.into_iter(), false)
     }
 
-    /// Internal implementation of the split functions.
+    /// Splits the context in a strip of `height_px` pixels at the top, and the rest of the
+    /// context below.
+    ///
+    /// If the context is less than `height_px` pixels high, the strip takes the whole context
+    /// and the rest is empty.
     #[inline]
-    fn split_weights<'a, I>(&'a self, weights: I, vertical: bool) -> SplitsIter<'a, 'b, I, D>
-        where I: ExactSizeIterator<Item = f32> + Clone
-    {
-        assert!(weights.len() != 0);
+    pub fn take_top(&self, height_px: f32) -> (DrawContext<'b, D>, DrawContext<'b, D>) {
+        let p = strip_percent(height_px * self.ui_scale(), self.height);
+        (self.margin(0.0, 0.0, 1.0 - p, 0.0), self.margin(p, 0.0, 0.0, 0.0))
+    }
 
-        let total_weight = weights.clone().fold(0.0, |a, b| a + b);
-        let total_weight_inverse = 1.0 / total_weight;
+    /// Same as `take_top`, but the strip is at the bottom of the context.
+    #[inline]
+    pub fn take_bottom(&self, height_px: f32) -> (DrawContext<'b, D>, DrawContext<'b, D>) {
+        let p = strip_percent(height_px * self.ui_scale(), self.height);
+        (self.margin(1.0 - p, 0.0, 0.0, 0.0), self.margin(0.0, 0.0, p, 0.0))
+    }
 
-        SplitsIter {
-            parent: self,
-            weights: weights,
-            total_weight_inverse: total_weight_inverse,
-            current_offset: 0.0,
-            vertical: vertical,
-        }
+    /// Same as `take_top`, but the strip is `width_px` pixels wide and at the left of the
+    /// context.
+    #[inline]
+    pub fn take_left(&self, width_px: f32) -> (DrawContext<'b, D>, DrawContext<'b, D>) {
+        let p = strip_percent(width_px * self.ui_scale(), self.width);
+        (self.margin(0.0, 1.0 - p, 0.0, 0.0), self.margin(0.0, 0.0, 0.0, p))
     }
 
-    /// Changes the dimensions of the context.
-    ///
-    /// The dimensions are a percentage of the current dimensions. For example to divide the width
-    /// by two, you need to pass `0.5`.
+    /// Same as `take_left`, but the strip is at the right of the context.
+    #[inline]
+    pub fn take_right(&self, width_px: f32) -> (DrawContext<'b, D>, DrawContext<'b, D>) {
+        let p = strip_percent(width_px * self.ui_scale(), self.width);
+        (self.margin(0.0, 0.0, 0.0, 1.0 - p), self.margin(0.0, p, 0.0, 0.0))
+    }
+
+    /// Builds a new draw context of `width_px` by `height_px` pixels, positioned inside the
+    /// current one with the alignment.
     ///
-    /// The alignment is used to determine the position of the newly-created context relative to
-    /// the old one.
-    pub fn rescale(&self, width_percent: f32, height_percent: f32, alignment: &Alignment)
-                   -> DrawContext<'b, D>
+    /// The new context can be larger than the current one. If the current context is empty, the
+    /// new one is empty as well.
+    #[inline]
+    pub fn fixed_size(&self, width_px: f32, height_px: f32, alignment: &Alignment)
+                      -> DrawContext<'b, D>
     {
-        let x = match alignment.horizontal {
-            HorizontalAlignment::Center => 0.0,
-            HorizontalAlignment::Left => width_percent - 1.0,
-            HorizontalAlignment::Right => 1.0 - width_percent,
-        };
+        let scale = self.ui_scale();
+        self.rescale(width_px * scale / self.width, height_px * scale / self.height, alignment)
+    }
+
+    /// Same as `margin`, except that the margins are in pixels.
+    ///
+    /// If the margins are larger than the context, the new context is flipped like with
+    /// `margin`.
+    #[inline]
+    pub fn margin_px(&self, top: f32, right: f32, bottom: f32, left: f32) -> DrawContext<'b, D> {
+        let scale = self.ui_scale();
+        let vertical = |px: f32| if self.height > 0.0 { px * scale / self.height } else { 0.0 };
+        let horizontal = |px: f32| if self.width > 0.0 { px * scale / self.width } else { 0.0 };
+        self.margin(vertical(top), horizontal(right), vertical(bottom), horizontal(left))
+    }
 
-        let y = match alignment.vertical {
-            VerticalAlignment::Center => 0.0,
-            VerticalAlignment::Bottom => height_percent - 1.0,
-            VerticalAlignment::Top => 1.0 - height_percent,
+    /// Builds a new draw context that excludes the parts of the viewport covered by the system,
+    /// as described by `ui_state.safe_area_insets`.
+    ///
+    /// This should be called on a context that covers the whole viewport. Contrary to
+    /// `margin_px`, the insets are physical pixels and aren't multiplied with the UI scale.
+    /// Insets that are NaN, infinite or negative are treated as zero.
+    pub fn safe_area(&self, ui_state: &UiState) -> DrawContext<'b, D> {
+        let insets = ui_state.safe_area_insets;
+        let inset = |px: f32, total: f32| {
+            if total > 0.0 && px > 0.0 { finite_or_zero(px / total) } else { 0.0 }
         };
 
-        DrawContext {
-            matrix: self.matrix * Matrix::translate(x, y)
-                                * Matrix::scale_wh(width_percent, height_percent),
-            width: self.width * width_percent,
-            height: self.height * height_percent,
-            animation: self.animation,
-            shared1: self.shared1.clone(),
-            shared2: self.shared2.clone(),
-            cursor: self.cursor,
-            cursor_was_pressed: self.cursor_was_pressed,
-            cursor_was_released: self.cursor_was_released,
-        }
+        self.margin(inset(insets[0], self.height), inset(insets[1], self.width),
+                    inset(insets[2], self.height), inset(insets[3], self.width))
     }
 
-    /// Starts an animation. The interpolation, start time and duration are used to calculate
-    /// at which point of the animation we are.
+    /// Builds a new draw context that is moved by `dx` pixels to the right and `dy` pixels
+    /// upwards, without changing its size.
     ///
-    /// At the moment where you call this function, the element must be at its starting point.
-    /// After you call this function, you must add further transformations to represent the
-    /// destination. Don't forget to call `animation_stop` if you want to add further
-    /// transformations.
-    ///
-    /// You can easily reverse this order (ie. the element is at its destination when you call the
-    /// function and will be moved to its source) by reversing the interpolation with `.reverse()`.
+    /// This is useful for nudging an element, for example a button that moves one pixel down
+    /// while it is pressed. The movement follows the orientation of the context if it is
+    /// rotated. Does nothing if the context is empty.
     #[inline]
-    pub fn animation_start<I>(&self, interpolation: I, start_time: SystemTime, duration: Duration)
-                              -> DrawContext<'b, D>
-        where I: Interpolation
+    pub fn offset_px(&self, dx: f32, dy: f32) -> DrawContext<'b, D> {
+        let scale = self.ui_scale();
+        let x = if self.width > 0.0 { finite_or_zero(2.0 * dx * scale / self.width) }
+                else { 0.0 };
+        let y = if self.height > 0.0 { finite_or_zero(2.0 * dy * scale / self.height) }
+                else { 0.0 };
+
+        let mut ctxt = self.clone();
+        ctxt.matrix = self.matrix * Matrix::translate(x, y);
+        ctxt
+    }
+
+    /// Builds a new draw context whose bottom-left hand corner is moved to the nearest pixel
+    /// boundary of the viewport.
+    ///
+    /// Text whose position lands on fractional pixels can look blurry or shimmer when it moves
+    /// slowly. Widgets that draw static text can use this function to keep it crisp. Only the
+    /// translation of the context is modified, not its size or its rotation, which means that
+    /// the other edges are only aligned on pixels if the size of the context is a whole number
+    /// of pixels. The animations that are in progress aren't snapped.
+    pub fn snap_to_pixels(&self) -> DrawContext<'b, D> {
+        #[inline]
+        fn snap(coord: f32, viewport_px: f32) -> f32 {
+            if viewport_px > 0.0 {
+                let px = ((coord + 1.0) * viewport_px * 0.5).round();
+                finite_or_zero(px * 2.0 / viewport_px - 1.0)
+            } else {
+                coord
+            }
+        }
+
+        let viewport = self.shared2.viewport_size;
+        let m = self.matrix.0;
+        let corner = [m[2][0] - m[0][0] - m[1][0], m[2][1] - m[0][1] - m[1][1]];
+
+        let mut ctxt = self.clone();
+        ctxt.matrix.0[2][0] += snap(corner[0], viewport[0]) - corner[0];
+        ctxt.matrix.0[2][1] += snap(corner[1], viewport[1]) - corner[1];
+        ctxt
+    }
+
+    /// Splits the viewport in `splits` horizontal chunks of equal size, separated with gutters.
+    ///
+    /// Returns the chunks from left to right, and the `splits - 1` gutters between them. Each
+    /// gutter is `gap_fraction` times the width of the context, and the chunks share the rest of
+    /// the width. This makes it possible to draw something in the gaps, like separators. If the
+    /// gutters don't fit, they are shrunk so that they take the whole context and the chunks are
+    /// empty.
+    #[inline]
+    pub fn horizontal_split_with_gutters(&self, splits: usize, gap_fraction: f32)
+                                         -> (Vec<DrawContext<'b, D>>, Vec<DrawContext<'b, D>>)
+    {
+        self.split_with_gutters(splits, gap_fraction, false)
+    }
+
+    /// Same as `horizontal_split_with_gutters`, except that the chunks are stacked from top to
+    /// bottom and that `gap_fraction` is a fraction of the height of the context.
+    #[inline]
+    pub fn vertical_split_with_gutters(&self, splits: usize, gap_fraction: f32)
+                                       -> (Vec<DrawContext<'b, D>>, Vec<DrawContext<'b, D>>)
+    {
+        self.split_with_gutters(splits, gap_fraction, true)
+    }
+
+    /// Internal implementation of the split functions with gutters.
+    fn split_with_gutters(&self, splits: usize, gap_fraction: f32, vertical: bool)
+                          -> (Vec<DrawContext<'b, D>>, Vec<DrawContext<'b, D>>)
+    {
+        if splits == 0 {
+            return (Vec::new(), Vec::new());
+        }
+
+        let gap_fraction = finite_or_zero(gap_fraction).max(0.0);
+        let gutters = (splits - 1) as f32;
+        let (gap, chunk) = if gap_fraction * gutters >= 1.0 {
+            (1.0 / gutters, 0.0)
+        } else {
+            (gap_fraction, (1.0 - gap_fraction * gutters) / splits as f32)
+        };
+
+        let mut chunks = Vec::with_capacity(splits);
+        let mut gaps = Vec::with_capacity(splits - 1);
+
+        let mut offset = 0.0;
+        for n in 0 .. splits * 2 - 1 {
+            let size = if n % 2 == 0 { chunk } else { gap };
+            let ctxt = if vertical {
+                self.margin(offset, 0.0, 1.0 - offset - size, 0.0)
+            } else {
+                self.margin(0.0, 1.0 - offset - size, 0.0, offset)
+            };
+            offset += size;
+
+            if n % 2 == 0 { chunks.push(ctxt) } else { gaps.push(ctxt) }
+        }
+
+        (chunks, gaps)
+    }
+
+    /// Splits the viewport in horizontal chunks whose widths are described by `specs`.
+    ///
+    /// The `Fixed` chunks get exactly their size in pixels, and the rest of the width is shared
+    /// between the `Flex` chunks according to their weights. If the fixed chunks are wider than
+    /// the context, they are shrunk proportionally and the flexible chunks are empty. If there is
+    /// no flexible chunk, the space that remains is left empty on the right.
+    #[inline]
+    pub fn horizontal_split_mixed(&self, specs: &[SizeSpec]) -> Vec<DrawContext<'b, D>> {
+        self.split_mixed(specs, false)
+    }
+
+    /// Same as `horizontal_split_mixed`, except that the chunks are stacked from top to bottom
+    /// and that the space that remains is left empty at the bottom.
+    #[inline]
+    pub fn vertical_split_mixed(&self, specs: &[SizeSpec]) -> Vec<DrawContext<'b, D>> {
+        self.split_mixed(specs, true)
+    }
+
+    /// Internal implementation of the mixed split functions.
+    fn split_mixed(&self, specs: &[SizeSpec], vertical: bool) -> Vec<DrawContext<'b, D>> {
+        let available = if vertical { self.height } else { self.width };
+        if available <= 0.0 {
+            return specs.iter().map(|_| self.margin(0.0, 0.0, 1.0, 1.0)).collect();
+        }
+
+        let positive = |value: f32| if value > 0.0 && value.is_finite() { value } else { 0.0 };
+        let scale = self.ui_scale();
+
+        let (fixed_total, flex_total) = specs.iter().fold((0.0, 0.0), |(fixed, flex), spec| {
+            match *spec {
+                SizeSpec::Fixed(px) => (fixed + positive(px * scale), flex),
+                SizeSpec::Flex(weight) => (fixed, flex + positive(weight)),
+            }
+        });
+
+        let (fixed_scale, flex_px) = if fixed_total > available {
+            (available / fixed_total, 0.0)
+        } else {
+            (1.0, available - fixed_total)
+        };
+
+        let mut offset = 0.0;
+        specs.iter().map(|spec| {
+            let size = match *spec {
+                SizeSpec::Fixed(px) => positive(px * scale) * fixed_scale,
+                SizeSpec::Flex(weight) if flex_total > 0.0 => {
+                    flex_px * positive(weight) / flex_total
+                },
+                SizeSpec::Flex(_) => 0.0,
+            };
+
+            let start = offset / available;
+            offset += size;
+            let end = offset / available;
+
+            if vertical {
+                self.margin(start, 0.0, 1.0 - end, 0.0)
+            } else {
+                self.margin(0.0, 1.0 - end, 0.0, start)
+            }
+        }).collect()
+    }
+
+    /// Lays out `count` items with the given width per height ratio from left to right, and
+    /// wraps them on multiple rows if they don't fit on one.
+    ///
+    /// The height of the context is divided between the rows, and the smallest number of rows
+    /// that can contain all the items is chosen. Each row is filled as much as possible before
+    /// moving to the next one, starting from the top-left hand corner.
+    pub fn flow_split(&self, width_per_height: f32, count: usize) -> Vec<DrawContext<'b, D>> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        if self.is_empty() || width_per_height <= 0.0 {
+            return (0 .. count).map(|_| self.clone()).collect();
+        }
+
+        let mut rows = 1;
+        let items_per_row = loop {
+            let item_width = self.height / rows as f32 * width_per_height;
+            let per_row = (self.width / item_width).floor() as usize;
+
+            if per_row * rows >= count || rows >= count {
+                break if per_row == 0 { 1 } else { per_row };
+            }
+
+            rows += 1;
+        };
+
+        // Width of an item, as a percentage of the width of the context.
+        let item_width = self.height / rows as f32 * width_per_height / self.width;
+        let item_width = if item_width > 1.0 { 1.0 } else { item_width };
+
+        (0 .. count).map(|num| {
+            let row = (num / items_per_row) as f32;
+            let column = (num % items_per_row) as f32;
+
+            self.margin(row / rows as f32, 1.0 - (column + 1.0) * item_width,
+                        1.0 - (row + 1.0) / rows as f32, column * item_width)
+        }).collect()
+    }
+
+    /// Internal implementation of the split functions.
+    #[inline]
+    #[allow(clippy::redundant_field_names)]
+    fn split_weights<'a, I>(&'a self, weights: I, vertical: bool) -> SplitsIter<'a, 'b, I, D>
+        where I: ExactSizeIterator<Item = f32> + Clone
+    {
+        assert!(weights.len() != 0);
+
+        let (total_weight, count) = weights.clone().fold((0.0, 0), |(a, n), b| {
+            (a + finite_or_zero(b), n + 1)
+        });
+        debug_assert_eq!(count, weights.len(), "The iterator of weights yielded a different \
+                                                number of items than its `len()`");
+        // If all the weights are zero, all the chunks are empty.
+        let total_weight_inverse = if total_weight != 0.0 { 1.0 / total_weight } else { 0.0 };
+
+        SplitsIter {
+            parent: self,
+            weights: weights,
+            total_weight_inverse: total_weight_inverse,
+            current_offset: 0.0,
+            vertical: vertical,
+        }
+    }
+
+    /// Changes the dimensions of the context.
+    ///
+    /// The dimensions are a percentage of the current dimensions. For example to divide the width
+    /// by two, you need to pass `0.5`.
+    ///
+    /// The alignment is used to determine the position of the newly-created context relative to
+    /// the old one.
+    ///
+    /// Percentages that are NaN or infinite are treated as `0.0`, and the new context is empty.
+    #[inline]
+    pub fn rescale(&self, width_percent: f32, height_percent: f32, alignment: &Alignment)
+                   -> DrawContext<'b, D>
+    {
+        self.rescale_f(width_percent, height_percent, (*alignment).into())
+    }
+
+    /// Same as `rescale`, except that the alignment is continuous.
+    ///
+    /// This makes it possible to animate the position of the context from one alignment to
+    /// another, by interpolating between the two `AlignmentF`s.
+    pub fn rescale_f(&self, width_percent: f32, height_percent: f32, alignment: AlignmentF)
+                     -> DrawContext<'b, D>
     {
-        let now = SystemTime::now();
+        let width_percent = finite_or_zero(width_percent);
+        let height_percent = finite_or_zero(height_percent);
 
-        let interpolation = interpolation.calculate(now, start_time, duration) as f32;
-        let current_matrix = self.matrix();
+        let x = finite_or_zero(alignment.horizontal) * (1.0 - width_percent);
+        let y = finite_or_zero(alignment.vertical) * (1.0 - height_percent);
 
         DrawContext {
-            matrix: self.matrix,
-            width: self.width,
-            height: self.height,
-            animation: Some((current_matrix, interpolation)),
+            matrix: self.matrix * Matrix::translate(x, y)
+                                * Matrix::scale_wh(width_percent, height_percent),
+            width: self.width * width_percent,
+            height: self.height * height_percent,
+            animation: self.animation,
             shared1: self.shared1.clone(),
             shared2: self.shared2.clone(),
             cursor: self.cursor,
             cursor_was_pressed: self.cursor_was_pressed,
             cursor_was_released: self.cursor_was_released,
+            tint: self.tint,
+            z_index: self.z_index,
+            clip: self.clip,
+            hover_suppressed: self.hover_suppressed,
+            layout_parent: self.layout_parent,
+            visual: self.visual,
         }
     }
 
-    /// Stops the animation process. The next commands will always be applied.
+    /// Same as `rescale`, except that the width and the height are multiplied by the same
+    /// `scale` and that `point`, in the coordinates of this context, stays at the same position.
+    ///
+    /// This is what zooming in or out at the position of the cursor does.
     #[inline]
-    pub fn animation_stop(&self) -> DrawContext<'b, D> {
+    pub fn rescale_around(&self, scale: f32, point: [f32; 2]) -> DrawContext<'b, D> {
+        self.rescale_f(scale, scale, AlignmentF { horizontal: point[0], vertical: point[1] })
+    }
+
+    /// Starts building a subarea of the context whose size is subject to constraints.
+    ///
+    /// Call the methods of the returned `ConstraintBuilder` to add constraints, then `build` to
+    /// obtain the new context. Without any constraint, the new context is the same as this one.
+    #[inline]
+    pub fn constrain<'a>(&'a self) -> ConstraintBuilder<'a, 'b, D> {
+        ConstraintBuilder {
+            ctxt: self,
+            aspect: None,
+            min_width_px: None,
+            max_width_px: None,
+            min_height_px: None,
+            max_height_px: None,
+        }
+    }
+
+    /// Starts an animation. The interpolation, start time and duration are used to calculate
+    /// at which point of the animation we are.
+    ///
+    /// At the moment where you call this function, the element must be at its starting point.
+    /// After you call this function, you must add further transformations to represent the
+    /// destination. Don't forget to call `animation_stop` if you want to add further
+    /// transformations.
+    ///
+    /// You can easily reverse this order (ie. the element is at its destination when you call the
+    /// function and will be moved to its source) by reversing the interpolation with `.reverse()`.
+    #[inline]
+    pub fn animation_start<I>(&self, interpolation: I, start_time: SystemTime, duration: Duration)
+                              -> DrawContext<'b, D>
+        where I: Interpolation
+    {
+        let interpolation = self.interpolate(&interpolation, start_time, duration);
+        let current_matrix = self.logical_matrix();
+
         DrawContext {
-            matrix: self.matrix(),
+            matrix: self.matrix,
             width: self.width,
             height: self.height,
-            animation: None,
+            animation: Some((current_matrix, interpolation)),
             shared1: self.shared1.clone(),
             shared2: self.shared2.clone(),
             cursor: self.cursor,
             cursor_was_pressed: self.cursor_was_pressed,
             cursor_was_released: self.cursor_was_released,
+            tint: self.tint,
+            z_index: self.z_index,
+            clip: self.clip,
+            hover_suppressed: self.hover_suppressed,
+            layout_parent: self.layout_parent,
+            visual: self.visual,
         }
     }
-}
 
-impl<'a, 'b, D: ?Sized + Draw + 'b> Clone for DrawContext<'b, D> {
-    fn clone(&self) -> DrawContext<'b, D> {
-        DrawContext {
-            matrix: self.matrix.clone(),
-            width: self.width.clone(),
-            height: self.height.clone(),
-            animation: self.animation.clone(),
-            shared1: self.shared1.clone(),
-            shared2: self.shared2.clone(),
-            cursor: self.cursor.clone(),
-            cursor_was_pressed: self.cursor_was_pressed,
-            cursor_was_released: self.cursor_was_released,
-        }
+    /// Same as `animate`, except that the animation starts the first time that the context is
+    /// drawn with the given id.
+    ///
+    /// The moment when `id` was first drawn is stored in `ui_state.appear_times`. It is
+    /// forgotten by `SharedDrawContext::finish` if `id` isn't drawn during a frame, so that the
+    /// animation plays again if the element disappears and then reappears. The id must therefore
+    /// stay the same from one frame to the next, and should usually be reserved by the widget
+    /// that is animated.
+    pub fn animate_in<A, I>(&self, ui_state: &mut UiState, id: &WidgetId, animation: A,
+                            interpolation: I, duration: Duration) -> DrawContext<'b, D>
+        where A: Animation, I: Interpolation
+    {
+        self.is_first_appearance(ui_state, id);
+        let start_time = ui_state.appear_times[id];
+        self.animate(animation, interpolation, start_time, duration)
     }
-}
 
-/// Represents the alignment of a viewport.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Alignment {
-    /// The horizontal alignment.
-    pub horizontal: HorizontalAlignment,
-    /// The vertical alignment.
-    pub vertical: VerticalAlignment,
-}
+    /// Returns true if the widget with the given id is drawn during this frame but wasn't drawn
+    /// during the previous one.
+    ///
+    /// This can be used to trigger one-shot effects, like playing a sound. All the calls made
+    /// with the same id during the frame return the same value. Just like `animate_in`, this
+    /// relies on `ui_state.appear_times`, so the id must stay the same from one frame to the
+    /// next.
+    pub fn is_first_appearance(&self, ui_state: &mut UiState, id: &WidgetId) -> bool {
+        self.shared1.seen_widgets.lock().unwrap().insert(id.clone());
 
-impl Alignment {
-    /// Shortcut for `(center, center)`.
-    #[inline]
-    pub fn center() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Center,
-            vertical: VerticalAlignment::Center,
+        let mut first_appearances = self.shared1.first_appearances.lock().unwrap();
+        if !ui_state.appear_times.contains_key(id) {
+            ui_state.appear_times.insert(id.clone(), self.now());
+            first_appearances.insert(id.clone());
         }
+
+        first_appearances.contains(id)
     }
 
-    /// Shortcut for `(center, bottom)`.
+    /// Applies an animation to the context. The interpolation, start time and duration are used to
+    /// calculate at which point of the animation we are.
+    ///
+    /// Contrary to `animation_start`, the animation is entirely described by the `Animation`
+    /// object and you don't need to call `animation_stop`.
     #[inline]
-    pub fn bottom() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Center,
-            vertical: VerticalAlignment::Bottom,
-        }
+    pub fn animate<A, I>(&self, animation: A, interpolation: I, start_time: SystemTime,
+                         duration: Duration) -> DrawContext<'b, D>
+        where A: Animation, I: Interpolation
+    {
+        let percent = self.interpolate(&interpolation, start_time, duration);
+
+        let mut ctxt = self.clone();
+        ctxt.matrix = self.matrix * animation.animate(percent);
+        ctxt
     }
 
-    /// Shortcut for `(center, top)`.
-    #[inline]
-    pub fn top() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Center,
-            vertical: VerticalAlignment::Top,
+    /// Applies a timeline of animations to the context, starting at the given time.
+    ///
+    /// Before `start_time` the timeline is at its very beginning, and after its end the context
+    /// is left untouched.
+    pub fn animate_timeline(&self, timeline: &Timeline, start_time: SystemTime)
+                            -> DrawContext<'b, D>
+    {
+        let elapsed = self.now().duration_since(start_time)
+                                .unwrap_or_else(|_| Duration::new(0, 0));
+
+        if elapsed < timeline.duration() {
+            self.request_redraw();
         }
+
+        let mut ctxt = self.clone();
+        ctxt.matrix = self.matrix * timeline.matrix_at(elapsed);
+        ctxt
     }
 
-    /// Shortcut for `(right, center)`.
+    /// Builds a new draw context that is drawn rotated around its center, but whose interactions
+    /// with the cursor are the same as the current context.
+    ///
+    /// This is meant for decorations, for example a "new" ribbon over an image. Since only the
+    /// way the context is drawn is modified, `is_cursor_hovering()` and the coordinates
+    /// conversion functions behave as if the context wasn't rotated.
+    ///
+    /// Like `Matrix::rotate`, the rotation is clockwise. It takes the dimensions of the context in
+    /// pixels into account, so that the content isn't distorted.
+    pub fn rotated_draw(&self, radians: f32) -> DrawContext<'b, D> {
+        let local = Matrix::scale_wh(1.0 / self.width, 1.0 / self.height) *
+                    Matrix::rotate(radians) * Matrix::scale_wh(self.width, self.height);
+
+        let logical = self.logical_matrix();
+        let inverse = match logical.invert() {
+            Some(m) => Matrix([[m[0][0], m[0][1]], [m[1][0], m[1][1]], [m[2][0], m[2][1]]]),
+            None => return self.clone(),
+        };
+
+        let mut ctxt = self.clone();
+        ctxt.visual = self.visual * logical * local * inverse;
+        ctxt
+    }
+
+    /// Builds a new draw context whose images and glyphs are multiplied by the given color.
+    ///
+    /// The color is multiplied with the current tint of the context, so that tints can be nested.
     #[inline]
-    pub fn right() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Right,
-            vertical: VerticalAlignment::Center,
-        }
+    pub fn with_tint(&self, color: [f32; 4]) -> DrawContext<'b, D> {
+        let mut ctxt = self.clone();
+        ctxt.tint = [
+            self.tint[0] * color[0],
+            self.tint[1] * color[1],
+            self.tint[2] * color[2],
+            self.tint[3] * color[3],
+        ];
+        ctxt
     }
 
-    /// Shortcut for `(left, center)`.
+    /// Returns the z-index of the context. See `with_z_index`.
     #[inline]
-    pub fn left() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Left,
-            vertical: VerticalAlignment::Center,
-        }
+    pub fn z_index(&self) -> i32 {
+        self.z_index
     }
 
-    /// Shortcut for `(left, top)`.
+    /// Builds a new draw context whose images and glyphs are drawn in the given layer.
+    ///
+    /// Elements with a higher z-index are drawn above elements with a lower z-index, no matter
+    /// the order in which they are drawn. The initial z-index is `0`. This requires support from
+    /// the implementation of `Draw`, see `Draw::set_z_index`.
     #[inline]
-    pub fn top_left() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Left,
-            vertical: VerticalAlignment::Top,
-        }
+    pub fn with_z_index(&self, z_index: i32) -> DrawContext<'b, D> {
+        let mut ctxt = self.clone();
+        ctxt.z_index = z_index;
+        ctxt
     }
 
-    /// Shortcut for `(right, top)`.
+    /// Returns the rectangle of the viewport outside of which nothing drawn with this context is
+    /// visible, in the same format as `bounding_box`. Returns `None` if there is no clipping.
     #[inline]
-    pub fn top_right() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Right,
-            vertical: VerticalAlignment::Top,
-        }
+    pub fn clip(&self) -> Option<[f32; 4]> {
+        self.clip
     }
 
-    /// Shortcut for `(right, bottom)`.
+    /// Builds a new draw context whose images and glyphs are only visible within the current
+    /// context.
+    ///
+    /// The clip rectangle is the `bounding_box` of the context, intersected with the clip
+    /// rectangle of the current context if any. This requires support from the implementation of
+    /// `Draw`, see `Draw::set_clip`. Contexts that have been built before keep their own clip
+    /// rectangle, so that the clipping stops as soon as they are used again.
+    pub fn with_clip(&self) -> DrawContext<'b, D> {
+        let bounds = self.bounding_box();
+        let clip = match self.clip {
+            Some(c) => [bounds[0].max(c[0]), bounds[1].max(c[1]),
+                        bounds[2].min(c[2]), bounds[3].min(c[3])],
+            None => bounds,
+        };
+
+        let mut ctxt = self.clone();
+        ctxt.clip = Some(clip);
+        ctxt
+    }
+
+    /// Calls `f` with a context whose z-index is below the z-index of the current context.
+    ///
+    /// This is useful for containers that only know the size of their background after having
+    /// drawn their children. Everything drawn by `f` appears under what is drawn with the current
+    /// context, even if it is drawn afterwards.
     #[inline]
-    pub fn bottom_right() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Right,
-            vertical: VerticalAlignment::Bottom,
-        }
+    pub fn draw_background<F>(&self, f: F) where F: FnOnce(&DrawContext<'b, D>) {
+        f(&self.with_z_index(self.z_index - 1))
     }
 
-    /// Shortcut for `(left, bottom)`.
+    /// Applies a color animation to the tint of the context. The interpolation, start time and
+    /// duration are used to calculate at which point of the animation we are.
     #[inline]
-    pub fn bottom_left() -> Alignment {
-        Alignment {
-            horizontal: HorizontalAlignment::Left,
-            vertical: VerticalAlignment::Bottom,
+    pub fn animate_tint<I>(&self, animation: &ColorAnimation, interpolation: I,
+                           start_time: SystemTime, duration: Duration) -> DrawContext<'b, D>
+        where I: Interpolation
+    {
+        let percent = self.interpolate(&interpolation, start_time, duration);
+        self.with_tint(animation.animate_color(percent))
+    }
+
+    /// Fades the context in when `visible` is true, and out when it is false.
+    ///
+    /// Returns a context whose opacity goes linearly towards `1.0` or `0.0` in `duration`, or
+    /// `None` once the context has completely faded out, in which case you can skip drawing its
+    /// content. If `visible` changes during a fade, the opacity goes back from its current value.
+    pub fn fade(&self, visible: bool, state: &mut FadeState, duration: Duration)
+                -> Option<DrawContext<'b, D>>
+    {
+        let now = self.now();
+
+        if visible != state.visible {
+            state.from = state.opacity(now, duration);
+            state.start = now;
+            state.visible = visible;
+        }
+
+        let target = if state.visible { 1.0 } else { 0.0 };
+        let percent = self.interpolate(&Linear, state.start, duration);
+        let opacity = state.from + (target - state.from) * percent;
+
+        if opacity <= 0.0 {
+            return None;
         }
+
+        Some(self.with_tint([1.0, 1.0, 1.0, opacity]))
     }
-}
 
-/// Describes a horizontal alignment.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum HorizontalAlignment {
-    /// Align in the middle.
-    Center,
-    /// Align left.
-    Left,
-    /// Align right.
-    Right,
+    /// Builds a new draw context with a margin that goes from `from` to `to`. The interpolation,
+    /// start time and duration are used to calculate at which point of the animation we are.
+    ///
+    /// The margins are in the same order and format as `margin`, in other words `[top, right,
+    /// bottom, left]`. Contrary to `animate`, the size of the context in pixels follows the
+    /// animation, so that for example text is laid out again at each frame.
+    pub fn animate_margin<I>(&self, from: [f32; 4], to: [f32; 4], interpolation: I,
+                             start_time: SystemTime, duration: Duration) -> DrawContext<'b, D>
+        where I: Interpolation
+    {
+        let percent = self.interpolate(&interpolation, start_time, duration);
+        let lerp = |n: usize| from[n] + (to[n] - from[n]) * percent;
+        self.margin(lerp(0), lerp(1), lerp(2), lerp(3))
+    }
+
+    /// Applies an animation of the transformation, the opacity and the color of the context. The
+    /// interpolation, start time and duration are used to calculate at which point of the
+    /// animation we are.
+    ///
+    /// This is the same as calling `animate` and `animate_tint` with the same interpolation.
+    pub fn animate_full<A, I>(&self, animation: &FullAnimation<A>, interpolation: I,
+                              start_time: SystemTime, duration: Duration) -> DrawContext<'b, D>
+        where A: Animation, I: Interpolation
+    {
+        let percent = self.interpolate(&interpolation, start_time, duration);
+        let (matrix, tint) = animation.animate(percent);
+
+        let mut ctxt = self.with_tint(tint);
+        ctxt.matrix = self.matrix * matrix;
+        ctxt
+    }
+
+    /// Calculates the current value of an interpolation, and signals that the UI needs to be
+    /// redrawn if the animation is still running.
+    fn interpolate<I>(&self, interpolation: &I, start_time: SystemTime, duration: Duration) -> f32
+        where I: Interpolation
+    {
+        let now = self.now();
+        let anim_progress = animations::cycles_elapsed(now, start_time, duration);
+
+        if interpolation.is_running(anim_progress) {
+            self.request_redraw();
+        }
+
+        interpolation.calculate(now, start_time, duration) as f32
+    }
+
+    /// Builds a new draw context whose content is panned and zoomed by the camera.
+    ///
+    /// The user can pan the content by dragging it with the middle button of the mouse, which is
+    /// read from `ui_state.middle_button_down`, and zoom with the mouse wheel. Zooming keeps the
+    /// point under the cursor at the same position. The wheel is read from
+    /// `ui_state.scroll_delta`, which is reset once it has been consumed.
+    pub fn with_camera(&self, ui_state: &mut UiState, camera: &mut Camera) -> DrawContext<'b, D> {
+        let widget_id = self.reserve_widget_id();
+        let cursor = self.cursor_local_coordinates();
+
+        if ui_state.active_widget.as_ref() != Some(&widget_id) {
+            camera.grab = None;
+        }
+
+        if self.is_cursor_hovering() {
+            self.note_hover(&widget_id);
+
+            if ui_state.middle_button_down && ui_state.active_widget.is_none() {
+                self.consume_click();
+                ui_state.active_widget = Some(widget_id.clone());
+                camera.grab = cursor;
+            }
+
+            if ui_state.scroll_delta[1] != 0.0 {
+                let delta = self.consume_scroll(ui_state);
+
+                // Zooming rescales this context around the cursor, with the camera applied
+                // inside of it. The new offset is where the old one ends up.
+                if let Some(cursor) = cursor {
+                    let factor = 1.1f32.powf(delta[1]);
+                    let zoomed = self.rescale_around(factor, cursor);
+                    let offset = zoomed.local_to_viewport(camera.offset);
+                    if let Some(offset) = self.viewport_to_local(offset) {
+                        camera.offset = offset;
+                        camera.zoom *= factor;
+                    }
+                }
+            }
+        }
+
+        if self.is_dragging_active(ui_state, &widget_id) {
+            if let (Some(grab), Some(cursor)) = (camera.grab, cursor) {
+                camera.offset[0] += cursor[0] - grab[0];
+                camera.offset[1] += cursor[1] - grab[1];
+                camera.grab = Some(cursor);
+            }
+
+            if !ui_state.middle_button_down {
+                ui_state.active_widget = None;
+                camera.grab = None;
+            }
+        }
+
+        let mut ctxt = self.clone();
+        ctxt.matrix = self.matrix * Matrix::translate(camera.offset[0], camera.offset[1]) *
+                      Matrix::scale(camera.zoom);
+        ctxt.width = self.width * camera.zoom;
+        ctxt.height = self.height * camera.zoom;
+        ctxt
+    }
+
+    /// Stops the animation process. The next commands will always be applied.
+    #[inline]
+    pub fn animation_stop(&self) -> DrawContext<'b, D> {
+        DrawContext {
+            matrix: self.logical_matrix(),
+            width: self.width,
+            height: self.height,
+            animation: None,
+            shared1: self.shared1.clone(),
+            shared2: self.shared2.clone(),
+            cursor: self.cursor,
+            cursor_was_pressed: self.cursor_was_pressed,
+            cursor_was_released: self.cursor_was_released,
+            tint: self.tint,
+            z_index: self.z_index,
+            clip: self.clip,
+            hover_suppressed: self.hover_suppressed,
+            layout_parent: self.layout_parent,
+            visual: self.visual,
+        }
+    }
 }
 
-/// Describes a vertical alignment.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum VerticalAlignment {
-    /// Align in the middle.
-    Center,
-    /// Align top.
-    Top,
-    /// Align bottom.
-    Bottom,
+#[allow(clippy::extra_unused_lifetimes, clippy::clone_on_copy)]
+impl<'a, 'b, D: ?Sized + Draw + 'b> Clone for DrawContext<'b, D> {
+    fn clone(&self) -> DrawContext<'b, D> {
+        DrawContext {
+            matrix: self.matrix.clone(),
+            width: self.width.clone(),
+            height: self.height.clone(),
+            animation: self.animation.clone(),
+            shared1: self.shared1.clone(),
+            shared2: self.shared2.clone(),
+            cursor: self.cursor.clone(),
+            cursor_was_pressed: self.cursor_was_pressed,
+            cursor_was_released: self.cursor_was_released,
+            tint: self.tint,
+            z_index: self.z_index,
+            clip: self.clip,
+            hover_suppressed: self.hover_suppressed,
+            layout_parent: self.layout_parent,
+            visual: self.visual,
+        }
+    }
 }
 
-/// Iterator that splits a context in pieces and returns new contexts.
-pub struct SplitsIter<'a, 'b: 'a, I, D: ?Sized + Draw + 'b> {
-    parent: &'a DrawContext<'b, D>,
-    weights: I,
-    total_weight_inverse: f32,
-    current_offset: f32,
-    vertical: bool,
+/// Constraints on the size of a subarea of a context. See `DrawContext::constrain`.
+pub struct ConstraintBuilder<'a, 'b: 'a, D: ?Sized + Draw + 'b> {
+    ctxt: &'a DrawContext<'b, D>,
+    aspect: Option<f32>,
+    min_width_px: Option<f32>,
+    max_width_px: Option<f32>,
+    min_height_px: Option<f32>,
+    max_height_px: Option<f32>,
 }
 
-impl<'a, 'b: 'a, I, D: ?Sized + Draw + 'b> Iterator for SplitsIter<'a, 'b, I, D>
-    where I: Iterator<Item = f32>
-{
-    type Item = DrawContext<'b, D>;
+impl<'a, 'b: 'a, D: ?Sized + Draw + 'b> ConstraintBuilder<'a, 'b, D> {
+    /// The width of the new context can't be smaller than this value, in pixels.
+    #[inline]
+    pub fn min_width_px(mut self, width_px: f32) -> ConstraintBuilder<'a, 'b, D> {
+        self.min_width_px = Some(width_px);
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let weight = match self.weights.next() {
-            Some(w) => w,
-            None => return None
-        };
+    /// The width of the new context can't be larger than this value, in pixels.
+    #[inline]
+    pub fn max_width_px(mut self, width_px: f32) -> ConstraintBuilder<'a, 'b, D> {
+        self.max_width_px = Some(width_px);
+        self
+    }
 
-        let new_width = if !self.vertical { self.parent.width * weight * self.total_weight_inverse }
-                        else { self.parent.width };
-        let new_height = if self.vertical { self.parent.height * weight * self.total_weight_inverse }
-                         else { self.parent.height };
+    /// The height of the new context can't be smaller than this value, in pixels.
+    #[inline]
+    pub fn min_height_px(mut self, height_px: f32) -> ConstraintBuilder<'a, 'b, D> {
+        self.min_height_px = Some(height_px);
+        self
+    }
 
-        let scale_matrix = if self.vertical {
-            Matrix::scale_wh(1.0, weight * self.total_weight_inverse)
-        } else {
-            Matrix::scale_wh(weight * self.total_weight_inverse, 1.0)
-        };
+    /// The height of the new context can't be larger than this value, in pixels.
+    #[inline]
+    pub fn max_height_px(mut self, height_px: f32) -> ConstraintBuilder<'a, 'b, D> {
+        self.max_height_px = Some(height_px);
+        self
+    }
 
-        let pos_matrix = if self.vertical {
-            let y = 1.0 - 2.0 * (self.current_offset + weight * 0.5) * self.total_weight_inverse;
-            Matrix::translate(0.0, y)
-        } else {
-            let x = 2.0 * (self.current_offset + weight * 0.5) * self.total_weight_inverse - 1.0;
-            Matrix::translate(x, 0.0)
+    /// The new context has the given width per height ratio.
+    #[inline]
+    pub fn aspect(mut self, width_per_height: f32) -> ConstraintBuilder<'a, 'b, D> {
+        self.aspect = Some(width_per_height);
+        self
+    }
+
+    /// Builds the new context. The alignment is used to determine its position inside the
+    /// original one.
+    ///
+    /// The constraints are applied in this order:
+    ///
+    /// - The context is shrunk to the aspect ratio, like `enforce_aspect_ratio_downscale`.
+    /// - The width is clamped between the minimum and the maximum width.
+    /// - The height is clamped between the minimum and the maximum height.
+    ///
+    /// If there is an aspect ratio, clamping one dimension also scales the other one so that the
+    /// ratio is kept, and when two constraints conflict the last one wins. The minimum sizes can
+    /// make the new context larger than the original one.
+    pub fn build(&self, alignment: &Alignment) -> DrawContext<'b, D> {
+        let mut width = self.ctxt.width();
+        let mut height = self.ctxt.height();
+
+        let aspect = match self.aspect {
+            Some(a) if a.is_finite() && a > 0.0 => Some(a),
+            _ => None,
         };
 
-        self.current_offset += weight;
+        if let Some(aspect) = aspect {
+            if width > height * aspect {
+                width = height * aspect;
+            } else {
+                height = width / aspect;
+            }
+        }
 
-        Some(DrawContext {
-            matrix: self.parent.matrix * pos_matrix * scale_matrix,
-            width: new_width,
-            height: new_height,
-            animation: self.parent.animation,
-            shared1: self.parent.shared1.clone(),
-            shared2: self.parent.shared2.clone(),
-            cursor: self.parent.cursor,
-            cursor_was_pressed: self.parent.cursor_was_pressed,
-            cursor_was_released: self.parent.cursor_was_released,
-        })
+        let scale = self.ctxt.ui_scale();
+        let min_width_px = self.min_width_px.map(|px| px * scale);
+        let max_width_px = self.max_width_px.map(|px| px * scale);
+        let min_height_px = self.min_height_px.map(|px| px * scale);
+        let max_height_px = self.max_height_px.map(|px| px * scale);
+
+        let clamped = clamp_px(width, min_width_px, max_width_px);
+        if clamped != width {
+            width = clamped;
+            if let Some(aspect) = aspect {
+                height = width / aspect;
+            }
+        }
+
+        let clamped = clamp_px(height, min_height_px, max_height_px);
+        if clamped != height {
+            height = clamped;
+            if let Some(aspect) = aspect {
+                width = height * aspect;
+            }
+        }
+
+        self.ctxt.rescale(width / self.ctxt.width(), height / self.ctxt.height(), alignment)
     }
+}
 
-    #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.weights.size_hint()
+/// Clamps a size between two optional bounds. The bounds that are NaN are ignored.
+#[inline]
+fn clamp_px(value: f32, min: Option<f32>, max: Option<f32>) -> f32 {
+    let value = match max {
+        Some(max) if value > max => max,
+        _ => value,
+    };
+
+    match min {
+        Some(min) if value < min => min,
+        _ => value,
     }
 }
 
-impl<'a, 'b: 'a, I, D: ?Sized + Draw + 'b> ExactSizeIterator for SplitsIter<'a, 'b, I, D>
-    where I: ExactSizeIterator<Item = f32>
-{
+/// Position and zoom of the content of a context. See `DrawContext::with_camera`.
+///
+/// Must be kept between frames.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Camera {
+    /// Position of the center of the content, in the coordinates of the context.
+    pub offset: [f32; 2],
+
+    /// Zoom of the content. `1.0` means that the content is drawn at its normal size.
+    pub zoom: f32,
+
+    /// While the content is dragged, previous position of the cursor.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    grab: Option<[f32; 2]>,
 }
 
-/// Iterator that generates `1.0` a certain number of times.
-// TODO: This is required so that `horizontal_split` and `vertical_split` can express their
-//       return type. Should be replaced with `-> impl Iterator` eventually.
-#[derive(Debug, Clone)]
-pub struct OneGen {
-    n: usize
+impl Camera {
+    /// Builds a `Camera` that doesn't pan nor zoom.
+    #[inline]
+    pub fn new() -> Camera {
+        Camera {
+            offset: [0.0, 0.0],
+            zoom: 1.0,
+            grab: None,
+        }
+    }
 }
 
-impl Iterator for OneGen {
-    type Item = f32;
+impl Default for Camera {
+    #[inline]
+    fn default() -> Camera {
+        Camera::new()
+    }
+}
 
+/// State of the fade of a context. See `DrawContext::fade`.
+///
+/// Must be kept between frames.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FadeState {
+    /// Whether the context is fading in or is visible.
+    visible: bool,
+    /// Opacity at the start of the current fade.
+    from: f32,
+    /// Moment when the current fade has started.
+    start: SystemTime,
+}
+
+impl FadeState {
+    /// Builds a `FadeState` that is either fully visible or fully faded out.
     #[inline]
-    fn next(&mut self) -> Option<f32> {
-        if self.n == 0 { return None; }
-        self.n -= 1;
-        Some(1.0)
+    pub fn new(visible: bool) -> FadeState {
+        FadeState {
+            visible,
+            from: if visible { 1.0 } else { 0.0 },
+            start: UNIX_EPOCH,
+        }
     }
 
+    /// Returns the opacity at the given time.
+    fn opacity(&self, now: SystemTime, duration: Duration) -> f32 {
+        let target = if self.visible { 1.0 } else { 0.0 };
+        let percent = Linear.calculate(now, self.start, duration) as f32;
+        self.from + (target - self.from) * percent
+    }
+}
+
+impl Default for FadeState {
     #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.n, Some(self.n))
+    fn default() -> FadeState {
+        FadeState::new(true)
     }
 }
 
-impl ExactSizeIterator for OneGen {
+/// Whether the cursor is hovering a context. Returned by `DrawContext::hover_status`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HoverStatus {
+    /// The cursor isn't over the context.
+    NotHovered,
+    /// The cursor is over the context, and no element with a higher z-index has been hovered.
+    HoveredTop,
+    /// The cursor is over the context, but an element with a higher z-index has been hovered.
+    HoveredOccluded,
+}
+
+/// Represents the alignment of a viewport.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Alignment {
+    /// The horizontal alignment.
+    pub horizontal: HorizontalAlignment,
+    /// The vertical alignment.
+    pub vertical: VerticalAlignment,
+}
+
+impl Alignment {
+    /// Builds an `Alignment` from its horizontal and vertical components.
+    #[inline]
+    pub fn new(horizontal: HorizontalAlignment, vertical: VerticalAlignment) -> Alignment {
+        Alignment {
+            horizontal,
+            vertical,
+        }
+    }
+
+    /// Shortcut for `(center, center)`.
+    #[inline]
+    pub fn center() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Center,
+            vertical: VerticalAlignment::Center,
+        }
+    }
+
+    /// Shortcut for `(center, bottom)`.
+    #[inline]
+    pub fn bottom() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Center,
+            vertical: VerticalAlignment::Bottom,
+        }
+    }
+
+    /// Shortcut for `(center, top)`.
+    #[inline]
+    pub fn top() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Center,
+            vertical: VerticalAlignment::Top,
+        }
+    }
+
+    /// Shortcut for `(right, center)`.
+    #[inline]
+    pub fn right() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Right,
+            vertical: VerticalAlignment::Center,
+        }
+    }
+
+    /// Shortcut for `(left, center)`.
+    #[inline]
+    pub fn left() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Left,
+            vertical: VerticalAlignment::Center,
+        }
+    }
+
+    /// Shortcut for `(left, top)`.
+    #[inline]
+    pub fn top_left() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Left,
+            vertical: VerticalAlignment::Top,
+        }
+    }
+
+    /// Shortcut for `(right, top)`.
+    #[inline]
+    pub fn top_right() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Right,
+            vertical: VerticalAlignment::Top,
+        }
+    }
+
+    /// Shortcut for `(right, bottom)`.
+    #[inline]
+    pub fn bottom_right() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Right,
+            vertical: VerticalAlignment::Bottom,
+        }
+    }
+
+    /// Shortcut for `(left, bottom)`.
+    #[inline]
+    pub fn bottom_left() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlignment::Left,
+            vertical: VerticalAlignment::Bottom,
+        }
+    }
+}
+
+impl From<(HorizontalAlignment, VerticalAlignment)> for Alignment {
+    #[inline]
+    fn from((horizontal, vertical): (HorizontalAlignment, VerticalAlignment)) -> Alignment {
+        Alignment::new(horizontal, vertical)
+    }
+}
+
+/// Represents a continuous alignment of a viewport.
+///
+/// Each component goes from `-1.0` to `1.0`. `-1.0` is the left or the bottom, `0.0` is the
+/// center, and `1.0` is the right or the top. Values in between place the context
+/// proportionally between these positions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AlignmentF {
+    /// The horizontal alignment.
+    pub horizontal: f32,
+    /// The vertical alignment.
+    pub vertical: f32,
+}
+
+impl AlignmentF {
+    /// Returns the alignment that is at `percent` between `self` and `other`, where `0.0` is
+    /// `self` and `1.0` is `other`.
+    #[inline]
+    pub fn lerp(&self, other: &AlignmentF, percent: f32) -> AlignmentF {
+        AlignmentF {
+            horizontal: self.horizontal + (other.horizontal - self.horizontal) * percent,
+            vertical: self.vertical + (other.vertical - self.vertical) * percent,
+        }
+    }
+}
+
+impl From<Alignment> for AlignmentF {
+    #[inline]
+    fn from(alignment: Alignment) -> AlignmentF {
+        AlignmentF {
+            horizontal: match alignment.horizontal {
+                HorizontalAlignment::Left => -1.0,
+                HorizontalAlignment::Center => 0.0,
+                HorizontalAlignment::Right => 1.0,
+            },
+            vertical: match alignment.vertical {
+                VerticalAlignment::Bottom => -1.0,
+                VerticalAlignment::Center => 0.0,
+                VerticalAlignment::Top => 1.0,
+            },
+        }
+    }
+}
+
+/// Size of a chunk of `horizontal_split_mixed` or `vertical_split_mixed`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SizeSpec {
+    /// The chunk has this size in pixels.
+    Fixed(f32),
+    /// The chunk shares the space left by the fixed chunks with the other flexible chunks,
+    /// proportionally to this weight.
+    Flex(f32),
+}
+
+/// Describes a horizontal alignment.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HorizontalAlignment {
+    /// Align in the middle.
+    Center,
+    /// Align left.
+    Left,
+    /// Align right.
+    Right,
+}
+
+/// Describes a vertical alignment.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VerticalAlignment {
+    /// Align in the middle.
+    Center,
+    /// Align top.
+    Top,
+    /// Align bottom.
+    Bottom,
+}
+
+/// Direction of the vertical axis of the viewport. See `UiState::y_axis`.
+///
+/// Whatever the direction, `VerticalAlignment::Top`, `take_top` or the first chunk of a vertical
+/// split are at the top of the screen. Only the coordinates of the matrices passed to the `Draw`
+/// object, of the cursor and of the bounds of the widgets change.
+///
+/// With `YAxis::Down`, the matrices passed to the `Draw` object already invert the `y`
+/// coordinate, so a Vulkan or DirectX backend must pass the positions through instead of
+/// inverting them, like an OpenGL backend does with `YAxis::Up`. The UV coordinates keep the
+/// same convention in both cases, see `Draw::draw_triangle`. Since the geometry is mirrored
+/// vertically, the triangles are wound in the opposite direction, which matters if the backend
+/// culls faces.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum YAxis {
+    /// The y axis points upwards, like in OpenGL. `-1.0` is the bottom of the viewport and `1.0`
+    /// the top.
+    Up,
+    /// The y axis points downwards, like in most 2D renderers. `-1.0` is the top of the viewport
+    /// and `1.0` the bottom.
+    Down,
+}
+
+impl Default for YAxis {
+    #[inline]
+    fn default() -> YAxis {
+        YAxis::Up
+    }
+}
+
+/// Iterator that splits a context in pieces and returns new contexts.
+pub struct SplitsIter<'a, 'b: 'a, I, D: ?Sized + Draw + 'b> {
+    parent: &'a DrawContext<'b, D>,
+    weights: I,
+    total_weight_inverse: f32,
+    current_offset: f32,
+    vertical: bool,
+}
+
+impl<'a, 'b: 'a, I, D: ?Sized + Draw + 'b> Iterator for SplitsIter<'a, 'b, I, D>
+    where I: Iterator<Item = f32>
+{
+    type Item = DrawContext<'b, D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let weight = match self.weights.next() {
+            Some(w) => finite_or_zero(w),
+            None => return None
+        };
+
+        let new_width = if !self.vertical { self.parent.width * weight * self.total_weight_inverse }
+                        else { self.parent.width };
+        let new_height = if self.vertical { self.parent.height * weight * self.total_weight_inverse }
+                         else { self.parent.height };
+
+        let scale_matrix = if self.vertical {
+            Matrix::scale_wh(1.0, weight * self.total_weight_inverse)
+        } else {
+            Matrix::scale_wh(weight * self.total_weight_inverse, 1.0)
+        };
+
+        let pos_matrix = if self.vertical {
+            let y = 1.0 - 2.0 * (self.current_offset + weight * 0.5) * self.total_weight_inverse;
+            Matrix::translate(0.0, y)
+        } else {
+            let x = 2.0 * (self.current_offset + weight * 0.5) * self.total_weight_inverse - 1.0;
+            Matrix::translate(x, 0.0)
+        };
+
+        self.current_offset += weight;
+
+        Some(DrawContext {
+            matrix: self.parent.matrix * pos_matrix * scale_matrix,
+            width: new_width,
+            height: new_height,
+            animation: self.parent.animation,
+            shared1: self.parent.shared1.clone(),
+            shared2: self.parent.shared2.clone(),
+            cursor: self.parent.cursor,
+            cursor_was_pressed: self.parent.cursor_was_pressed,
+            cursor_was_released: self.parent.cursor_was_released,
+            tint: self.parent.tint,
+            z_index: self.parent.z_index,
+            clip: self.parent.clip,
+            hover_suppressed: self.parent.hover_suppressed,
+            layout_parent: self.parent.layout_parent,
+            visual: self.parent.visual,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.weights.size_hint()
+    }
+}
+
+impl<'a, 'b: 'a, I, D: ?Sized + Draw + 'b> ExactSizeIterator for SplitsIter<'a, 'b, I, D>
+    where I: ExactSizeIterator<Item = f32>
+{
+}
+
+/// Iterator that generates `1.0` a certain number of times.
+// TODO: This is required so that `horizontal_split` and `vertical_split` can express their
+//       return type. Should be replaced with `-> impl Iterator` eventually.
+#[derive(Debug, Clone)]
+pub struct OneGen {
+    n: usize
+}
+
+impl Iterator for OneGen {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.n == 0 { return None; }
+        self.n -= 1;
+        Some(1.0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.n, Some(self.n))
+    }
+}
+
+impl ExactSizeIterator for OneGen {
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_1_SQRT_2;
+    use std::f32::consts::FRAC_PI_4;
+    use std::f32::consts::PI;
+
+    use mock;
+    use mock::Call;
+    use mock::MockDraw;
+    use widgets::image;
+    use widgets::image_button;
+    use widgets::label;
+    use animations::Linear;
+    use animations::Translation;
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+    use Alignment;
+    use KeyboardEvent;
+    use UiState;
+    use WidgetId;
+    use HorizontalAlignment;
+    use VerticalAlignment;
+    use YAxis;
+
+    use super::AlignmentF;
+    use super::DrawContext;
+    use super::FadeState;
+    use super::SizeSpec;
+
+    #[test]
+    fn local_viewport_round_trip() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let ctxt = ctxt.rescale(0.5, 0.25, &Alignment::top_left()).margin(0.1, 0.0, 0.0, 0.2);
+
+            let viewport = ctxt.local_to_viewport([0.5, -0.5]);
+            let local = ctxt.viewport_to_local(viewport).unwrap();
+            assert!((local[0] - 0.5).abs() < 0.0001);
+            assert!((local[1] + 0.5).abs() < 0.0001);
+
+            let local = ctxt.viewport_to_local([0.25, 0.75]).unwrap();
+            let viewport = ctxt.local_to_viewport(local);
+            assert!((viewport[0] - 0.25).abs() < 0.0001);
+            assert!((viewport[1] - 0.75).abs() < 0.0001);
+
+            let top_left = ctxt.local_to_viewport([-1.0, 1.0]);
+            assert!((top_left[0] - -0.8).abs() < 0.0001);
+            assert!((top_left[1] - 0.95).abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    fn zero_height_context() {
+        let (mock, _) = mock::frame(800.0, 600.0, Some([0.0, 0.0]), false, false, UNIX_EPOCH,
+                                    |ctxt| {
+            let ctxt = ctxt.vertical_rescale(0.0, &VerticalAlignment::Center);
+
+            assert!(ctxt.is_empty());
+            assert!(ctxt.width_per_height().is_finite());
+            assert!(!ctxt.is_cursor_hovering());
+            assert_eq!(ctxt.cursor_hover_coordinates(), None);
+
+            label::flow(&ctxt, "font", "hello", &HorizontalAlignment::Center);
+            label::contain(&ctxt, "font", "hello", &Alignment::center());
+            label::cover(&ctxt, "font", "hello", &Alignment::center());
+            assert!(!ctxt.cursor_hovered_widget());
+        });
+
+        assert!(mock.calls.is_empty());
+    }
+
+    #[test]
+    fn finish_clears_frame_state() {
+        let mut ui_state = UiState::default();
+        ui_state.keyboard_events.push(KeyboardEvent::Character('a'));
+        ui_state.active_widget = Some(WidgetId::from(1000));
+
+        let shared = ::draw();
+        mock::frame_in(&shared, 800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            ctxt.reserve_widget_id()
+        });
+
+        let summary = shared.finish(&mut ui_state);
+        assert!(!summary.cursor_hovered_widget);
+        assert!(ui_state.keyboard_events.is_empty());
+        assert_eq!(ui_state.active_widget, None);
+
+        // An active widget that has been drawn during the frame is kept.
+        let shared = ::draw();
+        let (_, id) = mock::frame_in(&shared, 800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            ctxt.reserve_widget_id()
+        });
+        ui_state.active_widget = Some(id.clone());
+        shared.finish(&mut ui_state);
+        assert_eq!(ui_state.active_widget, Some(id));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "different number of items")]
+    fn split_weights_lying_len() {
+        /// Iterator that yields three weights but pretends to have two.
+        #[derive(Clone)]
+        struct Liar(u32);
+
+        impl Iterator for Liar {
+            type Item = f32;
+
+            fn next(&mut self) -> Option<f32> {
+                if self.0 == 0 { return None; }
+                self.0 -= 1;
+                Some(1.0)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (2, Some(2))
+            }
+        }
+
+        impl ExactSizeIterator for Liar {}
+
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let _ = ctxt.horizontal_split_weights(Liar(3));
+        });
+    }
+
+    #[test]
+    fn layout_snapshot() {
+        use LayoutNode;
+
+        let mut ui_state = UiState::default();
+
+        // Nothing is recorded by default, even with the accessibility enabled.
+        let shared = ::draw();
+        shared.enable_accessibility();
+        mock::frame_in(&shared, 200.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let _ = image_button::stretch(ctxt, &mut ui_state, "normal", "hovered", "active");
+        });
+        shared.finish(&mut ui_state);
+        assert!(ui_state.layout.is_empty());
+
+        let shared = ::draw();
+        shared.enable_layout_recording();
+        mock::frame_in(&shared, 200.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let toolbar = ctxt.margin(0.0, 0.0, 0.5, 0.0).layout_group("toolbar");
+            for button in toolbar.horizontal_split(2) {
+                let _ = image_button::stretch(&button, &mut ui_state, "normal", "hovered",
+                                              "active");
+            }
+        });
+        shared.finish(&mut ui_state);
+
+        let node = |kind: &str, bounds, children| LayoutNode {
+            kind: kind.to_owned(),
+            bounds,
+            children,
+        };
+        assert_eq!(ui_state.layout, vec![
+            node("toolbar", [-1.0, 0.0, 1.0, 1.0], vec![
+                node("button", [-1.0, 0.0, 0.0, 1.0], vec![]),
+                node("button", [0.0, 0.0, 1.0, 1.0], vec![]),
+            ]),
+        ]);
+
+        #[cfg(feature = "serde")]
+        {
+            use serde_json;
+            assert_eq!(serde_json::to_string(&ui_state.layout).unwrap(),
+                       "[{\"kind\":\"toolbar\",\"bounds\":[-1.0,0.0,1.0,1.0],\"children\":[\
+                         {\"kind\":\"button\",\"bounds\":[-1.0,0.0,0.0,1.0],\"children\":[]},\
+                         {\"kind\":\"button\",\"bounds\":[0.0,0.0,1.0,1.0],\"children\":[]}]}]");
+        }
+    }
+
+    #[test]
+    fn animation_needs_redraw() {
+        let shared = ::draw();
+        let now = UNIX_EPOCH + Duration::from_millis(500);
+        mock::frame_in(&shared, 800.0, 600.0, None, false, false, now, |ctxt| {
+            ctxt.animation_start(Linear, UNIX_EPOCH, Duration::from_secs(10));
+        });
+        assert!(shared.needs_redraw());
+
+        let shared = ::draw();
+        let now = UNIX_EPOCH + Duration::from_secs(2);
+        mock::frame_in(&shared, 800.0, 600.0, None, false, false, now, |ctxt| {
+            ctxt.animation_start(Linear, UNIX_EPOCH, Duration::from_secs(1));
+        });
+        assert!(!shared.needs_redraw());
+        assert!(!shared.finish(&mut UiState::default()).needs_redraw);
+    }
+
+    #[test]
+    fn animation_follows_clock() {
+        fn offset_at(now_ms: u64) -> f32 {
+            let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+            mock::frame(800.0, 600.0, None, false, false, now, |ctxt| {
+                let animation = Translation { initial_offset: [2.0, 0.0] };
+                let ctxt = ctxt.animate(animation, Linear, UNIX_EPOCH, Duration::from_secs(1));
+                ctxt.matrix().0[2][0]
+            }).1
+        }
+
+        assert!((offset_at(0) - 2.0).abs() < 0.0001);
+        assert!((offset_at(250) - 1.5).abs() < 0.0001);
+        assert!((offset_at(500) - 1.0).abs() < 0.0001);
+        assert!(offset_at(1000).abs() < 0.0001);
+        assert!(offset_at(5000).abs() < 0.0001);
+    }
+
+    #[test]
+    fn animate_in() {
+        fn offset_at(ui_state: &mut UiState, now_ms: u64, drawn: bool) -> f32 {
+            let shared = ::draw();
+            let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+            let (_, offset) = mock::frame_in(&shared, 800.0, 600.0, None, false, false, now,
+                                             |ctxt| {
+                if drawn {
+                    let id = ctxt.reserve_widget_id();
+                    let animation = Translation { initial_offset: [2.0, 0.0] };
+                    let ctxt = ctxt.animate_in(ui_state, &id, animation, Linear,
+                                               Duration::from_secs(1));
+                    ctxt.matrix().0[2][0]
+                } else {
+                    0.0
+                }
+            });
+            shared.finish(ui_state);
+            offset
+        }
+
+        let mut ui_state = UiState::default();
+
+        assert!((offset_at(&mut ui_state, 5000, true) - 2.0).abs() < 0.0001);
+        assert!((offset_at(&mut ui_state, 5250, true) - 1.5).abs() < 0.0001);
+        assert!(offset_at(&mut ui_state, 6000, true).abs() < 0.0001);
+        assert!(offset_at(&mut ui_state, 7000, true).abs() < 0.0001);
+
+        // Not drawing the element restarts the animation.
+        offset_at(&mut ui_state, 7500, false);
+        assert!(ui_state.appear_times.is_empty());
+        assert!((offset_at(&mut ui_state, 8000, true) - 2.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn first_appearance() {
+        fn frame(ui_state: &mut UiState, drawn: bool) -> bool {
+            let shared = ::draw();
+            let (_, first) = mock::frame_in(&shared, 800.0, 600.0, None, false, false, UNIX_EPOCH,
+                                            |ctxt| {
+                if drawn {
+                    let id = ctxt.reserve_widget_id();
+                    let first = ctxt.is_first_appearance(ui_state, &id);
+                    assert_eq!(ctxt.is_first_appearance(ui_state, &id), first);
+                    first
+                } else {
+                    false
+                }
+            });
+            shared.finish(ui_state);
+            first
+        }
+
+        let mut ui_state = UiState::default();
+
+        assert!(frame(&mut ui_state, true));
+        assert!(!frame(&mut ui_state, true));
+        assert!(!frame(&mut ui_state, true));
+
+        frame(&mut ui_state, false);
+        assert!(frame(&mut ui_state, true));
+        assert!(!frame(&mut ui_state, true));
+    }
+
+    #[test]
+    fn with_cursor() {
+        let mut ui_state = UiState::default();
+        let (mock, _) = mock::frame(800.0, 600.0, Some([0.9, 0.9]), false, false, UNIX_EPOCH,
+                                    |ctxt| {
+            let button = ctxt.rescale(0.5, 0.5, &Alignment::center());
+            assert!(!button.is_cursor_hovering());
+
+            let moved = button.with_cursor(Some([0.1, -0.2]));
+            assert!(moved.is_cursor_hovering());
+            let _ = image_button::stretch(&moved, &mut ui_state, "normal", "hovered", "active");
+
+            assert!(!button.with_cursor(None).is_cursor_hovering());
+            let _ = image_button::stretch(&button, &mut ui_state, "normal", "hovered", "active");
+        });
+
+        assert_eq!(mock.images(), vec!["hovered", "normal"]);
+    }
+
+    #[test]
+    fn corners() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            assert_eq!(ctxt.corners(), [[-1.0, 1.0], [1.0, 1.0], [1.0, -1.0], [-1.0, -1.0]]);
+
+            // The right half of the viewport, translated by a quarter of the viewport to the top.
+            let ctxt = ctxt.margin(-0.25, 0.0, 0.25, 0.5);
+            let expected = [[0.0, 1.5], [1.0, 1.5], [1.0, -0.5], [0.0, -0.5]];
+            for (corner, expected) in ctxt.corners().iter().zip(expected.iter()) {
+                assert!((corner[0] - expected[0]).abs() < 0.0001);
+                assert!((corner[1] - expected[1]).abs() < 0.0001);
+            }
+        });
+    }
+
+    #[test]
+    fn animate_margin() {
+        let shared = ::draw();
+        let now = UNIX_EPOCH + Duration::from_millis(500);
+        mock::frame_in(&shared, 800.0, 600.0, None, false, false, now, |ctxt| {
+            let animated = ctxt.animate_margin([0.0, 0.0, 0.0, 1.0], [0.2, 0.4, 0.0, 0.0], Linear,
+                                               UNIX_EPOCH, Duration::from_secs(1));
+            let expected = ctxt.margin(0.1, 0.2, 0.0, 0.5);
+
+            assert_eq!(animated.matrix(), expected.matrix());
+            assert!((animated.width() - 240.0).abs() < 0.001);
+            assert!((animated.height() - 540.0).abs() < 0.001);
+        });
+        assert!(shared.needs_redraw());
+    }
+
+    #[test]
+    fn split_mixed() {
+        mock::frame(300.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let check_widths = |specs: &[SizeSpec], expected: &[f32]| {
+                let chunks = ctxt.horizontal_split_mixed(specs);
+                assert_eq!(chunks.len(), expected.len());
+                for (chunk, width) in chunks.iter().zip(expected) {
+                    assert!((chunk.width() - width).abs() < 0.001);
+                }
+            };
+
+            let specs = [SizeSpec::Fixed(50.0), SizeSpec::Flex(1.0), SizeSpec::Fixed(50.0)];
+            check_widths(&specs, &[50.0, 200.0, 50.0]);
+            let chunks = ctxt.horizontal_split_mixed(&specs);
+            assert!((chunks[1].bounding_box()[0] + 2.0 / 3.0).abs() < 0.0001);
+            assert!((chunks[2].bounding_box()[2] - 1.0).abs() < 0.0001);
+
+            check_widths(&[SizeSpec::Flex(1.0), SizeSpec::Fixed(60.0), SizeSpec::Flex(3.0)],
+                         &[60.0, 60.0, 180.0]);
+
+            // The fixed chunks are too large.
+            check_widths(&[SizeSpec::Fixed(400.0), SizeSpec::Flex(1.0), SizeSpec::Fixed(200.0)],
+                         &[200.0, 0.0, 100.0]);
+
+            let chunks = ctxt.vertical_split_mixed(&[SizeSpec::Fixed(25.0), SizeSpec::Fixed(25.0)]);
+            assert!((chunks[1].height() - 25.0).abs() < 0.0001);
+            assert!(chunks[1].bounding_box()[1].abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    fn rescale_f() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let alignment = AlignmentF { horizontal: -1.0, vertical: 1.0 };
+            assert_eq!(ctxt.rescale_f(0.5, 0.25, alignment).matrix(),
+                       ctxt.rescale(0.5, 0.25, &Alignment::top_left()).matrix());
+            assert_eq!(AlignmentF::from(Alignment::top_left()), alignment);
+
+            // Halfway between the left and the center.
+            let center = AlignmentF::from(Alignment::center());
+            let bounds = ctxt.rescale_f(0.5, 0.5, alignment.lerp(&center, 0.5)).bounding_box();
+            assert!((bounds[0] + 0.75).abs() < 0.0001);
+            assert!((bounds[3] - 0.75).abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    fn repeat() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let mut calls = Vec::new();
+            ctxt.repeat_horizontal(4, |index, chunk| {
+                let bounds = chunk.bounding_box();
+                calls.push(index);
+                assert!((bounds[0] - (-1.0 + 0.5 * index as f32)).abs() < 0.0001);
+                assert!((bounds[2] - (-0.5 + 0.5 * index as f32)).abs() < 0.0001);
+                assert!((chunk.width() - 200.0).abs() < 0.001);
+            });
+            assert_eq!(calls, vec![0, 1, 2, 3]);
+
+            let mut calls = Vec::new();
+            ctxt.repeat_vertical(3, |index, chunk| {
+                calls.push(index);
+                let top = chunk.bounding_box()[3];
+                assert!((top - (1.0 - 2.0 * index as f32 / 3.0)).abs() < 0.0001);
+            });
+            assert_eq!(calls, vec![0, 1, 2]);
+
+            ctxt.repeat_horizontal(0, |_, _| panic!());
+        });
+    }
+
+    #[test]
+    fn delta_time() {
+        let mut ui_state = UiState::default();
+
+        let shared = ::draw();
+        assert_eq!(shared.delta_time(&ui_state), Duration::new(0, 0));
+        let now = UNIX_EPOCH + Duration::from_millis(1000);
+        mock::frame_in(&shared, 800.0, 600.0, None, false, false, now, |_| ());
+        assert_eq!(shared.delta_time(&ui_state), Duration::new(0, 0));
+        shared.finish(&mut ui_state);
+
+        let shared = ::draw();
+        let now = UNIX_EPOCH + Duration::from_millis(1016);
+        mock::frame_in(&shared, 800.0, 600.0, None, false, false, now, |ctxt| {
+            assert_eq!(ctxt.delta_time(&ui_state), Duration::from_millis(16));
+        });
+        assert_eq!(shared.delta_time(&ui_state), Duration::from_millis(16));
+        shared.finish(&mut ui_state);
+        assert_eq!(ui_state.last_frame_time, Some(UNIX_EPOCH + Duration::from_millis(1016)));
+    }
+
+    #[test]
+    fn draw_border() {
+        let (mock, _) = mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let card = ctxt.margin(0.25, 0.25, 0.25, 0.25);
+            card.draw_border(4.0, [1.0, 0.0, 0.0, 1.0]);
+            // Nothing is drawn for an empty border.
+            card.draw_border(0.0, [1.0, 0.0, 0.0, 1.0]);
+        });
+
+        // The card is 400x300 pixels, and the lines are 2 pixels inside of its edges.
+        let expected = [[-0.495, 0.49333], [0.495, 0.49333], [0.495, -0.49333],
+                        [-0.495, -0.49333]];
+
+        assert_eq!(mock.calls.len(), 4);
+        for (n, call) in mock.calls.iter().enumerate() {
+            match *call {
+                Call::Line { from, to, thickness_px, color, .. } => {
+                    assert_eq!(thickness_px, 4.0);
+                    assert_eq!(color, [1.0, 0.0, 0.0, 1.0]);
+                    let next = expected[(n + 1) % 4];
+                    assert!((from[0] - expected[n][0]).abs() < 0.0001);
+                    assert!((from[1] - expected[n][1]).abs() < 0.0001);
+                    assert!((to[0] - next[0]).abs() < 0.0001);
+                    assert!((to[1] - next[1]).abs() < 0.0001);
+                },
+                _ => panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn constrain() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            // Without constraint, nothing changes.
+            let same = ctxt.constrain().build(&Alignment::center());
+            assert_eq!(same.bounding_box(), ctxt.bounding_box());
+
+            // The aspect ratio alone gives a 600x600 square, which is then shrunk to 300x300.
+            let square = ctxt.constrain().aspect(1.0).max_width_px(300.0)
+                             .build(&Alignment::top_left());
+            assert!((square.width() - 300.0).abs() < 0.001);
+            assert!((square.height() - 300.0).abs() < 0.001);
+            let bounds = square.bounding_box();
+            assert!((bounds[0] + 1.0).abs() < 0.0001);
+            assert!((bounds[1] - 0.0).abs() < 0.0001);
+            assert!((bounds[2] + 0.25).abs() < 0.0001);
+            assert!((bounds[3] - 1.0).abs() < 0.0001);
+
+            // The maximum width doesn't apply if the aspect ratio already makes it smaller.
+            let small = ctxt.constrain().aspect(0.5).max_width_px(400.0)
+                            .build(&Alignment::center());
+            assert!((small.width() - 300.0).abs() < 0.001);
+            assert!((small.height() - 600.0).abs() < 0.001);
+
+            // The last constraint wins.
+            let tall = ctxt.constrain().aspect(2.0).max_width_px(200.0).min_height_px(200.0)
+                           .build(&Alignment::center());
+            assert!((tall.width() - 400.0).abs() < 0.001);
+            assert!((tall.height() - 200.0).abs() < 0.001);
+        });
+    }
+
+    #[test]
+    fn last_frame_bounds() {
+        let mut ui_state = UiState::default();
+
+        // Nothing is recorded by default.
+        let shared = ::draw();
+        mock::frame_in(&shared, 800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            ctxt.reserve_widget_id()
+        });
+        shared.finish(&mut ui_state);
+        assert!(ui_state.last_frame_bounds.is_empty());
+
+        let shared = ::draw();
+        shared.enable_bounds_recording();
+        let (_, id) = mock::frame_in(&shared, 800.0, 600.0, Some([-0.5, 0.0]), true, false,
+                                     UNIX_EPOCH, |ctxt| {
+            ctxt.margin(0.0, 0.5, 0.0, 0.0).reserve_widget_id()
+        });
+        shared.finish(&mut ui_state);
+        assert_eq!(ui_state.last_frame_bounds.get(&id), Some(&[-1.0, -1.0, 0.0, 1.0]));
+
+        // The widget has moved to the right half, but the release is still routed to it.
+        let shared = ::draw();
+        shared.enable_bounds_recording();
+        mock::frame_in(&shared, 800.0, 600.0, Some([-0.5, 0.0]), false, true, UNIX_EPOCH, |ctxt| {
+            assert!(ctxt.was_cursor_over_last_frame(&ui_state, &id));
+            assert!(!ctxt.was_cursor_over_last_frame(&ui_state, &WidgetId::from(1000)));
+
+            let widget = ctxt.margin(0.0, 0.0, 0.0, 0.5);
+            assert_eq!(widget.reserve_widget_id(), id);
+            assert!(!widget.is_cursor_hovering());
+
+            let moved = ctxt.with_cursor(Some([0.5, 0.0]));
+            assert!(!moved.was_cursor_over_last_frame(&ui_state, &id));
+        });
+        shared.finish(&mut ui_state);
+        assert_eq!(ui_state.last_frame_bounds.get(&id), Some(&[0.0, -1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn fade() {
+        fn opacity(state: &mut FadeState, visible: bool, now_ms: u64) -> Option<f32> {
+            let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+            mock::frame(800.0, 600.0, None, false, false, now, |ctxt| {
+                ctxt.fade(visible, state, Duration::from_millis(200)).map(|c| c.opacity())
+            }).1
+        }
+
+        let mut state = FadeState::default();
+        assert_eq!(opacity(&mut state, true, 1000), Some(1.0));
+
+        // Fading out.
+        assert_eq!(opacity(&mut state, false, 1000), Some(1.0));
+        let half = opacity(&mut state, false, 1100).unwrap();
+        assert!((half - 0.5).abs() < 0.0001);
+        assert_eq!(opacity(&mut state, false, 1200), None);
+        assert_eq!(opacity(&mut state, false, 5000), None);
+
+        // Fading in, then out again before the end.
+        assert_eq!(opacity(&mut state, true, 6000), None);
+        let in_between = opacity(&mut state, true, 6050).unwrap();
+        assert!((in_between - 0.25).abs() < 0.0001);
+        let back = opacity(&mut state, false, 6050).unwrap();
+        assert!((back - 0.25).abs() < 0.0001);
+        assert_eq!(opacity(&mut state, false, 6250), None);
+
+        let mut hidden = FadeState::new(false);
+        assert_eq!(opacity(&mut hidden, false, 1000), None);
+    }
+
+    #[test]
+    fn ui_scale() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            assert_eq!(ctxt.ui_scale(), 1.0);
+            let normal = ctxt.fixed_size(100.0, 100.0, &Alignment::center());
+            assert!((normal.width() - 100.0).abs() < 0.001);
+            assert!((normal.height() - 100.0).abs() < 0.001);
+        });
+
+        let mut mock = MockDraw::new();
+        let shared = ::draw();
+        let ui_state = UiState { ui_scale: 2.0, .. UiState::default() };
+        {
+            let ctxt = shared.draw(800.0, 600.0, &mut mock, &ui_state, None, false, false, None,
+                                   UNIX_EPOCH);
+            assert_eq!(ctxt.ui_scale(), 2.0);
+
+            let scaled = ctxt.fixed_size(100.0, 100.0, &Alignment::center());
+            assert!((scaled.width() - 200.0).abs() < 0.001);
+            assert!((scaled.height() - 200.0).abs() < 0.001);
+            assert!((scaled.bounding_box()[2] - 0.25).abs() < 0.0001);
+
+            let inner = ctxt.margin_px(10.0, 20.0, 30.0, 40.0);
+            assert!((inner.width() - 680.0).abs() < 0.001);
+            assert!((inner.height() - 520.0).abs() < 0.001);
+
+            let (top, _) = ctxt.take_top(50.0);
+            assert!((top.height() - 100.0).abs() < 0.001);
+        }
+
+        let ui_state = UiState { ui_scale: f32::NAN, .. UiState::default() };
+        let ctxt = shared.draw(800.0, 600.0, &mut mock, &ui_state, None, false, false, None,
+                               UNIX_EPOCH);
+        assert_eq!(ctxt.ui_scale(), 1.0);
+    }
+
+    #[test]
+    fn offset_px() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let moved = ctxt.offset_px(0.0, -10.0);
+            assert_eq!(moved.width(), 800.0);
+            assert_eq!(moved.height(), 600.0);
+            let bounds = moved.bounding_box();
+            assert!(((bounds[1] + bounds[3]) / 2.0 + 20.0 / 600.0).abs() < 0.0001);
+            assert!(((bounds[0] + bounds[2]) / 2.0).abs() < 0.0001);
+
+            // The offset is in pixels, whatever the size of the context.
+            let half = ctxt.margin(0.0, 0.5, 0.0, 0.0).offset_px(40.0, 0.0);
+            let bounds = half.bounding_box();
+            assert!((bounds[0] + 0.9).abs() < 0.0001);
+            assert!((bounds[2] - 0.1).abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    fn safe_area() {
+        let mut mock = MockDraw::new();
+        let shared = ::draw();
+        let mut ui_state = UiState { ui_scale: 2.0, .. UiState::default() };
+        let ctxt = shared.draw(800.0, 600.0, &mut mock, &ui_state, None, false, false, None,
+                               UNIX_EPOCH);
+        assert_eq!(ctxt.safe_area(&ui_state).bounding_box(), ctxt.bounding_box());
+
+        // The insets are in physical pixels and are not multiplied by the UI scale.
+        ui_state.safe_area_insets = [44.0, 0.0, 0.0, f32::NAN];
+        let safe = ctxt.safe_area(&ui_state);
+        let bounds = safe.bounding_box();
+        assert!((bounds[3] - (1.0 - 2.0 * 44.0 / 600.0)).abs() < 0.0001);
+        assert_eq!(bounds[0], -1.0);
+        assert_eq!(bounds[1], -1.0);
+        assert!((safe.height() - 556.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn split_with_gutters() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let (chunks, gutters) = ctxt.horizontal_split_with_gutters(3, 0.05);
+            assert_eq!(chunks.len(), 3);
+            assert_eq!(gutters.len(), 2);
+            for chunk in &chunks {
+                assert!((chunk.width() - 240.0).abs() < 0.001);
+                assert!((chunk.height() - 600.0).abs() < 0.001);
+            }
+            for gutter in &gutters {
+                assert!((gutter.width() - 40.0).abs() < 0.001);
+            }
+
+            // The gutters are exactly between the chunks.
+            assert!((gutters[0].bounding_box()[0] - chunks[0].bounding_box()[2]).abs() < 0.0001);
+            assert!((gutters[1].bounding_box()[2] - chunks[2].bounding_box()[0]).abs() < 0.0001);
+            assert!((chunks[2].bounding_box()[2] - 1.0).abs() < 0.0001);
+
+            let (chunks, gutters) = ctxt.vertical_split_with_gutters(2, 0.1);
+            assert!((chunks[0].height() - 270.0).abs() < 0.001);
+            assert!((gutters[0].height() - 60.0).abs() < 0.001);
+            assert!((chunks[0].bounding_box()[3] - 1.0).abs() < 0.0001);
+
+            // Gutters that don't fit.
+            let (chunks, gutters) = ctxt.horizontal_split_with_gutters(3, 0.8);
+            assert_eq!(chunks[1].width(), 0.0);
+            assert!((gutters[1].width() - 400.0).abs() < 0.001);
+
+            let (chunks, gutters) = ctxt.horizontal_split_with_gutters(1, 0.5);
+            assert_eq!(chunks[0].bounding_box(), ctxt.bounding_box());
+            assert!(gutters.is_empty());
+            assert!(ctxt.horizontal_split_with_gutters(0, 0.5).0.is_empty());
+        });
+    }
+
+    #[test]
+    fn focus_grid() {
+        fn frame(ui_state: &mut UiState, key: Option<KeyboardEvent>) -> Vec<WidgetId> {
+            ui_state.keyboard_events.extend(key);
+
+            let mut mock = MockDraw::new();
+            let shared = ::draw();
+            let ids = {
+                let ctxt = shared.draw(800.0, 600.0, &mut mock, ui_state, None, false, false,
+                                       None, UNIX_EPOCH);
+                let mut ids = Vec::new();
+                for row in ctxt.vertical_split(2) {
+                    for cell in row.horizontal_split(2) {
+                        let id = cell.reserve_widget_id();
+                        cell.register_focusable_grid(&id);
+                        ids.push(id);
+                    }
+                }
+                ids
+            };
+            shared.finish(ui_state);
+            ids
+        }
+
+        // The keys move the focus on the screen whatever the direction of the y axis.
+        for &y_axis in &[YAxis::Up, YAxis::Down] {
+            // The cells are top-left, top-right, bottom-left, bottom-right.
+            let mut ui_state = UiState { y_axis, .. UiState::default() };
+            let ids = frame(&mut ui_state, None);
+            assert_eq!(ui_state.focused_widget, None);
+
+            frame(&mut ui_state, Some(KeyboardEvent::Right));
+            assert_eq!(ui_state.focused_widget, Some(ids[0].clone()));
+            frame(&mut ui_state, Some(KeyboardEvent::Right));
+            assert_eq!(ui_state.focused_widget, Some(ids[1].clone()));
+            frame(&mut ui_state, Some(KeyboardEvent::Down));
+            assert_eq!(ui_state.focused_widget, Some(ids[3].clone()));
+            frame(&mut ui_state, Some(KeyboardEvent::Left));
+            assert_eq!(ui_state.focused_widget, Some(ids[2].clone()));
+
+            // There is nothing further down.
+            frame(&mut ui_state, Some(KeyboardEvent::Down));
+            assert_eq!(ui_state.focused_widget, Some(ids[2].clone()));
+            assert!(ui_state.keyboard_events.is_empty());
+
+            frame(&mut ui_state, Some(KeyboardEvent::Up));
+            assert_eq!(ui_state.focused_widget, Some(ids[0].clone()));
+        }
+    }
+
+    #[test]
+    fn focus_distance_in_pixels() {
+        fn frame(ui_state: &mut UiState, key: KeyboardEvent) -> Vec<WidgetId> {
+            ui_state.keyboard_events.push(key);
+
+            let shared = ::draw();
+            let (_, ids) = mock::frame_in(&shared, 800.0, 200.0, None, false, false, UNIX_EPOCH,
+                                          |ctxt| {
+                let start = ctxt.fixed_size(20.0, 20.0, &Alignment::center());
+                let cells = [start.clone(), start.offset_px(100.0, 40.0),
+                             start.offset_px(200.0, 0.0)];
+                cells.iter().map(|cell| {
+                    let id = cell.reserve_widget_id();
+                    cell.register_focusable_grid(&id);
+                    id
+                }).collect()
+            });
+            shared.finish(ui_state);
+            ids
+        }
+
+        // The second cell is 100 pixels to the right and 40 pixels up, which costs less than the
+        // 200 pixels to the third one. In viewport coordinates, the vertical offset would be
+        // larger than the horizontal one because the viewport is wide.
+        let mut ui_state = UiState::default();
+        let ids = frame(&mut ui_state, KeyboardEvent::Right);
+        assert_eq!(ui_state.focused_widget, Some(ids[0].clone()));
+        frame(&mut ui_state, KeyboardEvent::Right);
+        assert_eq!(ui_state.focused_widget, Some(ids[1].clone()));
+    }
+
+    #[test]
+    fn clicked_strict() {
+        // Draws two widgets side by side, and returns whether each of them has been clicked.
+        fn frame(ui_state: &mut UiState, cursor: [f32; 2], pressed: bool, released: bool)
+                 -> (bool, bool)
+        {
+            mock::frame(800.0, 600.0, Some(cursor), pressed, released, UNIX_EPOCH, |ctxt| {
+                let left = ctxt.horizontal_rescale(0.5, &HorizontalAlignment::Left);
+                let right = ctxt.horizontal_rescale(0.5, &HorizontalAlignment::Right);
+                let left_id = left.reserve_widget_id();
+                let right_id = right.reserve_widget_id();
+                (left.clicked_strict(ui_state, &left_id), right.clicked_strict(ui_state, &right_id))
+            }).1
+        }
+
+        let mut ui_state = UiState::default();
+
+        // Press and release on the same widget.
+        assert_eq!(frame(&mut ui_state, [-0.5, 0.0], true, false), (false, false));
+        assert!(ui_state.active_widget.is_some());
+        assert_eq!(frame(&mut ui_state, [-0.5, 0.0], false, true), (true, false));
+        assert_eq!(ui_state.active_widget, None);
+
+        // Press on the left widget, and release on the right one.
+        assert_eq!(frame(&mut ui_state, [-0.5, 0.0], true, false), (false, false));
+        assert_eq!(frame(&mut ui_state, [0.5, 0.0], false, true), (false, false));
+        assert_eq!(ui_state.active_widget, None);
+
+        // Release without any press.
+        assert_eq!(frame(&mut ui_state, [0.5, 0.0], false, true), (false, false));
+    }
+
+    #[test]
+    fn snap_to_pixels() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            // The context is 400x300 pixels, and its bottom-left hand corner is at 100.3 and 0.75
+            // pixels from the bottom-left hand corner of the viewport.
+            let even = ctxt.margin(0.0, 0.5, 0.5, 0.0).offset_px(100.3, -299.25);
+            let snapped = even.snap_to_pixels();
+
+            let before = even.matrix().0;
+            let after = snapped.matrix().0;
+            assert!((after[2][0] - (300.0 / 400.0 - 1.0)).abs() < 0.0001);
+            assert!((after[2][1] - (151.0 / 300.0 - 1.0)).abs() < 0.0001);
+            assert_eq!(after[0], before[0]);
+            assert_eq!(after[1], before[1]);
+            assert_eq!(snapped.width(), even.width());
+
+            // Snapping twice doesn't move the context further.
+            assert_eq!(snapped.snap_to_pixels().matrix(), snapped.matrix());
+
+            // The context is 101x51 pixels and centered, so its edges are on half pixels.
+            let odd = ctxt.fixed_size(101.0, 51.0, &Alignment::center());
+            let snapped = odd.snap_to_pixels().matrix().0;
+            let left_px = (snapped[2][0] - snapped[0][0] + 1.0) * 400.0;
+            let bottom_px = (snapped[2][1] - snapped[1][1] + 1.0) * 300.0;
+            let right_px = (snapped[2][0] + snapped[0][0] + 1.0) * 400.0;
+            assert!((left_px - left_px.round()).abs() < 0.001);
+            assert!((bottom_px - bottom_px.round()).abs() < 0.001);
+            assert!((right_px - left_px - 101.0).abs() < 0.001);
+            assert_eq!(snapped[0], odd.matrix().0[0]);
+        });
+    }
+
+    #[test]
+    fn y_axis() {
+        // Returns the vertical center of the top quarter and of the first chunk of a vertical
+        // split, and whether the top quarter is hovered.
+        fn frame(y_axis: YAxis, cursor: [f32; 2]) -> (f32, f32, bool) {
+            let mut mock = MockDraw::new();
+            let shared = ::draw();
+            let ui_state = UiState { y_axis, .. UiState::default() };
+            let ctxt = shared.draw(800.0, 600.0, &mut mock, &ui_state, Some(cursor), false, false,
+                                   None, UNIX_EPOCH);
+            let top = ctxt.vertical_rescale(0.25, &VerticalAlignment::Top);
+            let first = ctxt.vertical_split(2).next().unwrap();
+            (top.matrix().0[2][1], first.matrix().0[2][1], top.is_cursor_hovering())
+        }
+
+        let (top, first, hovered) = frame(YAxis::Up, [0.0, 0.9]);
+        assert!((top - 0.75).abs() < 0.0001);
+        assert!((first - 0.5).abs() < 0.0001);
+        assert!(hovered);
+
+        // With a y axis that points downwards, the top of the viewport is at `-1.0`.
+        let (top, first, hovered) = frame(YAxis::Down, [0.0, -0.9]);
+        assert!((top + 0.75).abs() < 0.0001);
+        assert!((first + 0.5).abs() < 0.0001);
+        assert!(hovered);
+        assert!(!frame(YAxis::Down, [0.0, 0.9]).2);
+    }
+
+    #[test]
+    fn centered_content() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let expected = ctxt.uniform_margin(0.1, 0.1, 0.1, 0.1)
+                               .enforce_aspect_ratio_downscale(2.0, &Alignment::center());
+            let content = ctxt.centered_content(0.1, 2.0);
+            assert_eq!(content.matrix(), expected.matrix());
+            assert!((content.width_per_height() - 2.0).abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    fn split_off_aspect() {
+        mock::frame(200.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let (avatar, text) = ctxt.split_off_aspect(1.0, &HorizontalAlignment::Left);
+            assert!((avatar.width_per_height() - 1.0).abs() < 0.0001);
+            assert!((text.width_per_height() - 1.0).abs() < 0.0001);
+            assert!((avatar.local_to_viewport([-1.0, 1.0])[0] + 1.0).abs() < 0.0001);
+            assert!((avatar.local_to_viewport([1.0, 1.0])[0]).abs() < 0.0001);
+            assert!((text.local_to_viewport([-1.0, 1.0])[0]).abs() < 0.0001);
+            assert!((text.local_to_viewport([1.0, 1.0])[0] - 1.0).abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    fn cursor_pressure() {
+        let mut mock = MockDraw::new();
+        let shared = ::draw();
+        let ui_state = UiState::default();
+        let ctxt = shared.draw(800.0, 600.0, &mut mock, &ui_state, None, false, false, Some(0.7),
+                               UNIX_EPOCH);
+
+        let child = ctxt.rescale(0.5, 0.5, &Alignment::center());
+        assert_eq!(child.cursor_pressure(), Some(0.7));
+    }
+
+    #[test]
+    fn flip_cursor_y() {
+        let shared = ::draw();
+        shared.flip_cursor_y();
+
+        // The cursor is at the top of the window in y-down coordinates.
+        mock::frame_in(&shared, 800.0, 600.0, Some([0.0, -0.9]), false, false, UNIX_EPOCH, |ctxt| {
+            assert!(ctxt.vertical_rescale(0.2, &VerticalAlignment::Top).is_cursor_hovering());
+            assert!(!ctxt.vertical_rescale(0.2, &VerticalAlignment::Bottom).is_cursor_hovering());
+        });
+    }
+
+    #[test]
+    fn consume_click() {
+        let mut ui_state = UiState::default();
+        mock::frame(800.0, 600.0, Some([0.0, 0.0]), true, false, UNIX_EPOCH, |ctxt| {
+            assert!(ctxt.cursor_was_pressed());
+
+            let _ = image_button::stretch(ctxt, &mut ui_state, "a", "b", "c");
+            let button_id = ui_state.active_widget.clone();
+            assert!(button_id.is_some());
+            assert!(!ctxt.cursor_was_pressed());
+
+            // The second button is under the cursor but doesn't see the press.
+            let _ = image_button::stretch(ctxt, &mut ui_state, "a", "b", "c");
+            assert_eq!(ui_state.active_widget, button_id);
+
+            ui_state.scroll_delta = [0.0, 3.0];
+            assert_eq!(ctxt.consume_scroll(&mut ui_state), [0.0, 3.0]);
+            assert_eq!(ctxt.consume_scroll(&mut ui_state), [0.0, 0.0]);
+        });
+    }
+
+    #[test]
+    fn invisible_not_hovered() {
+        let shared = ::draw();
+        mock::frame_in(&shared, 800.0, 600.0, Some([0.0, 0.0]), true, false, UNIX_EPOCH, |ctxt| {
+            let faded = ctxt.with_tint([1.0, 1.0, 1.0, 0.0]);
+            assert_eq!(faded.opacity(), 0.0);
+            assert!(!faded.is_cursor_hovering());
+            assert_eq!(faded.cursor_hover_coordinates(), None);
+
+            let mut ui_state = UiState::default();
+            image::stretch(&faded, "image");
+            assert!(!image_button::stretch(&faded, &mut ui_state, "a", "b", "c").clicked());
+            assert_eq!(ui_state.active_widget, None);
+            assert!(!ctxt.cursor_hovered_widget());
+
+            let translucent = ctxt.with_tint([1.0, 1.0, 1.0, 0.5]);
+            assert_eq!(translucent.opacity(), 0.5);
+            assert!(translucent.is_cursor_hovering());
+        });
+
+        assert!(!shared.cursor_hovered_widget());
+    }
+
+    #[test]
+    fn suppress_hover() {
+        let shared = ::draw();
+        mock::frame_in(&shared, 800.0, 600.0, Some([0.0, 0.0]), false, false, UNIX_EPOCH, |ctxt| {
+            let suppressed = ctxt.suppress_hover();
+            label::flow(&suppressed, "font", "hello", &HorizontalAlignment::Center);
+            image::stretch(&suppressed.rescale(0.5, 0.5, &Alignment::center()), "image");
+            assert!(!ctxt.cursor_hovered_widget());
+            assert!(!shared.cursor_hovered_widget());
+
+            // The parent widget decides that the group is hovered.
+            ctxt.set_cursor_hovered_widget();
+        });
+
+        assert!(shared.cursor_hovered_widget());
+    }
+
+    #[test]
+    fn nested_clip() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            assert_eq!(ctxt.clip(), None);
+
+            let outer = ctxt.margin(0.0, 0.5, 0.0, 0.0).with_clip();
+            assert_eq!(outer.clip(), Some([-1.0, -1.0, 0.0, 1.0]));
+
+            // The inner context overflows the outer one.
+            let inner = outer.rescale(2.0, 0.5, &Alignment::center()).with_clip();
+            assert_eq!(inner.clip(), Some([-1.0, -0.5, 0.0, 0.5]));
+        });
+    }
+
+    #[test]
+    fn cursor_angle() {
+        fn angle(cursor: [f32; 2]) -> Option<f32> {
+            mock::frame(800.0, 600.0, Some(cursor), false, false, UNIX_EPOCH, |ctxt| {
+                ctxt.rescale(0.5, 0.5, &Alignment::center()).cursor_angle()
+            }).1
+        }
+
+        assert!(angle([0.4, 0.0]).unwrap().abs() < 0.0001);
+        assert!((angle([0.0, 0.4]).unwrap() - PI / 2.0).abs() < 0.0001);
+        assert!((angle([-0.4, 0.0]).unwrap().abs() - PI).abs() < 0.0001);
+
+        // In the corner of the context, but outside of the ellipse.
+        assert_eq!(angle([0.45, 0.45]), None);
+        assert_eq!(angle([0.9, 0.0]), None);
+    }
+
+    #[test]
+    fn hovered_widgets() {
+        let shared = ::draw();
+        let (_, (first, second, third)) = mock::frame_in(&shared, 800.0, 600.0, Some([0.0, 0.0]),
+                                                         false, false, UNIX_EPOCH, |ctxt| {
+            let first = ctxt.reserve_widget_id();
+            let second = ctxt.reserve_widget_id();
+            let third = ctxt.reserve_widget_id();
+
+            ctxt.note_hover(&first);
+            ctxt.rescale(0.5, 0.5, &Alignment::center()).note_hover(&second);
+            ctxt.note_hover(&first);
+            ctxt.note_hover(&third);
+            (first, second, third)
+        });
+
+        assert_eq!(shared.hovered_widgets(), vec![first, second, third]);
+    }
+
+    #[test]
+    fn hover_fast_path() {
+        use super::is_axis_aligned;
+        use super::is_point_in_axis_aligned_rectangle;
+        use super::is_point_in_rectangle;
+        use super::is_point_in_transformed_rectangle;
+        use Matrix;
+
+        let points = [[0.0, 0.0], [0.3, 0.1], [0.5, 0.5], [0.59, -0.19], [0.61, 0.0],
+                      [0.2, -0.21], [-0.5, 0.5], [0.5, 2.0]];
+
+        let matrix = Matrix::translate(0.4, 0.0) * Matrix::scale_wh(-0.2, 0.2);
+        assert!(is_axis_aligned(&matrix));
+        for point in &points {
+            assert_eq!(is_point_in_axis_aligned_rectangle(&matrix, point),
+                       is_point_in_transformed_rectangle(&matrix, point));
+        }
+
+        let matrix = Matrix::translate(0.4, 0.0) * Matrix::rotate(FRAC_PI_4) *
+                     Matrix::scale_wh(0.4, 0.05);
+        assert!(!is_axis_aligned(&matrix));
+        assert!(is_point_in_rectangle(&matrix, &[0.5, -0.1]));
+        assert!(!is_point_in_rectangle(&matrix, &[0.5, 0.1]));
+    }
+
+    #[test]
+    fn hover_grace() {
+        fn frame(ui_state: &mut UiState, cursor: [f32; 2], now_ms: u64) -> bool {
+            let shared = ::draw();
+            let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+            let (_, hovered) = mock::frame_in(&shared, 800.0, 600.0, Some(cursor), false, false,
+                                              now, |ctxt| {
+                let ctxt = ctxt.rescale(0.5, 0.5, &Alignment::center());
+                let id = ctxt.reserve_widget_id();
+                ctxt.is_hovered(ui_state, &id)
+            });
+            shared.finish(ui_state);
+            hovered
+        }
+
+        let mut ui_state = UiState {
+            hover_grace: Duration::from_millis(100),
+            .. UiState::default()
+        };
+
+        assert!(!frame(&mut ui_state, [0.9, 0.9], 0));
+        assert!(frame(&mut ui_state, [0.0, 0.0], 10));
+        assert!(frame(&mut ui_state, [0.9, 0.9], 60));
+        assert!(!frame(&mut ui_state, [0.9, 0.9], 160));
+        assert!(ui_state.hover_times.is_empty());
+    }
+
+    #[test]
+    fn bounding_box() {
+        use super::union_bounding_boxes;
+        use super::DrawContext;
+        use Matrix;
+
+        mock::frame(800.0, 800.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let small = ctxt.rescale(0.5, 0.5, &Alignment::top_right());
+            assert_eq!(small.bounding_box(), [0.0, 0.0, 1.0, 1.0]);
+
+            // A square rotated by 45 degrees.
+            let rotated = DrawContext {
+                matrix: Matrix::rotate(FRAC_PI_4) * Matrix::scale(0.5),
+                .. ctxt.clone()
+            };
+            let bounds = rotated.bounding_box();
+            for corner in &[[-1.0, 1.0], [1.0, 1.0], [1.0, -1.0], [-1.0, -1.0]] {
+                let corner = rotated.local_to_viewport(*corner);
+                assert!(corner[0] >= bounds[0] - 0.0001 && corner[0] <= bounds[2] + 0.0001);
+                assert!(corner[1] >= bounds[1] - 0.0001 && corner[1] <= bounds[3] + 0.0001);
+            }
+            assert!((bounds[2] - FRAC_1_SQRT_2).abs() < 0.001);
+            assert!((bounds[1] + FRAC_1_SQRT_2).abs() < 0.001);
+
+            let other = ctxt.rescale(0.25, 0.25, &Alignment::bottom_left());
+            assert_eq!(union_bounding_boxes(&[small.bounding_box(), other.bounding_box()]),
+                       Some([-1.0, -1.0, 1.0, 1.0]));
+            assert_eq!(union_bounding_boxes(&[]), None);
+        });
+    }
+
+    #[test]
+    fn rescale_around() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let zoomed = ctxt.rescale_around(2.0, [0.5, -0.25]);
+            assert!((zoomed.width() - 1600.0).abs() < 0.001);
+            let point = zoomed.local_to_viewport([0.5, -0.25]);
+            assert!((point[0] - 0.5).abs() < 0.0001);
+            assert!((point[1] + 0.25).abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    fn camera() {
+        use super::Camera;
+
+        fn frame(ui_state: &mut UiState, camera: &mut Camera, cursor: [f32; 2])
+                 -> Option<[f32; 2]>
+        {
+            mock::frame(800.0, 600.0, Some(cursor), false, false, UNIX_EPOCH, |ctxt| {
+                let content = ctxt.with_camera(ui_state, camera);
+                content.viewport_to_local(cursor)
+            }).1
+        }
+
+        let mut ui_state = UiState::default();
+        let mut camera = Camera::new();
+
+        let before = frame(&mut ui_state, &mut camera, [0.5, 0.25]).unwrap();
+        ui_state.scroll_delta = [0.0, 2.0];
+        let after = frame(&mut ui_state, &mut camera, [0.5, 0.25]).unwrap();
+        assert!((camera.zoom - 1.21).abs() < 0.0001);
+        assert_eq!(ui_state.scroll_delta, [0.0, 0.0]);
+        assert!((before[0] - after[0]).abs() < 0.0001);
+        assert!((before[1] - after[1]).abs() < 0.0001);
+
+        // Dragging the content with the middle button moves it with the cursor.
+        ui_state.middle_button_down = true;
+        frame(&mut ui_state, &mut camera, [0.0, 0.0]);
+        let offset = camera.offset;
+        let grabbed = frame(&mut ui_state, &mut camera, [0.1, -0.2]).unwrap();
+        assert!((camera.offset[0] - offset[0] - 0.1).abs() < 0.0001);
+        assert!((camera.offset[1] - offset[1] + 0.2).abs() < 0.0001);
+        assert!((grabbed[0] - before[0]).abs() > 0.0001);
+
+        ui_state.middle_button_down = false;
+        frame(&mut ui_state, &mut camera, [0.1, -0.2]);
+        assert_eq!(ui_state.active_widget, None);
+        let offset = camera.offset;
+        frame(&mut ui_state, &mut camera, [0.3, 0.3]);
+        assert_eq!(camera.offset, offset);
+
+        // The primary button doesn't pan the content.
+        mock::frame(800.0, 600.0, Some([0.0, 0.0]), true, false, UNIX_EPOCH, |ctxt| {
+            ctxt.with_camera(&mut ui_state, &mut camera);
+        });
+        assert_eq!(ui_state.active_widget, None);
+    }
+
+    #[test]
+    fn draw_background() {
+        let (mock, _) = mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let ctxt = ctxt.with_z_index(3);
+            image::draw(&ctxt, "foreground", &Alignment::center());
+            ctxt.draw_background(|ctxt| image::draw(ctxt, "background", &Alignment::center()));
+        });
+
+        let z_indices: Vec<_> = mock.calls.iter().map(|c| match *c {
+            Call::Image { ref name, z_index, .. } => (&name[..], z_index),
+            _ => panic!()
+        }).collect();
+        assert_eq!(z_indices, vec![("foreground", 3), ("background", 2)]);
+    }
+
+    #[test]
+    fn hover_status() {
+        use super::HoverStatus;
+
+        mock::frame(800.0, 600.0, Some([0.0, 0.0]), false, false, UNIX_EPOCH, |ctxt| {
+            let lower = ctxt.rescale(0.5, 0.5, &Alignment::center());
+            let upper = ctxt.rescale(0.2, 0.2, &Alignment::center()).with_z_index(1);
+
+            assert_eq!(ctxt.rescale(0.2, 0.2, &Alignment::top()).hover_status(),
+                       HoverStatus::NotHovered);
+
+            // A widget doesn't occlude itself.
+            lower.set_cursor_hovered_widget();
+            assert_eq!(lower.hover_status(), HoverStatus::HoveredTop);
+
+            upper.set_cursor_hovered_widget();
+            assert_eq!(upper.hover_status(), HoverStatus::HoveredTop);
+            assert_eq!(lower.hover_status(), HoverStatus::HoveredOccluded);
+            assert_eq!(ctxt.with_z_index(2).hover_status(), HoverStatus::HoveredTop);
+        });
+
+        // Same in the other order.
+        mock::frame(800.0, 600.0, Some([0.0, 0.0]), false, false, UNIX_EPOCH, |ctxt| {
+            let lower = ctxt.rescale(0.5, 0.5, &Alignment::center());
+            let upper = ctxt.rescale(0.2, 0.2, &Alignment::center()).with_z_index(1);
+
+            upper.set_cursor_hovered_widget();
+            assert_eq!(upper.hover_status(), HoverStatus::HoveredTop);
+            lower.set_cursor_hovered_widget();
+            assert_eq!(upper.hover_status(), HoverStatus::HoveredTop);
+            assert_eq!(lower.hover_status(), HoverStatus::HoveredOccluded);
+
+            // An element with the same z-index as the upper widget isn't occluded by it.
+            assert_eq!(ctxt.with_z_index(1).hover_status(), HoverStatus::HoveredTop);
+        });
+    }
+
+    #[test]
+    fn alignment_new() {
+        assert_eq!(Alignment::new(HorizontalAlignment::Left, VerticalAlignment::Top),
+                   Alignment::top_left());
+        assert_eq!(Alignment::from((HorizontalAlignment::Center, VerticalAlignment::Bottom)),
+                   Alignment::bottom());
+    }
+
+    #[test]
+    fn flow_split() {
+        mock::frame(300.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            // Three items fit on one row.
+            let items = ctxt.flow_split(1.0, 3);
+            assert_eq!(items.len(), 3);
+            assert!((items[2].width() - 100.0).abs() < 0.001);
+            assert!((items[2].height() - 100.0).abs() < 0.001);
+
+            // With seven items, there are two rows of six items of 50 pixels.
+            let items = ctxt.flow_split(1.0, 7);
+            assert_eq!(items.len(), 7);
+            for item in &items {
+                assert!((item.width() - 50.0).abs() < 0.001);
+                assert!((item.height() - 50.0).abs() < 0.001);
+            }
+
+            let bounds = items[5].bounding_box();
+            assert!((bounds[0] - 2.0 / 3.0).abs() < 0.001 && (bounds[2] - 1.0).abs() < 0.001);
+            assert!(bounds[1].abs() < 0.001 && (bounds[3] - 1.0).abs() < 0.001);
+
+            let bounds = items[6].bounding_box();
+            assert!((bounds[0] + 1.0).abs() < 0.001 && (bounds[2] + 2.0 / 3.0).abs() < 0.001);
+            assert!((bounds[1] + 1.0).abs() < 0.001 && bounds[3].abs() < 0.001);
+        });
+    }
+
+    #[test]
+    fn take_strips() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let (strip, rest) = ctxt.take_top(100.0);
+            assert!((strip.height() - 100.0).abs() < 0.001);
+            assert!((rest.height() - 500.0).abs() < 0.001);
+            assert!((strip.bounding_box()[1] - 2.0 / 3.0).abs() < 0.001);
+            assert!((rest.bounding_box()[3] - 2.0 / 3.0).abs() < 0.001);
+
+            let (strip, rest) = ctxt.take_right(200.0);
+            assert!((strip.width() - 200.0).abs() < 0.001);
+            assert!((strip.bounding_box()[0] - 0.5).abs() < 0.001);
+            assert!((rest.width() - 600.0).abs() < 0.001);
+
+            let (strip, rest) = ctxt.take_bottom(1000.0);
+            assert!((strip.height() - 600.0).abs() < 0.001);
+            assert!(rest.is_empty());
+
+            let (strip, rest) = ctxt.take_left(0.0);
+            assert!(strip.is_empty());
+            assert!((rest.width() - 800.0).abs() < 0.001);
+        });
+    }
+
+    #[test]
+    fn split_enumerate() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let chunks: Vec<_> = ctxt.horizontal_split_enumerate(4).collect();
+            assert_eq!(chunks.len(), 4);
+
+            for (n, &(index, center, ref chunk)) in chunks.iter().enumerate() {
+                assert_eq!(index, n);
+                assert!((center - (0.125 + 0.25 * n as f32)).abs() < 0.0001);
+
+                // The center fraction matches the actual position of the chunk.
+                let bounds = chunk.bounding_box();
+                assert!(((bounds[0] + bounds[2]) * 0.25 + 0.5 - center).abs() < 0.0001);
+            }
+        });
+    }
+
+    #[test]
+    fn non_finite_percentages() {
+        mock::frame(800.0, 600.0, Some([0.0, 0.0]), false, false, UNIX_EPOCH, |ctxt| {
+            let is_valid = |c: &DrawContext<MockDraw>| {
+                c.matrix.0.iter().all(|col| col.iter().all(|v| v.is_finite())) &&
+                    c.bounding_box().iter().all(|v| v.is_finite())
+            };
+
+            let rescaled = [
+                ctxt.rescale(f32::NAN, 0.5, &Alignment::center()),
+                ctxt.rescale(0.5, f32::INFINITY, &Alignment::top()),
+                ctxt.vertical_rescale(f32::NAN, &VerticalAlignment::Bottom),
+                ctxt.horizontal_rescale(f32::NEG_INFINITY, &HorizontalAlignment::Left),
+            ];
+
+            for c in rescaled.iter() {
+                assert!(is_valid(c));
+                assert!(c.is_empty());
+                assert!(!c.is_cursor_hovering());
+            }
+
+            let weights = vec![1.0, f32::NAN, f32::INFINITY, 1.0];
+            let chunks: Vec<_> = ctxt.horizontal_split_weights(weights).collect();
+            assert!(chunks.iter().all(&is_valid));
+            assert!((chunks[0].width() - 400.0).abs() < 0.001);
+            assert!(chunks[1].is_empty());
+            assert!(chunks[2].is_empty());
+            assert!((chunks[3].width() - 400.0).abs() < 0.001);
+
+            let chunks: Vec<_> = ctxt.vertical_split_weights(vec![f32::NAN, f32::NAN]).collect();
+            assert!(chunks.iter().all(|c| is_valid(c) && c.is_empty()));
+        });
+    }
+
+    #[test]
+    fn clamp_into_parent() {
+        mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            // This context goes from 0.75 to 1.25 horizontally.
+            let ctxt = ctxt.rescale(0.25, 0.25, &Alignment::center()).margin(0.0, -2.0, 0.0, 2.0);
+            assert!((ctxt.local_to_viewport([1.0, 1.0])[0] - 1.25).abs() < 0.0001);
+            let clamped = ctxt.clamp_into_parent();
+
+            let top_left = clamped.local_to_viewport([-1.0, 1.0]);
+            let bottom_right = clamped.local_to_viewport([1.0, -1.0]);
+            assert!((top_left[0] - 0.5).abs() < 0.0001);
+            assert!((bottom_right[0] - 1.0).abs() < 0.0001);
+            assert!((top_left[1] - 0.25).abs() < 0.0001);
+            assert!((bottom_right[1] + 0.25).abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    fn rotated_draw() {
+        let (mock, _) = mock::frame(800.0, 400.0, Some([0.45, 0.0]), false, false, UNIX_EPOCH,
+                                    |ctxt| {
+            let ctxt = ctxt.rescale(0.5, 0.5, &Alignment::center());
+            let rotated = ctxt.rotated_draw(PI / 2.0);
+
+            // After the rotation, the context would be 200 pixels wide and wouldn't contain
+            // the cursor.
+            assert!(rotated.is_cursor_hovering());
+            assert_eq!(rotated.cursor_hover_coordinates(), ctxt.cursor_hover_coordinates());
+
+            image::stretch(&rotated, "image");
+
+            // Children of the rotated context are rotated as well.
+            let child = rotated.rescale(0.5, 1.0, &Alignment::right());
+            let corner = child.matrix() * [-1.0, 1.0, 1.0];
+            assert!((corner[0] - 0.25).abs() < 0.0001);
+            assert!(corner[1].abs() < 0.0001);
+        });
+
+        match mock.calls[0] {
+            Call::Image { ref matrix, .. } => {
+                let top_right = *matrix * [1.0, 1.0, 1.0];
+                assert!((top_right[0] - 0.25).abs() < 0.0001);
+                assert!((top_right[1] + 1.0).abs() < 0.0001);
+            },
+            _ => panic!()
+        }
+    }
 }
@@ -32,6 +32,9 @@
 //! - Call your custom UI-drawing function (see above), and pass it a reference to the `DrawContext`
 //!   and a mutable reference to your custom state-holding structure.
 //! - The function draws the various elements and updates the UI state.
+//! - Call `finish()` on your `SharedDrawContext`, and pass it a mutable reference to your
+//!   `immi::UiState`. This performs some bookkeeping at the end of the frame, such as removing the
+//!   keyboard events that no widget has processed.
 //!
 //! ## Example
 //!
@@ -68,9 +71,16 @@
 //! let mut drawer = MyDrawer;
 //! 
 //! loop {
-//!     let ui_context = immi::draw();
-//!     let ui_context = ui_context.draw(1024.0, 768.0, &mut drawer, None, false, false);
-//!     draw_ui(&ui_context, &mut my_state);
+//!     let shared_context = immi::draw();
+//!
+//!     {
+//!         let ui_context = shared_context.draw(1024.0, 768.0, &mut drawer,
+//!                                              &my_state.immi_state, None, false, false, None,
+//!                                              std::time::SystemTime::now());
+//!         draw_ui(&ui_context, &mut my_state);
+//!     }
+//!
+//!     shared_context.finish(&mut my_state.immi_state);
 //! # break;
 //! }
 //! ```
@@ -115,31 +125,262 @@
 //! }
 //! ```
 //!
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::SystemTime;
+
+pub use accessibility::AccessibilityNode;
+pub use accessibility::AccessibilityRole;
+pub use color::contrast_color;
 pub use draw::Draw;
 pub use draw::GlyphInfos;
 pub use id::WidgetId;
+pub use inspector::LayoutNode;
+pub use keyboard::KeyboardEvent;
 pub use layout::draw;
 pub use layout::Alignment;
+pub use layout::AlignmentF;
+pub use layout::Camera;
+pub use layout::ConstraintBuilder;
 pub use layout::DrawContext;
+pub use layout::FadeState;
+pub use layout::FrameSummary;
+pub use layout::HoverStatus;
 pub use layout::SharedDrawContext;
+pub use layout::SizeSpec;
+pub use layout::union_bounding_boxes;
 pub use layout::HorizontalAlignment;
 pub use layout::VerticalAlignment;
+pub use layout::YAxis;
 pub use matrix::Matrix;
+pub use theme::Theme;
 
+mod accessibility;
+mod color;
 mod draw;
 mod id;
+mod inspector;
+mod keyboard;
 mod layout;
 mod matrix;
+mod theme;
+
+#[cfg(test)]
+mod mock;
 
 pub mod animations;
 pub mod widgets;
 
 /// Contains some persistent info about the UI.
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+///
+/// This type implements `PartialEq` but not `Eq`, because some of its fields are floating-point
+/// numbers.
+#[derive(Debug, Clone, PartialEq)]
 pub struct UiState {
     /// Identifier of the widget that is currently active.
     ///
     /// For example if you maintain the left button of the mouse, the element under will be active.
     /// If you then move your mouse somewhere else, the active element doesn't change.
     pub active_widget: Option<WidgetId>,
+
+    /// Identifier of the widget that has the keyboard focus.
+    ///
+    /// Moved with the arrow keys between the widgets registered with
+    /// `DrawContext::register_focusable_grid`.
+    pub focused_widget: Option<WidgetId>,
+
+    /// Keyboard events that haven't been processed yet.
+    ///
+    /// You should push the events that you receive from your windowing system here before drawing
+    /// your UI. Widgets remove the events that they process, and `SharedDrawContext::finish`
+    /// clears the remaining ones at the end of the frame.
+    pub keyboard_events: Vec<KeyboardEvent>,
+
+    /// Amount of scrolling that hasn't been processed yet, horizontally and vertically.
+    ///
+    /// You should add the movements of the mouse wheel here before drawing your UI, in number of
+    /// lines. Positive values scroll up. Widgets reset it once they have processed it with
+    /// `DrawContext::consume_scroll`, and `SharedDrawContext::finish` clears it at the end of the
+    /// frame.
+    pub scroll_delta: [f32; 2],
+
+    /// Whether the middle button of the mouse is currently held down.
+    ///
+    /// You should update this from your windowing system before drawing your UI. It is used for
+    /// panning by `DrawContext::with_camera`.
+    pub middle_button_down: bool,
+
+    /// Duration during which a widget is still considered hovered after the cursor has left it.
+    ///
+    /// This is used by `DrawContext::is_hovered`, and avoids for example closing a submenu when
+    /// the cursor quickly crosses the gap between a menu and its submenu. Defaults to zero.
+    pub hover_grace: Duration,
+
+    /// Parts of the viewport that are covered by the system, like status bars or notches, in
+    /// the `[top, right, bottom, left]` order and in physical pixels.
+    ///
+    /// You should update this from your windowing system. Widgets that must stay visible can be
+    /// drawn inside `DrawContext::safe_area`. Defaults to zero.
+    pub safe_area_insets: [f32; 4],
+
+    /// Factor by which all the sizes in pixels that are passed to the library are multiplied.
+    ///
+    /// This is used for HiDPI screens and for letting the user enlarge the UI. It applies to
+    /// the functions of `DrawContext` that take pixels, like `take_top` or `fixed_size`, and to
+    /// the sizes in pixels of the widgets, like the maximum height of the text. The dimensions
+    /// passed to `SharedDrawContext::draw` and returned by `DrawContext::width` and
+    /// `DrawContext::height` are physical pixels and are not affected. Values that are NaN,
+    /// infinite or not positive are treated as `1.0`. Defaults to `1.0`.
+    pub ui_scale: f32,
+
+    /// Direction of the vertical axis of the viewport. Defaults to `YAxis::Up`.
+    ///
+    /// With `YAxis::Down`, the contexts are laid out for a renderer whose y axis points
+    /// downwards, and the cursor coordinates passed to `SharedDrawContext::draw` must use the
+    /// same convention. Do not call `SharedDrawContext::flip_cursor_y` in addition to this.
+    pub y_axis: YAxis,
+
+    /// Last moment when each widget was hovered. Maintained by `DrawContext::is_hovered`.
+    pub hover_times: HashMap<WidgetId, SystemTime>,
+
+    /// Moment when each widget was first drawn. Maintained by `DrawContext::is_first_appearance`
+    /// and `DrawContext::animate_in`.
+    pub appear_times: HashMap<WidgetId, SystemTime>,
+
+    /// Time of the last frame that has been passed to `SharedDrawContext::finish`. Used by
+    /// `SharedDrawContext::delta_time`.
+    pub last_frame_time: Option<SystemTime>,
+
+    /// Bounding box of the widgets whose bounds have been recorded during the last frame that
+    /// has been passed to `SharedDrawContext::finish`, in the `[min_x, min_y, max_x, max_y]`
+    /// format. Used by `DrawContext::was_cursor_over_last_frame`. See
+    /// `SharedDrawContext::enable_bounds_recording`.
+    pub last_frame_bounds: HashMap<WidgetId, [f32; 4]>,
+
+    /// Tree of the kinds and bounds of the elements that have been drawn during the last frame
+    /// that has been passed to `SharedDrawContext::finish`.
+    ///
+    /// This is only recorded if `SharedDrawContext::enable_layout_recording` has been called.
+    /// With the `serde` feature it can be serialized and compared against a snapshot in order to
+    /// test the layout of a UI.
+    pub layout: Vec<LayoutNode>,
+}
+
+impl Default for UiState {
+    fn default() -> UiState {
+        UiState {
+            active_widget: None,
+            focused_widget: None,
+            keyboard_events: Vec::new(),
+            scroll_delta: [0.0, 0.0],
+            middle_button_down: false,
+            hover_grace: Duration::new(0, 0),
+            safe_area_insets: [0.0, 0.0, 0.0, 0.0],
+            ui_scale: 1.0,
+            y_axis: YAxis::Up,
+            hover_times: HashMap::new(),
+            appear_times: HashMap::new(),
+            last_frame_time: None,
+            last_frame_bounds: HashMap::new(),
+            layout: Vec::new(),
+        }
+    }
+}
+
+impl UiState {
+    /// Captures the interaction state, so that it can be restored later with `restore`.
+    ///
+    /// This includes the active and the focused widgets, the input that hasn't been processed
+    /// yet, and what the library remembers about the previous frames, like the hover and
+    /// appearance times. The configuration, like `hover_grace`, and the recorded layout of the
+    /// last frame aren't included. The state of the widgets themselves, like a `ScrollState`, is
+    /// stored by the user and must be saved separately.
+    ///
+    /// This makes it possible for example to roll back the UI and replay some input.
+    pub fn snapshot(&self) -> UiStateSnapshot {
+        UiStateSnapshot {
+            active_widget: self.active_widget.clone(),
+            focused_widget: self.focused_widget.clone(),
+            keyboard_events: self.keyboard_events.clone(),
+            scroll_delta: self.scroll_delta,
+            hover_times: self.hover_times.clone(),
+            appear_times: self.appear_times.clone(),
+            last_frame_time: self.last_frame_time,
+            last_frame_bounds: self.last_frame_bounds.clone(),
+        }
+    }
+
+    /// Restores the interaction state captured by `snapshot`. The rest of the state is left
+    /// untouched.
+    pub fn restore(&mut self, snapshot: UiStateSnapshot) {
+        self.active_widget = snapshot.active_widget;
+        self.focused_widget = snapshot.focused_widget;
+        self.keyboard_events = snapshot.keyboard_events;
+        self.scroll_delta = snapshot.scroll_delta;
+        self.hover_times = snapshot.hover_times;
+        self.appear_times = snapshot.appear_times;
+        self.last_frame_time = snapshot.last_frame_time;
+        self.last_frame_bounds = snapshot.last_frame_bounds;
+    }
+}
+
+/// Interaction state of a `UiState` at some point in time. Built by `UiState::snapshot`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiStateSnapshot {
+    active_widget: Option<WidgetId>,
+    focused_widget: Option<WidgetId>,
+    keyboard_events: Vec<KeyboardEvent>,
+    scroll_delta: [f32; 2],
+    hover_times: HashMap<WidgetId, SystemTime>,
+    appear_times: HashMap<WidgetId, SystemTime>,
+    last_frame_time: Option<SystemTime>,
+    last_frame_bounds: HashMap<WidgetId, [f32; 4]>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use KeyboardEvent;
+    use UiState;
+    use WidgetId;
+
+    #[test]
+    fn snapshot_restore() {
+        let mut ui_state = UiState {
+            active_widget: Some(WidgetId::from(3)),
+            scroll_delta: [0.0, 1.0],
+            .. UiState::default()
+        };
+        ui_state.hover_times.insert(WidgetId::from(3), UNIX_EPOCH);
+
+        let snapshot = ui_state.snapshot();
+        let saved = ui_state.clone();
+
+        ui_state.active_widget = None;
+        ui_state.scroll_delta = [0.0, 0.0];
+        ui_state.keyboard_events.push(KeyboardEvent::Character('a'));
+        ui_state.hover_times.clear();
+        ui_state.last_frame_time = Some(UNIX_EPOCH + Duration::from_secs(1));
+        ui_state.last_frame_bounds.insert(WidgetId::from(4), [0.0, 0.0, 1.0, 1.0]);
+        ui_state.hover_grace = Duration::from_millis(300);
+
+        ui_state.restore(snapshot.clone());
+        assert_eq!(ui_state.snapshot(), snapshot);
+        assert_eq!(ui_state.active_widget, saved.active_widget);
+        assert_eq!(ui_state.scroll_delta, saved.scroll_delta);
+        assert!(ui_state.keyboard_events.is_empty());
+        assert_eq!(ui_state.hover_times, saved.hover_times);
+        assert_eq!(ui_state.last_frame_time, None);
+
+        // The configuration isn't part of the snapshot.
+        assert_eq!(ui_state.hover_grace, Duration::from_millis(300));
+    }
 }
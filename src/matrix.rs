@@ -77,14 +77,33 @@ impl Matrix {
         ])
     }
 
+    /// Returns the determinant of the matrix.
+    ///
+    /// This is the factor by which the matrix multiplies areas. A determinant of zero means that
+    /// the matrix collapses everything to a line or a point, in which case the cursor can't be
+    /// tested against the objects drawn with it.
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        let me = self.0;
+        me[0][0] * me[1][1] - me[1][0] * me[0][1]
+    }
+
+    /// Returns true if the magnitude of the determinant is large enough for the matrix to be
+    /// reliably inverted.
+    #[inline]
+    pub fn is_invertible(&self) -> bool {
+        let det = self.determinant();
+        det.is_finite() && det.abs() > 1.0e-6
+    }
+
     /// Builds the matrix's invert.
     ///
     /// Returns `None` if the determinant is zero, infinite or NaN.
     pub fn invert(&self) -> Option<[[f32; 3]; 3]> {
         let me = self.0;
-        let det = me[0][0] * me[1][1] - me[1][0] * me[0][1];
+        let det = self.determinant();
 
-        if det == 0.0 || det != det {
+        if det == 0.0 || det.is_nan() {
             return None;
         }
 
@@ -140,6 +159,7 @@ impl ops::Mul<[f32; 3]> for Matrix {
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<[[f32; 3]; 3]> for Matrix {
     #[inline]
     fn into(self) -> [[f32; 3]; 3] {
@@ -153,6 +173,7 @@ impl Into<[[f32; 3]; 3]> for Matrix {
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<[[f32; 4]; 4]> for Matrix {
     #[inline]
     fn into(self) -> [[f32; 4]; 4] {
@@ -194,4 +215,20 @@ mod tests {
         assert_eq!(Matrix::rotate(PI).invert().unwrap(),
                    Into::<[[f32; 3]; 3]>::into(Matrix::rotate(-PI)));
     }
+
+    #[test]
+    fn determinant() {
+        assert_eq!(Matrix::identity().determinant(), 1.0);
+        assert!(Matrix::identity().is_invertible());
+
+        assert_eq!(Matrix::scale_wh(2.0, 3.0).determinant(), 6.0);
+
+        assert_eq!(Matrix::scale(0.0).determinant(), 0.0);
+        assert!(!Matrix::scale(0.0).is_invertible());
+        assert!(!Matrix::scale_wh(1.0, 0.0).is_invertible());
+
+        let rotation = Matrix::rotate(PI / 3.0);
+        assert!((rotation.determinant() - 1.0).abs() < 0.0001);
+        assert!(rotation.is_invertible());
+    }
 }
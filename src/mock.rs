@@ -0,0 +1,165 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Implementation of `Draw` that records what is drawn. Used by the tests.
+
+use std::time::SystemTime;
+
+use Draw;
+use DrawContext;
+use GlyphInfos;
+use Matrix;
+use SharedDrawContext;
+use Theme;
+use UiState;
+
+/// Draws a frame of `width`x`height` pixels on a new `MockDraw`, and calls `f` with the context
+/// of the whole frame.
+///
+/// Returns the `MockDraw` with the calls that have been made, and what `f` returned.
+pub fn frame<F, R>(width: f32, height: f32, cursor: Option<[f32; 2]>, pressed: bool,
+                   released: bool, now: SystemTime, f: F) -> (MockDraw, R)
+    where F: FnOnce(&DrawContext<MockDraw>) -> R
+{
+    frame_in(&::draw(), width, height, cursor, pressed, released, now, f)
+}
+
+/// Same as `frame`, except that the frame is drawn with the given `SharedDrawContext`.
+///
+/// This is useful for the tests that configure the `SharedDrawContext` before drawing, or that
+/// call `finish` afterwards. The settings of the frame are the ones of `UiState::default()`.
+#[allow(clippy::too_many_arguments)]
+pub fn frame_in<F, R>(shared: &SharedDrawContext, width: f32, height: f32,
+                      cursor: Option<[f32; 2]>, pressed: bool, released: bool, now: SystemTime,
+                      f: F) -> (MockDraw, R)
+    where F: FnOnce(&DrawContext<MockDraw>) -> R
+{
+    let mut mock = MockDraw::new();
+    let result = {
+        let ui_state = UiState::default();
+        let ctxt = shared.draw(width, height, &mut mock, &ui_state, cursor, pressed, released,
+                               None, now);
+        f(&ctxt)
+    };
+    (mock, result)
+}
+
+/// Returns a theme whose resources are named after the fields of `Theme`, except for the text
+/// style which is named `"font"`.
+pub fn theme() -> Theme<'static, MockDraw> {
+    Theme {
+        button_normal: "normal",
+        button_hovered: "hovered",
+        button_active: "active",
+        checkbox_checked: "checked",
+        checkbox_unchecked: "unchecked",
+        text: "font",
+    }
+}
+
+/// A call that was made to the `MockDraw`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Call {
+    Image { name: String, matrix: Matrix, tint: [f32; 4], z_index: i32, clip: Option<[f32; 4]> },
+    Triangle { name: String, matrix: Matrix, tint: [f32; 4], z_index: i32,
+               clip: Option<[f32; 4]> },
+    Glyph { style: String, glyph: char, matrix: Matrix, tint: [f32; 4], z_index: i32,
+            clip: Option<[f32; 4]> },
+    Line { from: [f32; 2], to: [f32; 2], thickness_px: f32, color: [f32; 4], tint: [f32; 4],
+           z_index: i32, clip: Option<[f32; 4]> },
+}
+
+/// Records all the calls. Images are always square and glyphs are always one EM wide.
+pub struct MockDraw {
+    pub calls: Vec<Call>,
+    tint: [f32; 4],
+    z_index: i32,
+    clip: Option<[f32; 4]>,
+}
+
+impl MockDraw {
+    pub fn new() -> MockDraw {
+        MockDraw {
+            calls: Vec::new(),
+            tint: [1.0, 1.0, 1.0, 1.0],
+            z_index: 0,
+            clip: None,
+        }
+    }
+
+    /// Returns the names of the images that were drawn, in order.
+    pub fn images(&self) -> Vec<&str> {
+        self.calls.iter().filter_map(|c| match *c {
+            Call::Image { ref name, .. } => Some(&name[..]),
+            _ => None,
+        }).collect()
+    }
+
+    /// Returns the glyphs that were drawn, in order.
+    pub fn text(&self) -> String {
+        self.calls.iter().filter_map(|c| match *c {
+            Call::Glyph { glyph, .. } => Some(glyph),
+            _ => None,
+        }).collect()
+    }
+}
+
+impl Draw for MockDraw {
+    type ImageResource = str;
+    type TextStyle = str;
+
+    fn draw_triangle(&mut self, texture: &str, matrix: &Matrix, _: [[f32; 2]; 3]) {
+        self.calls.push(Call::Triangle { name: texture.to_owned(), matrix: *matrix,
+                                         tint: self.tint, z_index: self.z_index,
+                                         clip: self.clip });
+    }
+
+    fn draw_image_uv(&mut self, name: &str, matrix: &Matrix, _: [f32; 2], _: [f32; 2],
+                     _: [f32; 2], _: [f32; 2])
+    {
+        self.calls.push(Call::Image { name: name.to_owned(), matrix: *matrix, tint: self.tint,
+                                      z_index: self.z_index, clip: self.clip });
+    }
+
+    fn draw_line(&mut self, from: [f32; 2], to: [f32; 2], thickness_px: f32, color: [f32; 4]) {
+        self.calls.push(Call::Line { from, to, thickness_px, color,
+                                     tint: self.tint, z_index: self.z_index, clip: self.clip });
+    }
+
+    fn get_image_width_per_height(&mut self, _: &str) -> f32 {
+        1.0
+    }
+
+    fn draw_glyph(&mut self, style: &str, glyph: char, matrix: &Matrix) {
+        self.calls.push(Call::Glyph { style: style.to_owned(), glyph, matrix: *matrix,
+                                      tint: self.tint, z_index: self.z_index, clip: self.clip });
+    }
+
+    fn line_height(&self, _: &str) -> f32 {
+        1.2
+    }
+
+    fn kerning(&self, _: &str, _: char, _: char) -> f32 {
+        0.0
+    }
+
+    fn glyph_infos(&self, _: &str, _: char) -> GlyphInfos {
+        GlyphInfos { width: 1.0, height: 1.0, x_offset: 0.0, y_offset: 1.0, x_advance: 1.0 }
+    }
+
+    fn set_tint(&mut self, tint: [f32; 4]) {
+        self.tint = tint;
+    }
+
+    fn set_z_index(&mut self, z_index: i32) {
+        self.z_index = z_index;
+    }
+
+    fn set_clip(&mut self, clip: Option<[f32; 4]>) {
+        self.clip = clip;
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use Draw;
+
+/// Bundles the resources that are commonly used by widgets.
+///
+/// Instead of passing the same images and text styles to each widget, you can build a `Theme`
+/// once and pass it to the `*_themed` functions of the widgets.
+pub struct Theme<'a, D: ?Sized + Draw + 'a> {
+    /// Image of a button when it is neither hovered nor active.
+    pub button_normal: &'a D::ImageResource,
+
+    /// Image of a button when it is hovered.
+    pub button_hovered: &'a D::ImageResource,
+
+    /// Image of a button when it is active, in other words while it is being pressed.
+    pub button_active: &'a D::ImageResource,
+
+    /// Image of a checkbox when it is checked.
+    pub checkbox_checked: &'a D::ImageResource,
+
+    /// Image of a checkbox when it is not checked.
+    pub checkbox_unchecked: &'a D::ImageResource,
+
+    /// Style of the text.
+    pub text: &'a D::TextStyle,
+}
+
+impl<'a, D: ?Sized + Draw + 'a> Clone for Theme<'a, D> {
+    #[inline]
+    fn clone(&self) -> Theme<'a, D> {
+        *self
+    }
+}
+
+impl<'a, D: ?Sized + Draw + 'a> Copy for Theme<'a, D> {
+}
@@ -0,0 +1,85 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A badge is a small counter drawn in the top-right hand corner of an icon, for example to show
+//! the number of unread notifications.
+//!
+//! A widget like this is composed of two images and a text:
+//!
+//! - The icon, which is stretched over the whole context.
+//! - The background of the badge, which is stretched behind the count. A circle will look like
+//!   a pill when the count has multiple digits.
+//! - The count.
+//!
+//! The badge is drawn after the icon, so that it appears above it.
+
+use Alignment;
+use Draw;
+use DrawContext;
+
+use widgets::image;
+use widgets::label;
+
+/// Draws an icon with a badge containing `count`. The badge isn't drawn if `count` is `0`.
+///
+/// The height of the badge is 40% of the height of the context, and the badge gets wider as the
+/// number of digits increases.
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, icon: &D::ImageResource,
+                              badge_background: &D::ImageResource, text_style: &D::TextStyle,
+                              count: u32)
+{
+    image::stretch(draw, icon);
+
+    if count == 0 || draw.is_empty() {
+        return;
+    }
+
+    let text = count.to_string();
+
+    // The badge is a square for a single digit, and each additional digit adds half of the
+    // height.
+    let badge_height = 0.4;
+    let width_per_height = 1.0 + 0.5 * (text.len() - 1) as f32;
+    let badge_width = badge_height * width_per_height / draw.width_per_height();
+    let badge_width = if badge_width > 1.0 { 1.0 } else { badge_width };
+
+    let badge = draw.rescale(badge_width, badge_height, &Alignment::top_right());
+    image::stretch(&badge, badge_background);
+
+    let text_ctxt = badge.uniform_margin(0.2, 0.2, 0.2, 0.2);
+    label::contain(&text_ctxt, text_style, &text, &Alignment::center());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::MockDraw;
+
+    use super::draw;
+
+    #[test]
+    fn badge_hidden_when_zero() {
+        fn frame(count: u32) -> MockDraw {
+            mock::frame(100.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+                draw(ctxt, "icon", "badge", "font", count)
+            }).0
+        }
+
+        let mock = frame(0);
+        assert_eq!(mock.images(), vec!["icon"]);
+        assert_eq!(mock.text(), "");
+
+        let mock = frame(5);
+        assert_eq!(mock.images(), vec!["icon", "badge"]);
+        assert_eq!(mock.text(), "5");
+
+        let mock = frame(42);
+        assert_eq!(mock.text(), "42");
+    }
+}
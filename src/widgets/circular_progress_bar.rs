@@ -16,12 +16,25 @@
 //! This module supposes that the center of the circular progress bar is the center of the image.
 //! The direction is always clockwise. <-- TODO: allow choosing this
 //!
+//! The `indeterminate` function turns the progress bar into a spinner, for when the progression
+//! isn't known. It requires a `SpinnerState` that must be kept between frames.
+//!
+use std::f32::consts::PI;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use AccessibilityRole;
 use Alignment;
 use Draw;
 use DrawContext;
 use Matrix;
 
+use animations::EaseInOut;
+use animations::Linear;
+use animations::Interpolation;
 use widgets::image;
+use widgets::label;
 
 /// Draws a circular progress bar and keeps the aspect ratio of the empty image.
 ///
@@ -39,6 +52,33 @@ pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource,
     stretch(&draw.enforce_aspect_ratio_downscale(ratio, alignment), empty, full, progress)
 }
 
+/// Same as `draw`, except that the percentage of progress is written in the middle of the ring,
+/// for example `42%`.
+///
+/// `thickness` is the thickness of the ring, as a percentage of its radius. The text is
+/// contained in the largest square that fits inside of the ring. The progress bar is centered
+/// in the context.
+///
+/// # Panic
+///
+/// Panicks if `progress` is not between 0.0 and 1.0.
+pub fn with_label<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource,
+                                    full: &D::ImageResource, text_style: &D::TextStyle,
+                                    progress: f32, thickness: f32)
+{
+    let draw = draw.animation_stop();
+    let ratio = draw.draw().get_image_width_per_height(empty);
+    let ring = draw.enforce_aspect_ratio_downscale(ratio, &Alignment::center());
+    stretch(&ring, empty, full, progress);
+
+    let thickness = thickness.clamp(0.0, 1.0);
+    let margin = (1.0 - (1.0 - thickness) / 2.0f32.sqrt()) / 2.0;
+    let inner = ring.margin(margin, margin, margin, margin);
+
+    let text = format!("{}%", (progress * 100.0).round() as u32);
+    label::contain(&inner, text_style, &text, &Alignment::center());
+}
+
 /// Draws a circular progress bar, stretching it over the whole area.
 ///
 /// # Panic
@@ -49,9 +89,87 @@ pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource
 {
     assert!(progress >= 0.0);
     assert!(progress <= 1.0);
+    let draw = &draw.layout_group("circular_progress_bar");
+    draw.push_accessibility(AccessibilityRole::ProgressBar, "");
 
     // Drawing the empty image, which serves as a background.
     image::stretch(draw, empty);
+    draw_arc(draw, full, progress);
+}
+
+/// Draws a circular progress bar that spins forever, and keeps the aspect ratio of the empty
+/// image.
+///
+/// The arc starts and ends at different rates, which produces a "chasing" effect. The `full`
+/// image is rotated, so it should look the same from every angle.
+pub fn indeterminate<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource,
+                                       full: &D::ImageResource, state: &SpinnerState,
+                                       alignment: &Alignment)
+{
+    let draw = draw.animation_stop();
+    let ratio = draw.draw().get_image_width_per_height(empty);
+    let draw = draw.enforce_aspect_ratio_downscale(ratio, alignment);
+
+    let (start, length) = state.arc(draw.now());
+    draw.request_redraw();
+    let draw = &draw.layout_group("circular_progress_bar");
+    draw.push_accessibility(AccessibilityRole::ProgressBar, "");
+
+    image::stretch(draw, empty);
+    draw_arc(&draw.rotated_draw(start * 2.0 * PI), full, length);
+}
+
+/// State of a circular progress bar drawn with `indeterminate`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SpinnerState {
+    /// Moment when the spinner started spinning.
+    pub start: SystemTime,
+
+    /// Duration of a full cycle of the spinner.
+    pub period: Duration,
+}
+
+impl SpinnerState {
+    /// Builds a `SpinnerState` that starts spinning at `now`.
+    ///
+    /// `now` should be the same clock as the one that is passed to `SharedDrawContext::draw`,
+    /// for example `DrawContext::now()`.
+    #[inline]
+    pub fn new(period: Duration, now: SystemTime) -> SpinnerState {
+        SpinnerState {
+            start: now,
+            period,
+        }
+    }
+
+    /// Returns the start of the arc and its length at the given point in time, both as a
+    /// fraction of a full turn.
+    ///
+    /// During the first half of a cycle the head of the arc moves forward, and during the second
+    /// half the tail catches up. The whole arc also slowly rotates, so that it does exactly one
+    /// full turn per cycle.
+    pub fn arc(&self, now: SystemTime) -> (f32, f32) {
+        let progress = Linear.repeat().calculate(now, self.start, self.period) as f32;
+
+        let head = 0.75 * EaseInOut.sample(progress * 2.0);
+        let tail = 0.75 * EaseInOut.sample(progress * 2.0 - 1.0);
+
+        ((tail + 0.25 * progress) % 1.0, 0.05 + head - tail)
+    }
+}
+
+impl Default for SpinnerState {
+    #[inline]
+    fn default() -> SpinnerState {
+        // The spinner repeats forever, so the moment when it starts doesn't matter much.
+        SpinnerState::new(Duration::from_millis(1500), UNIX_EPOCH)
+    }
+}
+
+/// Draws the part of the `full` image that corresponds to the progress.
+fn draw_arc<D: ?Sized + Draw>(draw: &DrawContext<D>, full: &D::ImageResource, progress: f32) {
 
     // The top image will be split in 4 rectangles, one for each quater (top-left, top-right,
     // bottom-left, bottom-right). These 4 rectangles are themselves split into two triangles each.
@@ -65,7 +183,7 @@ pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource
 
         let local_matrix = Matrix::translate(1.0, 1.0);
         let local_matrix = Matrix::scale_wh(0.5 * local_percent, 0.5) * local_matrix;
-        let local_matrix = Matrix::rotate(num as f32 * -3.141592 * 0.5) * local_matrix;
+        let local_matrix = Matrix::rotate(num as f32 * -PI * 0.5) * local_matrix;
 
         let (uv1, uv3) = match num {
             0 => ([0.5, 1.0], [0.5 + 0.5 * local_percent, 1.0]),
@@ -86,8 +204,8 @@ pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource
 
         let local_matrix = Matrix::translate(1.0, 1.0);
         let local_matrix = Matrix::scale_wh(0.5 * local_percent, 0.5) * local_matrix;
-        let local_matrix = Matrix::skew_x(-3.141592 / 4.0) * local_matrix;
-        let local_matrix = Matrix::rotate((num + 1) as f32 * -3.141592 * 0.5) * local_matrix;
+        let local_matrix = Matrix::skew_x(-PI / 4.0) * local_matrix;
+        let local_matrix = Matrix::rotate((num + 1) as f32 * -PI * 0.5) * local_matrix;
 
         let (uv1, uv3) = match num {
             0 => ([1.0, 1.0], [1.0, 1.0 - 0.5 * local_percent]),
@@ -100,3 +218,72 @@ pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource
         draw.draw().draw_triangle(full, &(draw.matrix() * local_matrix), [uv1, [0.5, 0.5], uv3]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+    use Alignment;
+
+    use super::indeterminate;
+    use super::with_label;
+    use super::SpinnerState;
+
+    #[test]
+    fn spinner_arc() {
+        let state = SpinnerState::new(Duration::from_millis(1000), UNIX_EPOCH);
+        let arc = |ms| state.arc(UNIX_EPOCH + Duration::from_millis(ms));
+        let close = |a: f32, b: f32| (a - b).abs() < 0.0001;
+
+        // The arc is the shortest at the start of the cycle and the longest at the middle.
+        let (start, length) = arc(0);
+        assert!(close(start, 0.0) && close(length, 0.05));
+        let (start, length) = arc(500);
+        assert!(close(start, 0.125) && close(length, 0.8));
+
+        // The head moves faster than the tail during the first half, and the opposite during
+        // the second half.
+        let (start, length) = arc(250);
+        assert!(close(start, 0.0625) && close(length, 0.425));
+        let (start, length) = arc(750);
+        assert!(close(start, 0.375 + 0.1875) && close(length, 0.425));
+
+        // The next cycle starts where the previous one ended.
+        let (start, length) = arc(1000);
+        assert!(close(start, 0.0) && close(length, 0.05));
+        let (start, length) = arc(999);
+        assert!(start > 0.99 && length < 0.06);
+
+        let shared = ::draw();
+        let (mock, _) = mock::frame_in(&shared, 100.0, 100.0, None, false, false, UNIX_EPOCH,
+                                       |ctxt| {
+            indeterminate(ctxt, "empty", "full", &SpinnerState::default(), &Alignment::center())
+        });
+        assert!(shared.needs_redraw());
+        assert_eq!(mock.images(), vec!["empty"]);
+        assert!(mock.calls.iter().any(|c| matches!(*c, Call::Triangle { .. })));
+    }
+
+    #[test]
+    fn label_in_center() {
+        let (mock, _) = mock::frame(200.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            with_label(ctxt, "empty", "full", "font", 0.424, 0.2)
+        });
+
+        assert_eq!(mock.text(), "42%");
+
+        // The ring is a centered square of 100x100 pixels, and the text is within the square
+        // inscribed in the inner circle.
+        let limit_x = 0.5 * 0.8 / 2.0f32.sqrt();
+        let limit_y = 0.8 / 2.0f32.sqrt();
+        for call in &mock.calls {
+            if let Call::Glyph { ref matrix, .. } = *call {
+                assert!(matrix.0[2][0].abs() < limit_x);
+                assert!(matrix.0[2][1].abs() < limit_y);
+            }
+        }
+    }
+}
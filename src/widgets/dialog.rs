@@ -0,0 +1,133 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A dialog is a box drawn in the middle of the context, above the rest of the UI.
+//!
+//! The rest of the UI is dimmed while the dialog is open, and the whole context counts as
+//! hovered so that the clicks don't reach what is under it. Since the widgets react to the
+//! cursor when they are drawn, you should draw the dialog after the rest of your UI, and avoid
+//! drawing interactive widgets under it while it is open.
+//!
+//! The images of the dialog are taken from the theme. The `button_normal` image is used as the
+//! background of the dialog, and is also tinted in black in order to dim the rest of the UI.
+
+use Alignment;
+use Draw;
+use DrawContext;
+use HorizontalAlignment;
+use Theme;
+use UiState;
+
+use widgets::image;
+use widgets::image_button;
+use widgets::label;
+
+/// State of a dialog. Must be kept between frames.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DialogState {
+    /// True if the dialog is visible. Set it to `true` in order to show the dialog.
+    pub open: bool,
+}
+
+/// Draws a dialog with a message and "OK" and "Cancel" buttons, if `state.open` is true.
+///
+/// Returns `Some(true)` if the user clicked "OK" and `Some(false)` if they clicked "Cancel", in
+/// which case the dialog is closed. Returns `None` if the dialog is closed or if the user hasn't
+/// made a choice yet.
+pub fn confirm<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                 theme: &Theme<D>, state: &mut DialogState, message: &str)
+                                 -> Option<bool>
+{
+    if !state.open {
+        return None;
+    }
+
+    let draw = draw.animation_stop();
+
+    // Dimming the rest of the UI. This also marks the whole context as hovered.
+    image::stretch(&draw.with_tint([0.0, 0.0, 0.0, 0.5]), theme.button_normal);
+
+    let dialog = draw.centered_content(0.1, 2.0);
+    image::stretch(&dialog, theme.button_normal);
+
+    let content = dialog.uniform_margin(0.1, 0.1, 0.1, 0.1);
+    let rows: Vec<_> = content.vertical_split_weights(vec![2.0, 1.0]).collect();
+    label::contain(&rows[0], theme.text, message, &Alignment::center());
+
+    let mut result = None;
+    for (button, &(text, value)) in rows[1].horizontal_split(2)
+                                           .zip(&[("OK", true), ("Cancel", false)])
+    {
+        let button = button.uniform_margin(0.1, 0.1, 0.1, 0.1);
+        if image_button::stretch_themed(&button, ui_state, theme).clicked() {
+            result = Some(value);
+        }
+
+        let text_ctxt = button.uniform_margin(0.2, 0.2, 0.2, 0.2);
+        label::flow(&text_ctxt, theme.text, text, &HorizontalAlignment::Center);
+    }
+
+    if result.is_some() {
+        state.open = false;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use UiState;
+
+    use super::confirm;
+    use super::DialogState;
+
+    fn frame(ui_state: &mut UiState, state: &mut DialogState, cursor: [f32; 2], pressed: bool,
+             released: bool) -> Option<bool>
+    {
+        let theme = mock::theme();
+
+        mock::frame(1000.0, 1000.0, Some(cursor), pressed, released, UNIX_EPOCH, |ctxt| {
+            confirm(ctxt, ui_state, &theme, state, "Quit?")
+        }).1
+    }
+
+    #[test]
+    fn ok_closes() {
+        let mut ui_state = UiState::default();
+        let mut state = DialogState::default();
+
+        // A closed dialog does nothing.
+        assert_eq!(frame(&mut ui_state, &mut state, [-0.36, -0.21], true, false), None);
+        assert_eq!(ui_state.active_widget, None);
+
+        state.open = true;
+        assert_eq!(frame(&mut ui_state, &mut state, [-0.36, -0.21], true, false), None);
+        assert!(state.open);
+        assert_eq!(frame(&mut ui_state, &mut state, [-0.36, -0.21], false, true), Some(true));
+        assert!(!state.open);
+    }
+
+    #[test]
+    fn cancel() {
+        let mut ui_state = UiState::default();
+        let mut state = DialogState { open: true };
+
+        // Clicking outside of the dialog doesn't close it.
+        assert_eq!(frame(&mut ui_state, &mut state, [0.0, 0.9], true, false), None);
+        assert_eq!(frame(&mut ui_state, &mut state, [0.0, 0.9], false, true), None);
+        assert!(state.open);
+
+        assert_eq!(frame(&mut ui_state, &mut state, [0.36, -0.21], true, false), None);
+        assert_eq!(frame(&mut ui_state, &mut state, [0.36, -0.21], false, true), Some(false));
+        assert!(!state.open);
+    }
+}
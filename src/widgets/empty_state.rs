@@ -0,0 +1,89 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! An empty state is a placeholder that is drawn instead of some content that is empty, for
+//! example a list without any item.
+//!
+//! It is composed of an icon and of a short message below it, both centered in the context.
+
+use Alignment;
+use Draw;
+use DrawContext;
+use VerticalAlignment;
+
+use widgets::image;
+use widgets::label;
+
+/// Height of the placeholder, as a fraction of the height of the context.
+const HEIGHT: f32 = 0.5;
+
+/// Maximum height of the text of the message, in pixels.
+const TEXT_MAX_EM_PX: f32 = 24.0;
+
+/// Draws an icon above a message, in the middle of the context.
+///
+/// The icon keeps its aspect ratio and takes the upper half of the placeholder, and the message
+/// is drawn right below it.
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, icon: &D::ImageResource,
+                              text_style: &D::TextStyle, message: &str)
+{
+    if draw.is_empty() {
+        return;
+    }
+
+    let draw = draw.animation_stop();
+    let area = draw.vertical_rescale(HEIGHT, &VerticalAlignment::Center);
+
+    let mut rows = area.vertical_split(2);
+    let icon_row = rows.next().unwrap();
+    let text_row = rows.next().unwrap();
+
+    // A small space between the icon and the message.
+    image::draw(&icon_row.margin(0.0, 0.0, 0.1, 0.0), icon, &Alignment::bottom());
+    label::contain_capped(&text_row, text_style, message, &Alignment::top(), TEXT_MAX_EM_PX);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+
+    use super::draw;
+
+    #[test]
+    fn icon_above_message() {
+        let (mock, _) = mock::frame(800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            draw(ctxt, "icon", "font", "empty")
+        });
+
+        assert_eq!(mock.images(), vec!["icon"]);
+        assert_eq!(mock.text(), "empty");
+
+        let icon_bottom = match mock.calls[0] {
+            Call::Image { ref matrix, .. } => {
+                assert!(matrix.0[2][0].abs() < 0.0001);
+                matrix.0[2][1] - matrix.0[1][1]
+            },
+            _ => panic!()
+        };
+        assert!(icon_bottom > 0.0);
+
+        let glyphs: Vec<[f32; 2]> = mock.calls.iter().filter_map(|call| match *call {
+            Call::Glyph { ref matrix, .. } => Some([matrix.0[2][0], matrix.0[2][1]]),
+            _ => None,
+        }).collect();
+        assert_eq!(glyphs.len(), 5);
+
+        // The glyphs are centered horizontally and below the icon.
+        assert!((glyphs[0][0] + glyphs[4][0]).abs() < 0.0001);
+        for glyph in &glyphs {
+            assert!(glyph[1] < icon_bottom);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A gallery is a grid of clickable thumbnails that can be scrolled vertically.
+//!
+//! The thumbnails are drawn in square cells, from left to right and then from top to bottom. The
+//! right part of the context is occupied by a vertical scrollbar, and the user can also scroll
+//! with the mouse wheel while the cursor is over the grid. The position of the content is stored
+//! in a `ScrollState` that must be kept between frames.
+//!
+//! Clipping the thumbnails that are partially scrolled out requires support from the
+//! implementation of `Draw`, see `Draw::set_clip`.
+
+use AccessibilityRole;
+use Alignment;
+use Draw;
+use DrawContext;
+use UiState;
+
+use widgets::image;
+use widgets::scrollbar;
+use widgets::scrollbar::ScrollState;
+
+/// Part of the width of the context that is occupied by the scrollbar.
+const SCROLLBAR_WIDTH: f32 = 0.05;
+
+/// Draws a grid of thumbnails with `cols` columns, and returns the index of the thumbnail that
+/// has been clicked, if any.
+///
+/// The `highlight` image is stretched under the thumbnail that is hovered, and the `track` and
+/// `thumb` images are used to draw the scrollbar. One notch of the mouse wheel scrolls by one
+/// row.
+#[allow(clippy::too_many_arguments)]
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                              thumbnails: &[&D::ImageResource], cols: usize,
+                              highlight: &D::ImageResource, track: &D::ImageResource,
+                              thumb: &D::ImageResource, state: &mut ScrollState) -> Option<usize>
+{
+    if draw.is_empty() || cols == 0 {
+        return None;
+    }
+
+    let draw = draw.animation_stop();
+
+    let grid = draw.margin(0.0, SCROLLBAR_WIDTH, 0.0, 0.0);
+    let bar = draw.margin(0.0, 0.0, 0.0, 1.0 - SCROLLBAR_WIDTH);
+
+    let rows = thumbnails.len().div_ceil(cols);
+    let cell_px = grid.width() / cols as f32;
+    let content_px = cell_px * rows as f32;
+    let content_ratio = if content_px > 0.0 { grid.height() / content_px } else { 1.0 };
+
+    if grid.is_cursor_hovering() && rows != 0 {
+        let delta = grid.consume_scroll(ui_state);
+        state.offset -= delta[1] / rows as f32;
+    }
+
+    // This also clamps the offset.
+    scrollbar::vertical(&bar, ui_state, track, thumb, state, content_ratio);
+
+    let grid = grid.with_clip();
+    let grid_hovered = grid.is_cursor_hovering();
+    let scroll_px = state.offset * content_px;
+    let mut clicked = None;
+
+    for (index, thumbnail) in thumbnails.iter().enumerate() {
+        // The ids are reserved even for the cells that aren't visible, so that they don't change
+        // when scrolling.
+        let widget_id = grid.reserve_widget_id();
+
+        let top_px = (index / cols) as f32 * cell_px - scroll_px;
+        if top_px + cell_px <= 0.0 || top_px >= grid.height() {
+            continue;
+        }
+
+        let column = (index % cols) as f32;
+        let cell = grid.margin(top_px / grid.height(),
+                               1.0 - (column + 1.0) / cols as f32,
+                               1.0 - (top_px + cell_px) / grid.height(),
+                               column / cols as f32);
+        let cell = &cell.layout_group("gallery_item");
+        cell.push_accessibility(AccessibilityRole::Button, "");
+
+        if grid_hovered && cell.is_cursor_hovering() {
+            cell.set_cursor_hovered_widget();
+            cell.note_hover(&widget_id);
+
+            if cell.cursor_was_pressed() {
+                ui_state.active_widget = Some(widget_id.clone());
+                cell.consume_click();
+            } else if cell.cursor_was_released() &&
+                      ui_state.active_widget.as_ref() == Some(&widget_id)
+            {
+                ui_state.active_widget = None;
+                clicked = Some(index);
+            }
+
+            image::stretch(cell, highlight);
+        }
+
+        let content = cell.uniform_margin(0.05, 0.05, 0.05, 0.05);
+        image::draw(&content, *thumbnail, &Alignment::center());
+    }
+
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use UiState;
+
+    use widgets::scrollbar::ScrollState;
+    use super::draw;
+
+    const THUMBNAILS: &[&str] = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+    /// Draws a gallery of 4 columns whose cells are 99.75 pixels wide, in a context that is 200
+    /// pixels high.
+    fn frame(ui_state: &mut UiState, state: &mut ScrollState, cursor: [f32; 2], pressed: bool,
+             released: bool) -> (Option<usize>, usize)
+    {
+        let (mock, result) = mock::frame(420.0, 200.0, Some(cursor), pressed, released,
+                                         UNIX_EPOCH, |ctxt| {
+            draw(ctxt, ui_state, THUMBNAILS, 4, "highlight", "track", "thumb", state)
+        });
+
+        let highlights = mock.images().iter().filter(|name| **name == "highlight").count();
+        (result, highlights)
+    }
+
+    #[test]
+    fn click_thumbnail() {
+        let mut ui_state = UiState::default();
+        let mut state = ScrollState::new();
+
+        // Third column of the second row.
+        let cursor = [0.1875, -0.5];
+        assert_eq!(frame(&mut ui_state, &mut state, cursor, false, false), (None, 1));
+        assert_eq!(frame(&mut ui_state, &mut state, cursor, true, false), (None, 1));
+        assert_eq!(frame(&mut ui_state, &mut state, cursor, false, true), (Some(6), 1));
+        assert_eq!(ui_state.active_widget, None);
+
+        // Over the scrollbar, nothing is highlighted.
+        assert_eq!(frame(&mut ui_state, &mut state, [0.95, 0.0], false, false), (None, 0));
+    }
+
+    #[test]
+    fn scroll() {
+        let mut ui_state = UiState::default();
+        let mut state = ScrollState::new();
+
+        // Scrolling down by one row, which is the whole remaining content.
+        ui_state.scroll_delta = [0.0, -1.0];
+        frame(&mut ui_state, &mut state, [0.0, 0.0], false, false);
+        assert_eq!(ui_state.scroll_delta, [0.0, 0.0]);
+        assert!((state.offset - (1.0 - 200.0 / (99.75 * 3.0))).abs() < 0.0001);
+
+        // The last row is now at the bottom of the grid.
+        let cursor = [-0.3, -0.5];
+        frame(&mut ui_state, &mut state, cursor, true, false);
+        assert_eq!(frame(&mut ui_state, &mut state, cursor, false, true), (Some(9), 1));
+    }
+}
@@ -24,7 +24,10 @@ pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, image_name: &D::ImageResour
 }
 
 /// Stretches the image if necessary so that it corresponds to the context's area, then draws it.
+#[allow(clippy::collapsible_if)]
 pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, image_name: &D::ImageResource) {
+    draw.record_layout("image");
+
     if !draw.cursor_hovered_widget() {
         if draw.is_cursor_hovering() {
             draw.set_cursor_hovered_widget();
@@ -42,3 +45,47 @@ pub fn cover<D: ?Sized + Draw>(draw: &DrawContext<D>, image_name: &D::ImageResou
     let ratio = draw.draw().get_image_width_per_height(image_name);
     stretch(&draw.enforce_aspect_ratio_upscale(ratio, alignment), image_name)
 }
+
+/// Stretches two images over the context and blends them. `from` is drawn with an opacity of
+/// `1.0 - t` and `to` is drawn above it with an opacity of `t`.
+///
+/// The value of `t` is clamped between `0.0` and `1.0`. The opacity is applied with the tint of
+/// the context, see `Draw::set_tint`.
+pub fn crossfade<D: ?Sized + Draw>(draw: &DrawContext<D>, from: &D::ImageResource,
+                                   to: &D::ImageResource, t: f32)
+{
+    let t = t.clamp(0.0, 1.0);
+
+    stretch(&draw.with_tint([1.0, 1.0, 1.0, 1.0 - t]), from);
+    stretch(&draw.with_tint([1.0, 1.0, 1.0, t]), to);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+
+    use super::crossfade;
+
+    #[test]
+    fn crossfade_opacity() {
+        fn opacities(t: f32) -> Vec<(String, f32)> {
+            let (mock, _) = mock::frame(100.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+                crossfade(ctxt, "from", "to", t)
+            });
+
+            mock.calls.iter().map(|c| match *c {
+                Call::Image { ref name, tint, .. } => (name.clone(), tint[3]),
+                _ => panic!()
+            }).collect()
+        }
+
+        assert_eq!(opacities(0.0), vec![("from".to_owned(), 1.0), ("to".to_owned(), 0.0)]);
+        assert_eq!(opacities(1.0), vec![("from".to_owned(), 0.0), ("to".to_owned(), 1.0)]);
+        assert_eq!(opacities(0.25), vec![("from".to_owned(), 0.75), ("to".to_owned(), 0.25)]);
+        assert_eq!(opacities(-3.0), opacities(0.0));
+        assert_eq!(opacities(3.0), opacities(1.0));
+    }
+}
@@ -29,6 +29,7 @@ use DrawContext;
 ///
 /// - Panics if `top_percent + bottom_percent > 1.0` or `left_percent + right_percent > 1.0`.
 ///
+#[allow(clippy::collapsible_if)]
 pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, left_border_percent: f32,
                               image_name: &D::ImageResource, top_percent: f32, right_percent: f32,
                               bottom_percent: f32, left_percent: f32)
@@ -36,6 +37,8 @@ pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, left_border_percent: f32,
     assert!(top_percent + bottom_percent <= 1.0);
     assert!(left_percent + right_percent <= 1.0);
 
+    draw.record_layout("image9");
+
     let image_width_per_height = draw.draw().get_image_width_per_height(image_name);
 
     let top_border_percent = left_border_percent * top_percent / left_percent * draw.width_per_height() / image_width_per_height;
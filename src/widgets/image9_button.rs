@@ -7,6 +7,7 @@
 
 //! Same as `image9`, except that the image is clickable.
 
+use AccessibilityRole;
 use Draw;
 use DrawContext;
 use UiState;
@@ -16,15 +17,80 @@ use widgets::image9;
 
 /// Same as `image9::draw`, except that the image is clickable. You can specify different images
 /// for when the button is non-hovered, hovered, or active. 
+#[inline]
+#[allow(clippy::too_many_arguments)]
 pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
                               left_border_percent: f32, normal_image: &D::ImageResource,
                               hovered_image: &D::ImageResource, active_image: &D::ImageResource,
                               top_percent: f32, right_percent: f32, bottom_percent: f32,
                               left_percent: f32) -> Interaction
+{
+    draw_impl(draw, ui_state, left_border_percent, normal_image, hovered_image, active_image,
+              top_percent, right_percent, bottom_percent, left_percent, None)
+}
+
+/// Same as `draw`, except that the button has rounded corners whose radius is
+/// `corner_radius_px` pixels. The cursor doesn't hover the button when it is outside of the
+/// rounded corners.
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub fn draw_rounded<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                      left_border_percent: f32, normal_image: &D::ImageResource,
+                                      hovered_image: &D::ImageResource,
+                                      active_image: &D::ImageResource, top_percent: f32,
+                                      right_percent: f32, bottom_percent: f32, left_percent: f32,
+                                      corner_radius_px: f32) -> Interaction
+{
+    draw_impl(draw, ui_state, left_border_percent, normal_image, hovered_image, active_image,
+              top_percent, right_percent, bottom_percent, left_percent, Some(corner_radius_px))
+}
+
+/// Returns true if the cursor is hovering the context, whose corners are rounded with a radius of
+/// `corner_radius_px` pixels.
+///
+/// This is the same test as `is_cursor_hovering`, except that the cursor must also be inside of
+/// the rounded corners.
+fn is_cursor_in_rounded<D: ?Sized + Draw>(draw: &DrawContext<D>, corner_radius_px: f32) -> bool {
+    if !draw.is_cursor_hovering() {
+        return false;
+    }
+
+    let cursor = match draw.cursor_hover_coordinates() {
+        Some(c) => c,
+        None => return false,
+    };
+
+    // Position of the cursor relative to the center of the context, in pixels.
+    let half_width = draw.width() / 2.0;
+    let half_height = draw.height() / 2.0;
+    let x = (cursor[0] * half_width).abs();
+    let y = (cursor[1] * half_height).abs();
+
+    let radius = (corner_radius_px * draw.ui_scale()).min(half_width).min(half_height).max(0.0);
+    let dx = (x - (half_width - radius)).max(0.0);
+    let dy = (y - (half_height - radius)).max(0.0);
+    dx * dx + dy * dy <= radius * radius
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_impl<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                               left_border_percent: f32, normal_image: &D::ImageResource,
+                               hovered_image: &D::ImageResource, active_image: &D::ImageResource,
+                               top_percent: f32, right_percent: f32, bottom_percent: f32,
+                               left_percent: f32, corner_radius_px: Option<f32>) -> Interaction
 {
     let widget_id = draw.reserve_widget_id();
+    let draw = &draw.layout_group("button");
+    draw.push_accessibility(AccessibilityRole::Button, "");
+
+    let hovering = match corner_radius_px {
+        Some(radius) => is_cursor_in_rounded(draw, radius),
+        None => draw.is_cursor_hovering(),
+    };
+
+    if hovering {
+        draw.note_hover(&widget_id);
 
-    if draw.is_cursor_hovering() {
         if Some(widget_id.clone()) == ui_state.active_widget {
             image9::draw(draw, left_border_percent, active_image, top_percent, right_percent,
                          bottom_percent, left_percent);
@@ -40,6 +106,7 @@ pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
             image9::draw(draw, left_border_percent, active_image, top_percent, right_percent,
                          bottom_percent, left_percent);
             ui_state.active_widget = Some(widget_id.clone());
+            draw.consume_click();
             Interaction::None
 
         } else {
@@ -54,3 +121,64 @@ pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
         Interaction::None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use Alignment;
+    use UiState;
+
+    use super::draw;
+    use super::draw_rounded;
+
+    #[test]
+    fn rounded_corners() {
+        fn click(cursor: [f32; 2]) -> bool {
+            let mut ui_state = UiState::default();
+            let mut clicked = false;
+
+            for &(pressed, released) in &[(true, false), (false, true)] {
+                clicked = mock::frame(100.0, 100.0, Some(cursor), pressed, released, UNIX_EPOCH,
+                                      |ctxt| {
+                    draw_rounded(ctxt, &mut ui_state, 0.1, "normal", "hovered", "active", 0.1, 0.1,
+                                 0.1, 0.1, 30.0).clicked()
+                }).1;
+            }
+
+            clicked
+        }
+
+        assert!(click([0.0, 0.0]));
+        assert!(click([0.95, 0.0]));
+        assert!(!click([0.95, 0.95]));
+        assert!(!click([-0.95, -0.95]));
+        assert!(click([-0.7, 0.7]));
+    }
+
+    #[test]
+    fn rounded_same_hit_area_without_radius() {
+        fn hovered(rounded: bool, cursor: [f32; 2]) -> bool {
+            let mut ui_state = UiState::default();
+            let (mock, _) = mock::frame(100.0, 50.0, Some(cursor), false, false, UNIX_EPOCH,
+                                        |ctxt| {
+                let ctxt = ctxt.rescale(0.5, 0.5, &Alignment::center());
+                if rounded {
+                    draw_rounded(&ctxt, &mut ui_state, 0.1, "normal", "hovered", "active", 0.1,
+                                 0.1, 0.1, 0.1, 0.0)
+                } else {
+                    draw(&ctxt, &mut ui_state, 0.1, "normal", "hovered", "active", 0.1, 0.1, 0.1,
+                         0.1)
+                }
+            });
+            mock.images()[0] == "hovered"
+        }
+
+        for &cursor in &[[0.0, 0.0], [0.49, -0.49], [0.51, 0.0], [0.0, -0.51]] {
+            assert_eq!(hovered(true, cursor), hovered(false, cursor));
+        }
+        assert!(hovered(true, [0.49, -0.49]));
+        assert!(!hovered(true, [0.51, 0.0]));
+    }
+}
@@ -11,13 +11,23 @@
 //! or an active state.
 //!
 //! All the functions in this module return an `Interaction` object that indicates whether they
-//! were clicked. 
+//! were clicked, except for `draw_long_press` which returns a `PressResult`.
 
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use AccessibilityRole;
 use Alignment;
 use Draw;
 use DrawContext;
+use Matrix;
+use Theme;
 use UiState;
 
+use animations;
+use animations::EaseOut;
+use animations::Interpolation;
 use widgets::Interaction;
 
 /// Same as `image::draw`, except that the image is clickable.
@@ -32,18 +42,251 @@ pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
             hovered_image, active_image)
 }
 
+/// Same as `draw`, except that the images are taken from the theme.
+#[inline]
+pub fn draw_themed<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                     theme: &Theme<D>, alignment: &Alignment) -> Interaction
+{
+    self::draw(draw, ui_state, theme.button_normal, theme.button_hovered, theme.button_active,
+               alignment)
+}
+
+/// Same as `stretch`, except that the images are taken from the theme.
+#[inline]
+pub fn stretch_themed<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                        theme: &Theme<D>) -> Interaction
+{
+    stretch(draw, ui_state, theme.button_normal, theme.button_hovered, theme.button_active)
+}
+
+/// Draws a checkbox with the checkbox images of the theme, and toggles `checked` when it is
+/// clicked.
+pub fn checkbox_themed<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                         theme: &Theme<D>, checked: &mut bool) -> Interaction
+{
+    let image = if *checked { theme.checkbox_checked } else { theme.checkbox_unchecked };
+    let interaction = stretch(draw, ui_state, image, image, image);
+    if interaction.clicked() {
+        *checked = !*checked;
+    }
+    interaction
+}
+
 /// Same as `image::stretch`, except that the image is clickable.
 pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
                                  normal_image: &D::ImageResource, hovered_image: &D::ImageResource,
                                  active_image: &D::ImageResource) -> Interaction
+{
+    let matrix = draw.matrix();
+    stretch_with_matrix(draw, &matrix, ui_state, normal_image, hovered_image, active_image)
+}
+
+/// Same as `stretch`, except that the button can be disabled.
+///
+/// A disabled button is always drawn with the `normal` image and never returns `Clicked`. If it
+/// was being pressed when it got disabled, it stops being the active widget. It still counts as
+/// hovered, so that the clicks don't go through it.
+pub fn draw_states<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                     normal: &D::ImageResource, hovered: &D::ImageResource,
+                                     pressed: &D::ImageResource, disabled: bool) -> Interaction
+{
+    if !disabled {
+        return stretch(draw, ui_state, normal, hovered, pressed);
+    }
+
+    let widget_id = draw.reserve_widget_id();
+    draw.push_accessibility(AccessibilityRole::Button, "");
+    draw.record_layout("button");
+
+    if ui_state.active_widget.as_ref() == Some(&widget_id) {
+        ui_state.active_widget = None;
+    }
+
+    if draw.is_cursor_hovering() {
+        draw.set_cursor_hovered_widget();
+    }
+
+    draw.draw().draw_image(normal, &draw.matrix());
+    Interaction::None
+}
+
+/// Same as `stretch`, except that the button grows to `hover_scale` times its size while it is
+/// hovered, and shrinks back to its normal size when it is no longer hovered.
+///
+/// The size changes smoothly with an `EaseOut` interpolation. The `anim` parameter holds the
+/// state of the animation and must be kept between frames.
+///
+/// The area where the button can be hovered or clicked is always the context's area, no matter
+/// the current scale. This avoids jitters when the cursor is near the border of the button.
+pub fn draw_hover_scale<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                          normal_image: &D::ImageResource,
+                                          hovered_image: &D::ImageResource,
+                                          active_image: &D::ImageResource, hover_scale: f32,
+                                          anim: &mut HoverAnim) -> Interaction
+{
+    let now = draw.now();
+    let draw = draw.animation_stop();
+
+    let target = if draw.is_cursor_hovering() { hover_scale } else { 1.0 };
+    if target != anim.to_scale {
+        anim.from_scale = anim.scale(now);
+        anim.to_scale = target;
+        anim.start = now;
+    }
+
+    if anim.is_running(now) {
+        draw.request_redraw();
+    }
+
+    let scale = anim.scale(now);
+    let matrix = draw.rescale(scale, scale, &Alignment::center()).matrix();
+    stretch_with_matrix(&draw, &matrix, ui_state, normal_image, hovered_image, active_image)
+}
+
+/// State of the animation of a button drawn with `draw_hover_scale`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct HoverAnim {
+    /// Duration of the transition between the normal size and the hovered size.
+    pub duration: Duration,
+
+    from_scale: f32,
+    to_scale: f32,
+    start: SystemTime,
+}
+
+impl HoverAnim {
+    /// Builds a `HoverAnim` object. The button starts at its normal size.
+    #[inline]
+    pub fn new(duration: Duration) -> HoverAnim {
+        HoverAnim {
+            duration,
+            from_scale: 1.0,
+            to_scale: 1.0,
+            start: UNIX_EPOCH,
+        }
+    }
+
+    /// Returns the scale of the button at the given point in time.
+    #[inline]
+    pub fn scale(&self, now: SystemTime) -> f32 {
+        let percent = EaseOut::default().calculate(now, self.start, self.duration) as f32;
+        self.from_scale + (self.to_scale - self.from_scale) * percent
+    }
+
+    /// Returns true if the scale of the button is still changing.
+    #[inline]
+    fn is_running(&self, now: SystemTime) -> bool {
+        animations::cycles_elapsed(now, self.start, self.duration) < 1.0
+    }
+}
+
+impl Default for HoverAnim {
+    #[inline]
+    fn default() -> HoverAnim {
+        HoverAnim::new(Duration::from_millis(150))
+    }
+}
+
+/// Same as `stretch`, except that the button also reports when it is held for longer than
+/// `threshold`.
+///
+/// `LongPressed` is returned once, when the threshold is reached while the cursor is still
+/// pressed inside of the button. The click that follows the release is then ignored. Moving the
+/// cursor out of the button cancels the long press. The `state` must be kept between frames.
+pub fn draw_long_press<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                         normal_image: &D::ImageResource,
+                                         hovered_image: &D::ImageResource,
+                                         active_image: &D::ImageResource, state: &mut PressState,
+                                         threshold: Duration) -> PressResult
+{
+    let now = draw.now();
+    // The button consumes the click, so this must be read before drawing it.
+    let pressed = draw.cursor_was_pressed();
+    let interaction = stretch(draw, ui_state, normal_image, hovered_image, active_image);
+
+    if !draw.is_cursor_hovering() {
+        state.start = None;
+    } else if pressed {
+        state.start = Some(now);
+        state.long_pressed = false;
+    }
+
+    let mut result = PressResult::None;
+
+    if let Some(start) = state.start {
+        if !state.long_pressed && now.duration_since(start).map(|d| d >= threshold)
+                                                           .unwrap_or(false)
+        {
+            state.long_pressed = true;
+            result = PressResult::LongPressed;
+        }
+
+        if !state.long_pressed {
+            // Nothing happens while the user holds the button, so we have to redraw in order to
+            // notice when the threshold is reached.
+            draw.request_redraw();
+        }
+    }
+
+    if interaction.clicked() && !state.long_pressed {
+        result = PressResult::Clicked;
+    }
+
+    if draw.cursor_was_released() {
+        state.start = None;
+        state.long_pressed = false;
+    }
+
+    result
+}
+
+/// Result of `draw_long_press`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum PressResult {
+    /// The button was clicked.
+    Clicked,
+    /// The button has been held for longer than the threshold.
+    LongPressed,
+    /// Nothing happened.
+    None,
+}
+
+/// State of a button drawn with `draw_long_press`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PressState {
+    /// Moment when the button was pressed, if it is currently being pressed.
+    start: Option<SystemTime>,
+    /// True if `LongPressed` has been returned for the current press.
+    long_pressed: bool,
+}
+
+impl PressState {
+    /// Builds a `PressState` for a button that isn't pressed.
+    #[inline]
+    pub fn new() -> PressState {
+        PressState::default()
+    }
+}
+
+/// Draws the button with the given matrix, but uses the context for the interactions.
+fn stretch_with_matrix<D: ?Sized + Draw>(draw: &DrawContext<D>, matrix: &Matrix,
+                                         ui_state: &mut UiState, normal_image: &D::ImageResource,
+                                         hovered_image: &D::ImageResource,
+                                         active_image: &D::ImageResource) -> Interaction
 {
     let widget_id = draw.reserve_widget_id();
+    draw.push_accessibility(AccessibilityRole::Button, "");
+    draw.record_layout("button");
 
     if draw.is_cursor_hovering() {
         draw.set_cursor_hovered_widget();
+        draw.note_hover(&widget_id);
 
         if Some(widget_id.clone()) == ui_state.active_widget {
-            draw.draw().draw_image(active_image, &draw.matrix());
+            draw.draw().draw_image(active_image, matrix);
 
             if draw.cursor_was_released() {
                 ui_state.active_widget = None;
@@ -53,17 +296,210 @@ pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
             }
 
         } else if draw.cursor_was_pressed() {
-            draw.draw().draw_image(active_image, &draw.matrix());
+            draw.draw().draw_image(active_image, matrix);
             ui_state.active_widget = Some(widget_id.clone());
+            draw.consume_click();
             Interaction::None
 
         } else {
-            draw.draw().draw_image(hovered_image, &draw.matrix());
+            draw.draw().draw_image(hovered_image, matrix);
             Interaction::None
         }
 
     } else {
-        draw.draw().draw_image(normal_image, &draw.matrix());
+        draw.draw().draw_image(normal_image, matrix);
         Interaction::None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+    use AccessibilityNode;
+    use AccessibilityRole;
+    use Alignment;
+    use HorizontalAlignment;
+    use UiState;
+
+    use widgets::label;
+
+    use super::draw_hover_scale;
+    use super::checkbox_themed;
+    use super::draw_long_press;
+    use super::draw_states;
+    use super::stretch_themed;
+    use super::HoverAnim;
+    use super::PressResult;
+    use super::PressState;
+    use super::stretch;
+
+    #[test]
+    fn themed_forwards_normal_image() {
+        let theme = mock::theme();
+
+        let mut ui_state = UiState::default();
+        let (mock, clicked) = mock::frame(100.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            stretch_themed(ctxt, &mut ui_state, &theme).clicked()
+        });
+
+        assert!(!clicked);
+
+        assert_eq!(mock.images(), vec!["normal"]);
+    }
+
+    #[test]
+    fn checkbox_toggles() {
+        let theme = mock::theme();
+        let mut ui_state = UiState::default();
+        let mut checked = false;
+
+        for &(pressed, released) in &[(true, false), (false, true)] {
+            let _ = mock::frame(100.0, 100.0, Some([0.0, 0.0]), pressed, released, UNIX_EPOCH,
+                                |ctxt| checkbox_themed(ctxt, &mut ui_state, &theme, &mut checked));
+        }
+        assert!(checked);
+
+        let (mock, _) = mock::frame(100.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            checkbox_themed(ctxt, &mut ui_state, &theme, &mut checked)
+        });
+        assert_eq!(mock.images(), vec!["checked"]);
+    }
+
+    #[test]
+    fn states() {
+        fn frame(ui_state: &mut UiState, cursor: [f32; 2], pressed: bool, released: bool,
+                 disabled: bool) -> (String, bool)
+        {
+            let (mock, clicked) = mock::frame(100.0, 100.0, Some(cursor), pressed, released,
+                                              UNIX_EPOCH, |ctxt| {
+                let ctxt = ctxt.rescale(0.5, 0.5, &Alignment::center());
+                draw_states(&ctxt, ui_state, "normal", "hovered", "pressed", disabled).clicked()
+            });
+
+            (mock.images()[0].to_owned(), clicked)
+        }
+
+        let mut ui_state = UiState::default();
+        let image = |s: &str, clicked| (s.to_owned(), clicked);
+
+        assert_eq!(frame(&mut ui_state, [0.9, 0.9], false, false, false), image("normal", false));
+        assert_eq!(frame(&mut ui_state, [0.0, 0.0], false, false, false), image("hovered", false));
+        assert_eq!(frame(&mut ui_state, [0.0, 0.0], true, false, false), image("pressed", false));
+        assert_eq!(frame(&mut ui_state, [0.0, 0.0], false, false, false), image("pressed", false));
+        assert_eq!(frame(&mut ui_state, [0.0, 0.0], false, true, false), image("pressed", true));
+
+        // A disabled button ignores the cursor.
+        assert_eq!(frame(&mut ui_state, [0.0, 0.0], false, false, true), image("normal", false));
+        assert_eq!(frame(&mut ui_state, [0.0, 0.0], true, false, true), image("normal", false));
+        assert_eq!(frame(&mut ui_state, [0.0, 0.0], false, true, true), image("normal", false));
+        assert_eq!(ui_state.active_widget, None);
+
+        // Disabling a button while it is pressed cancels the click.
+        frame(&mut ui_state, [0.0, 0.0], true, false, false);
+        assert!(ui_state.active_widget.is_some());
+        frame(&mut ui_state, [0.0, 0.0], false, false, true);
+        assert_eq!(ui_state.active_widget, None);
+        assert_eq!(frame(&mut ui_state, [0.0, 0.0], false, true, false), image("hovered", false));
+    }
+
+    #[test]
+    fn accessibility() {
+        fn frame(enable: bool) -> Vec<AccessibilityNode> {
+            let shared = ::draw();
+            if enable {
+                shared.enable_accessibility();
+            }
+
+            mock::frame_in(&shared, 100.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+                let ctxt = ctxt.rescale(0.5, 0.25, &Alignment::top_left());
+                let _ = stretch(&ctxt, &mut UiState::default(), "normal", "hovered", "active");
+                label::flow(&ctxt, "font", "OK", &HorizontalAlignment::Center);
+            });
+
+            shared.accessibility_tree()
+        }
+
+        assert_eq!(frame(false), vec![]);
+        assert_eq!(frame(true), vec![
+            AccessibilityNode {
+                role: AccessibilityRole::Button,
+                label: String::new(),
+                bounds: [-1.0, 0.5, 0.0, 1.0],
+            },
+            AccessibilityNode {
+                role: AccessibilityRole::Label,
+                label: "OK".to_owned(),
+                bounds: [-1.0, 0.5, 0.0, 1.0],
+            },
+        ]);
+    }
+
+    #[test]
+    fn hover_scale() {
+        fn frame(ui_state: &mut UiState, anim: &mut HoverAnim, cursor: [f32; 2], pressed: bool,
+                 released: bool, now_ms: u64) -> (bool, f32)
+        {
+            let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+            let (mock, clicked) = mock::frame(100.0, 100.0, Some(cursor), pressed, released, now,
+                                              |ctxt| {
+                let ctxt = ctxt.rescale(0.5, 0.5, &Alignment::center());
+                draw_hover_scale(&ctxt, ui_state, "normal", "hovered", "active", 1.5,
+                                 anim).clicked()
+            });
+
+            match mock.calls[0] {
+                Call::Image { ref matrix, .. } => (clicked, matrix.0[0][0] / 0.5),
+                _ => panic!()
+            }
+        }
+
+        let mut ui_state = UiState::default();
+        let mut anim = HoverAnim::new(Duration::from_millis(20));
+
+        let (_, scale) = frame(&mut ui_state, &mut anim, [0.0, 0.0], false, false, 1000);
+        assert_eq!(scale, 1.0);
+        let (_, scale) = frame(&mut ui_state, &mut anim, [0.0, 0.0], false, false, 1010);
+        assert!(scale > 1.0 && scale < 1.5);
+        let (_, scale) = frame(&mut ui_state, &mut anim, [0.0, 0.0], false, false, 1030);
+        assert!((scale - 1.5).abs() < 0.0001);
+
+        // The cursor is within the scaled button but outside of the context.
+        assert!(!frame(&mut ui_state, &mut anim, [0.6, 0.0], true, false, 1040).0);
+        assert!(!frame(&mut ui_state, &mut anim, [0.6, 0.0], false, true, 1050).0);
+        assert_eq!(ui_state.active_widget, None);
+
+        assert!(!frame(&mut ui_state, &mut anim, [0.4, 0.0], true, false, 1060).0);
+        assert!(frame(&mut ui_state, &mut anim, [0.4, 0.0], false, true, 1070).0);
+    }
+
+    #[test]
+    fn long_press() {
+        fn frame(ui_state: &mut UiState, state: &mut PressState, pressed: bool, released: bool,
+                 now_ms: u64) -> PressResult
+        {
+            let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+            mock::frame(100.0, 100.0, Some([0.0, 0.0]), pressed, released, now, |ctxt| {
+                draw_long_press(ctxt, ui_state, "normal", "hovered", "active", state,
+                                Duration::from_millis(30))
+            }).1
+        }
+
+        let mut ui_state = UiState::default();
+        let mut state = PressState::new();
+
+        // A short press is a click.
+        assert_eq!(frame(&mut ui_state, &mut state, true, false, 0), PressResult::None);
+        assert_eq!(frame(&mut ui_state, &mut state, false, true, 10), PressResult::Clicked);
+
+        assert_eq!(frame(&mut ui_state, &mut state, true, false, 100), PressResult::None);
+        assert_eq!(frame(&mut ui_state, &mut state, false, false, 120), PressResult::None);
+        assert_eq!(frame(&mut ui_state, &mut state, false, false, 130), PressResult::LongPressed);
+        assert_eq!(frame(&mut ui_state, &mut state, false, false, 140), PressResult::None);
+        assert_eq!(frame(&mut ui_state, &mut state, false, true, 150), PressResult::None);
+        assert_eq!(ui_state.active_widget, None);
+    }
+}
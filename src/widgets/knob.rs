@@ -0,0 +1,218 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A knob is a dial that the user can turn in order to choose a value between `0.0` and `1.0`.
+//!
+//! A widget like this is composed of two images:
+//!
+//! - The base, which is stretched over the whole context.
+//! - The indicator, which is also stretched over the whole context and rotated around its
+//!   center. When the value is `0.5`, the indicator is drawn without any rotation, so the image
+//!   should point upwards.
+//!
+//! Only the ellipse inscribed in the context can be pressed. The `draw` function lets the user
+//! turn the knob by dragging around its center, while the `vertical` function lets the user drag
+//! up or down. The drag is stored in a `KnobState` that must be kept between frames.
+
+use std::f32::consts::PI;
+
+use AccessibilityRole;
+use Draw;
+use DrawContext;
+use UiState;
+
+use widgets::image;
+
+/// State of a knob. Must be kept between frames.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct KnobState {
+    /// While the knob is dragged, angle or vertical position of the cursor at the previous
+    /// frame.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    grab: Option<f32>,
+}
+
+impl KnobState {
+    /// Builds a `KnobState` for a knob that isn't dragged.
+    #[inline]
+    pub fn new() -> KnobState {
+        KnobState::default()
+    }
+}
+
+/// Draws a knob that the user turns by dragging around its center, and updates `value`.
+///
+/// The indicator covers `range_deg` degrees, clockwise. For example with `270.0` a value of `0.0`
+/// points to the bottom-left and a value of `1.0` points to the bottom-right. The range is
+/// clamped between `0.0` and `360.0`, and `value` is clamped between `0.0` and `1.0`.
+///
+/// The knob is only turned while the cursor is within its ellipse.
+#[inline]
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                              base: &D::ImageResource, indicator: &D::ImageResource,
+                              value: &mut f32, state: &mut KnobState, range_deg: f32)
+{
+    draw_impl(draw, ui_state, base, indicator, value, state, range_deg, false)
+}
+
+/// Same as `draw`, except that the user turns the knob by dragging up or down. Dragging by the
+/// height of the knob goes from `0.0` to `1.0`.
+#[inline]
+pub fn vertical<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                  base: &D::ImageResource, indicator: &D::ImageResource,
+                                  value: &mut f32, state: &mut KnobState, range_deg: f32)
+{
+    draw_impl(draw, ui_state, base, indicator, value, state, range_deg, true)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_impl<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                               base: &D::ImageResource, indicator: &D::ImageResource,
+                               value: &mut f32, state: &mut KnobState, range_deg: f32,
+                               vertical: bool)
+{
+    let widget_id = draw.reserve_widget_id();
+    let draw = &draw.layout_group("knob");
+    draw.push_accessibility(AccessibilityRole::Slider, "");
+    let draw = draw.animation_stop();
+
+    let range = (range_deg / 360.0).clamp(0.0, 1.0) * 2.0 * PI;
+    *value = value.clamp(0.0, 1.0);
+
+    let grab = if vertical {
+        draw.cursor_local_coordinates().map(|cursor| cursor[1])
+    } else {
+        draw.cursor_angle()
+    };
+
+    if draw.is_cursor_hovering_ellipse() {
+        draw.note_hover(&widget_id);
+
+        if draw.cursor_was_pressed() {
+            ui_state.active_widget = Some(widget_id.clone());
+            draw.consume_click();
+            state.grab = grab;
+        }
+    }
+
+    if draw.is_dragging_active(ui_state, &widget_id) {
+        if let (Some(previous), Some(current)) = (state.grab, grab) {
+            let delta = if vertical {
+                (current - previous) / 2.0
+            } else if range > 0.0 {
+                // The angles go counter-clockwise while the value goes clockwise.
+                -wrap_angle(current - previous) / range
+            } else {
+                0.0
+            };
+
+            *value = (*value + delta).clamp(0.0, 1.0);
+        }
+
+        state.grab = grab;
+
+        if draw.cursor_was_released() {
+            ui_state.active_widget = None;
+            state.grab = None;
+        }
+    }
+
+    image::stretch(&draw, base);
+    image::stretch(&draw.rotated_draw((*value - 0.5) * range), indicator);
+}
+
+/// Brings an angle difference between `-π` and `π`.
+#[inline]
+fn wrap_angle(angle: f32) -> f32 {
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+    use UiState;
+
+    use super::draw;
+    use super::vertical;
+    use super::KnobState;
+
+    /// Draws a frame and returns the clockwise rotation of the indicator, in degrees.
+    fn frame(ui_state: &mut UiState, state: &mut KnobState, value: &mut f32, cursor: [f32; 2],
+             pressed: bool, released: bool, is_vertical: bool) -> f32
+    {
+        let (mock, _) = mock::frame(100.0, 100.0, Some(cursor), pressed, released, UNIX_EPOCH,
+                                    |ctxt| {
+            if is_vertical {
+                vertical(ctxt, ui_state, "base", "indicator", value, state, 270.0);
+            } else {
+                draw(ctxt, ui_state, "base", "indicator", value, state, 270.0);
+            }
+        });
+
+        match mock.calls[1] {
+            Call::Image { ref name, ref matrix, .. } => {
+                assert_eq!(name, "indicator");
+                (-matrix.0[0][1]).atan2(matrix.0[0][0]).to_degrees()
+            },
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn drag_around() {
+        let mut ui_state = UiState::default();
+        let mut state = KnobState::new();
+        let mut value = 0.5;
+
+        // Grabbing the top of the knob doesn't change the value.
+        let angle = frame(&mut ui_state, &mut state, &mut value, [0.0, 0.5], true, false, false);
+        assert!((value - 0.5).abs() < 0.0001);
+        assert!(angle.abs() < 0.01);
+
+        // A quarter turn clockwise.
+        let angle = frame(&mut ui_state, &mut state, &mut value, [0.5, 0.0], false, false, false);
+        assert!((value - (0.5 + 90.0 / 270.0)).abs() < 0.0001);
+        assert!((angle - 90.0).abs() < 0.01);
+
+        // Another quarter turn goes past the end of the range.
+        let angle = frame(&mut ui_state, &mut state, &mut value, [0.0, -0.5], false, true, false);
+        assert_eq!(value, 1.0);
+        assert!((angle - 135.0).abs() < 0.01);
+        assert_eq!(ui_state.active_widget, None);
+
+        // The corners of the context aren't part of the knob.
+        frame(&mut ui_state, &mut state, &mut value, [-0.9, -0.9], true, false, false);
+        assert_eq!(ui_state.active_widget, None);
+    }
+
+    #[test]
+    fn drag_vertically() {
+        let mut ui_state = UiState::default();
+        let mut state = KnobState::new();
+        let mut value = 0.5;
+
+        frame(&mut ui_state, &mut state, &mut value, [0.0, 0.0], true, false, true);
+        let angle = frame(&mut ui_state, &mut state, &mut value, [0.8, -0.5], false, false, true);
+        assert!((value - 0.25).abs() < 0.0001);
+        assert!((angle + 67.5).abs() < 0.01);
+
+        // The drag continues outside of the knob.
+        frame(&mut ui_state, &mut state, &mut value, [0.0, -3.0], false, true, true);
+        assert_eq!(value, 0.0);
+    }
+}
@@ -7,14 +7,17 @@
 
 //! A label is a single line of text.
 //!
-//! You usually want to use the `flow` function.
+//! You usually want to use the `flow` function. The `block` function draws multiple lines on
+//! top of each other.
 
-use std::mem;
 
+use AccessibilityRole;
 use Alignment;
 use Draw;
 use DrawContext;
 use HorizontalAlignment;
+use Theme;
+use VerticalAlignment;
 use matrix::Matrix;
 
 /// Draws text. The text will always have the same height as the context and will stretch
@@ -23,32 +26,126 @@ use matrix::Matrix;
 /// This is usually the function that you want in order to draw text. Even though the text
 /// can overflow its container if it is too long, it is usually visually better to have an
 /// overflow than to have multiple texts of different heights when they should be the same.
+#[inline]
 pub fn flow<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
                               alignment: &HorizontalAlignment)
+{
+    flow_impl(draw, text_style, text, alignment, None)
+}
+
+/// Same as `flow`, except that the height of an EM can't be larger than `max_em_px` pixels.
+///
+/// If the context is taller than that, the text is vertically centered in the context.
+#[inline]
+pub fn flow_capped<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
+                                     alignment: &HorizontalAlignment, max_em_px: f32)
+{
+    flow_impl(draw, text_style, text, alignment, Some(max_em_px))
+}
+
+/// Same as `flow`, except that the text style is taken from the theme.
+#[inline]
+pub fn flow_themed<D: ?Sized + Draw>(draw: &DrawContext<D>, theme: &Theme<D>, text: &str,
+                                     alignment: &HorizontalAlignment)
+{
+    flow(draw, theme.text, text, alignment)
+}
+
+/// Draws multiple lines of text on top of each other, each with its own alignment.
+///
+/// All the lines have the same height, and are drawn with `flow`. `line_spacing` is the space
+/// between two lines, as a multiple of the height of a line. For example a heading followed by
+/// two lines of text, with a spacing of `0.5`, will divide the height of the context in
+/// `1 + 0.5 + 1 + 0.5 + 1` parts.
+pub fn block<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle,
+                               lines: &[(&str, HorizontalAlignment)], line_spacing: f32)
+{
+    if lines.is_empty() {
+        return;
+    }
+
+    let line_spacing = if line_spacing < 0.0 { 0.0 } else { line_spacing };
+    let weights: Vec<f32> = (0 .. lines.len() * 2 - 1)
+                                .map(|n| if n % 2 == 0 { 1.0 } else { line_spacing })
+                                .collect();
+
+    let line_ctxts = draw.vertical_split_weights(weights).step_by(2);
+    for (ctxt, &(text, ref alignment)) in line_ctxts.zip(lines.iter()) {
+        flow(&ctxt, text_style, text, alignment);
+    }
+}
+
+/// Draws text. The text will be sized so that it is entirely contained within the context, and
+/// either its width or its height is equal to the width or the height of the context.
+#[inline]
+pub fn contain<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
+                                 alignment: &Alignment)
+{
+    contain_impl(draw, text_style, text, alignment, None)
+}
+
+/// Same as `contain`, except that the height of an EM can't be larger than `max_em_px` pixels.
+///
+/// If the text would be larger than that, it is shrunk and positioned in the available space
+/// according to the alignment.
+#[inline]
+pub fn contain_capped<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle,
+                                        text: &str, alignment: &Alignment, max_em_px: f32)
+{
+    contain_impl(draw, text_style, text, alignment, Some(max_em_px))
+}
+
+/// Draws text. The text will be sized so that it entirely covers the context, and either its
+/// width or its height is equal to the width or the height of the context.
+#[inline]
+pub fn cover<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
+                               alignment: &Alignment)
+{
+    cover_impl(draw, text_style, text, alignment, None)
+}
+
+/// Same as `cover`, except that the height of an EM can't be larger than `max_em_px` pixels.
+///
+/// If the text would be larger than that, it is shrunk and positioned in the available space
+/// according to the alignment. The text then doesn't cover the context anymore.
+#[inline]
+pub fn cover_capped<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
+                                      alignment: &Alignment, max_em_px: f32)
+{
+    cover_impl(draw, text_style, text, alignment, Some(max_em_px))
+}
+
+fn flow_impl<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
+                               alignment: &HorizontalAlignment, max_em_px: Option<f32>)
 {
     let draw = draw.animation_stop();
+    let draw = match max_em_px.map(|max| max * draw.ui_scale()) {
+        Some(max) if draw.height() > max => {
+            draw.vertical_rescale(max / draw.height(), &VerticalAlignment::Center)
+        },
+        _ => draw,
+    };
+
     helper(&draw, text_style, text, |ratio| {
         let current_width_per_height = draw.width_per_height();
-        let draw = draw.horizontal_rescale(ratio / current_width_per_height, &alignment);
+        let draw = draw.horizontal_rescale(ratio / current_width_per_height, alignment);
 
-        if !draw.cursor_hovered_widget() {
-            if draw.is_cursor_hovering() {
-                draw.set_cursor_hovered_widget();
-            }
+        if !draw.cursor_hovered_widget() && draw.is_cursor_hovering() {
+            draw.set_cursor_hovered_widget();
         }
 
         draw.matrix()
     })
 }
 
-/// Draws text. The text will be sized so that it is entirely contained within the context, and
-/// either its width or its height is equal to the width or the height of the context.
-pub fn contain<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
-                                 alignment: &Alignment)
+#[allow(clippy::collapsible_if)]
+fn contain_impl<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
+                                  alignment: &Alignment, max_em_px: Option<f32>)
 {
     let draw = draw.animation_stop();
     helper(&draw, text_style, text, |ratio| {
         let draw = draw.enforce_aspect_ratio_downscale(ratio, alignment);
+        let draw = cap(draw, alignment, max_em_px);
 
         if !draw.cursor_hovered_widget() {
             if draw.is_cursor_hovering() {
@@ -60,14 +157,14 @@ pub fn contain<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyl
     })
 }
 
-/// Draws text. The text will be sized so that it entirely covers the context, and either its
-/// width or its height is equal to the width or the height of the context.
-pub fn cover<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
-                               alignment: &Alignment)
+#[allow(clippy::collapsible_if)]
+fn cover_impl<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
+                                alignment: &Alignment, max_em_px: Option<f32>)
 {
     let draw = draw.animation_stop();
     helper(&draw, text_style, text, |ratio| {
         let draw = draw.enforce_aspect_ratio_upscale(ratio, alignment);
+        let draw = cap(draw, alignment, max_em_px);
 
         if !draw.cursor_hovered_widget() {
             if draw.is_cursor_hovering() {
@@ -79,22 +176,32 @@ pub fn cover<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle,
     })
 }
 
-fn helper<D: ?Sized + Draw, F>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
-                               final_matrix: F)
-    where F: FnOnce(f32) -> Matrix
+/// Returns the width of the text, as a multiple of the height of the line.
+///
+/// This is the aspect ratio that `flow` gives to the text, and can be used to calculate the size
+/// of a widget before drawing it. Returns `0.0` if the text is empty.
+pub fn measure_width<D: ?Sized + Draw>(draw: &DrawContext<D>, text_style: &D::TextStyle,
+                                       text: &str) -> f32
 {
-    let mut glyphs: Vec<(char, Matrix)> = Vec::with_capacity(text.len());
+    layout(&mut **draw.draw(), text_style, text, |_, _| ())
+}
 
+/// Lays out the glyphs of a line of text, calls `glyph` with each character and its matrix, and
+/// returns the width of the text in ems.
+///
+/// The matrices are in a coordinate system where 1.0 unit is equal to 1.0 EM and the
+/// bottom-left corner of the first glyph is 0.0. Y=1.0 is the top of the line of text.
+fn layout<D: ?Sized + Draw, F>(draw: &mut D, text_style: &D::TextStyle, text: &str,
+                               mut glyph: F) -> f32
+    where F: FnMut(char, Matrix)
+{
     let mut previous_chr = None;
     let mut x = 0.0;
     for chr in text.chars() {
-        let glyph_infos = draw.draw().glyph_infos(text_style, chr);
-        let kerning = match mem::replace(&mut previous_chr, Some((chr, glyph_infos))) {
-            Some((prev, _)) => draw.draw().kerning(text_style, prev, chr),
-            None => 0.0
-        };
-
-        x += kerning;
+        let glyph_infos = draw.glyph_infos(text_style, chr);
+        if let Some((prev, _)) = previous_chr.replace((chr, glyph_infos)) {
+            x += draw.kerning(text_style, prev, chr);
+        }
 
         let matrix = Matrix::translate(x + glyph_infos.x_offset,
                                        glyph_infos.y_offset - glyph_infos.height)
@@ -102,7 +209,7 @@ fn helper<D: ?Sized + Draw, F>(draw: &DrawContext<D>, text_style: &D::TextStyle,
             * Matrix::translate(0.5, 0.5)
             * Matrix::scale(0.5);
 
-        glyphs.push((chr, matrix));
+        glyph(chr, matrix);
         x += glyph_infos.x_advance;
     }
 
@@ -112,11 +219,41 @@ fn helper<D: ?Sized + Draw, F>(draw: &DrawContext<D>, text_style: &D::TextStyle,
         x += prev_infos.width;
     }
 
+    x
+}
+
+/// Shrinks the context while keeping its aspect ratio, so that its height is at most
+/// `max_em_px`.
+fn cap<'b, D: ?Sized + Draw>(draw: DrawContext<'b, D>, alignment: &Alignment,
+                             max_em_px: Option<f32>) -> DrawContext<'b, D>
+{
+    match max_em_px.map(|max| max * draw.ui_scale()) {
+        Some(max) if draw.height() > max => {
+            let scale = max / draw.height();
+            draw.rescale(scale, scale, alignment)
+        },
+        _ => draw,
+    }
+}
+
+fn helper<D: ?Sized + Draw, F>(draw: &DrawContext<D>, text_style: &D::TextStyle, text: &str,
+                               final_matrix: F)
+    where F: FnOnce(f32) -> Matrix
+{
+    if draw.is_empty() {
+        return;
+    }
+
+    draw.push_accessibility(AccessibilityRole::Label, text);
+    draw.record_layout("label");
+
+    let mut glyphs: Vec<(char, Matrix)> = Vec::with_capacity(text.len());
+    let x = layout(&mut **draw.draw(), text_style, text, |chr, matrix| glyphs.push((chr, matrix)));
+
     // `x` now contains the width of the text in ems.
 
-    // So far the matrix of each character is in a coordinate system where 1.0 unit is equal to 1.0
-    // EM and the bottom-left corner of the first glyph is 0.0. Y=1.0 is the top of the line of
-    // text. We have to adjust this coordinates system for the final output.
+    // So far the matrix of each character is in the coordinate system of `layout`. We have to
+    // adjust this coordinates system for the final output.
     let recenter_matrix = Matrix::scale_wh(2.0 / x, 2.0)
             * Matrix::translate(-x / 2.0, -0.5);
 
@@ -126,3 +263,60 @@ fn helper<D: ?Sized + Draw, F>(draw: &DrawContext<D>, text_style: &D::TextStyle,
         draw.draw().draw_glyph(text_style, chr, &(final_matrix * recenter_matrix * matrix));
     } 
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+    use Alignment;
+    use HorizontalAlignment;
+
+    use super::block;
+    use super::contain_capped;
+
+    #[test]
+    fn capped_text_is_centered() {
+        for &size in &[800.0, 1600.0] {
+            let (mock, _) = mock::frame(size, size, None, false, false, UNIX_EPOCH, |ctxt| {
+                contain_capped(ctxt, "font", "a", &Alignment::center(), 100.0)
+            });
+
+            match mock.calls[0] {
+                Call::Glyph { ref matrix, .. } => {
+                    // The height of the glyph in pixels doesn't depend on the context.
+                    assert!((matrix.0[1][1] * size - 100.0).abs() < 0.001);
+                    assert!(matrix.0[2][0].abs() < 0.001);
+                    assert!(matrix.0[2][1].abs() < 0.001);
+                },
+                _ => panic!()
+            }
+        }
+    }
+
+    #[test]
+    fn block_alignments() {
+        let (mock, _) = mock::frame(600.0, 300.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            block(ctxt, "font", &[("a", HorizontalAlignment::Left),
+                                  ("b", HorizontalAlignment::Center),
+                                  ("c", HorizontalAlignment::Right)], 0.5)
+        });
+
+        let positions: Vec<[f32; 2]> = mock.calls.iter().map(|c| match *c {
+            Call::Glyph { ref matrix, .. } => matrix.0[2],
+            _ => panic!()
+        }).collect();
+
+        assert_eq!(mock.text(), "abc");
+        assert!(positions[0][0] < -0.5);
+        assert!(positions[1][0].abs() < 0.001);
+        assert!(positions[2][0] > 0.5);
+        assert!((positions[0][0] + positions[2][0]).abs() < 0.001);
+
+        // Each line is 75 pixels high, and the lines are separated by 37.5 pixels.
+        assert!((positions[0][1] - 0.75).abs() < 0.001);
+        assert!(positions[1][1].abs() < 0.001);
+        assert!((positions[2][1] + 0.75).abs() < 0.001);
+    }
+}
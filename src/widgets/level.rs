@@ -0,0 +1,77 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A level indicator is a row of segments, some of which are filled, like the bars of a battery
+//! or of the strength of a signal.
+//!
+//! A widget like this is composed of two images:
+//!
+//! - The image of a filled segment.
+//! - The image of an empty segment.
+//!
+
+use AccessibilityRole;
+use Draw;
+use DrawContext;
+
+use widgets::image;
+
+/// Space between two segments, as a fraction of the width of a segment.
+const GAP: f32 = 0.1;
+
+/// Draws `max` segments of the same size from left to right, and fills the first `level` ones.
+///
+/// `level` is clamped to `max`. Nothing is drawn if `max` is 0.
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, filled: &D::ImageResource,
+                              empty: &D::ImageResource, level: u32, max: u32)
+{
+    if max == 0 {
+        return;
+    }
+
+    let draw = draw.animation_stop();
+    let draw = &draw.layout_group("level");
+    draw.push_accessibility(AccessibilityRole::ProgressBar, "");
+
+    let level = if level > max { max } else { level };
+
+    for (index, segment) in draw.horizontal_split(max as usize).enumerate() {
+        let segment = segment.margin(0.0, GAP / 2.0, 0.0, GAP / 2.0);
+        let image = if (index as u32) < level { filled } else { empty };
+        image::stretch(&segment, image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+
+    use super::draw;
+
+    /// Draws the indicator in a 400x100 viewport, and returns the images that have been drawn.
+    fn images(level: u32, max: u32) -> Vec<String> {
+        let (mock, _) = mock::frame(400.0, 100.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            draw(ctxt, "filled", "empty", level, max)
+        });
+
+        mock.images().into_iter().map(|name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn segments() {
+        assert_eq!(images(2, 4), vec!["filled", "filled", "empty", "empty"]);
+        assert_eq!(images(0, 2), vec!["empty", "empty"]);
+        assert!(images(3, 0).is_empty());
+    }
+
+    #[test]
+    fn clamp_level() {
+        assert_eq!(images(7, 3), vec!["filled", "filled", "filled"]);
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A menu bar is a horizontal list of menus, like "File" or "Edit". Clicking on a menu opens a
+//! dropdown that contains the actions of this menu.
+//!
+//! The menus are laid out horizontally over the whole context, and the dropdown is drawn below
+//! the menu that is open. Only one menu can be open at a time. The dropdown is closed when the
+//! user chooses an action or clicks somewhere else.
+//!
+//! Since the dropdown is drawn by the function of this module, you should draw the menu bar after
+//! the rest of your UI so that the dropdown appears on top of it.
+
+use Draw;
+use DrawContext;
+use HorizontalAlignment;
+
+use widgets::image;
+use widgets::label;
+
+/// State of a menu bar. Must be kept between frames.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MenuBarState {
+    /// Index of the menu whose dropdown is open, if any.
+    pub open: Option<usize>,
+}
+
+/// Draws a menu bar.
+///
+/// Each element of `menus` contains the name of a menu and the list of its actions. The dropdowns
+/// use `dropdown_background` as their background.
+///
+/// Returns the index of the menu and the index of the action within the menu if the user clicked
+/// on an action.
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, state: &mut MenuBarState,
+                              menus: &[(&str, &[&str])], text_style: &D::TextStyle,
+                              dropdown_background: &D::ImageResource) -> Option<(usize, usize)>
+{
+    if menus.is_empty() {
+        state.open = None;
+        return None;
+    }
+
+    let draw = draw.animation_stop();
+    let mut clicked_inside = false;
+    let mut result = None;
+
+    let menus_ctxt: Vec<_> = draw.horizontal_split(menus.len()).collect();
+
+    for (num, (ctxt, &(name, _))) in menus_ctxt.iter().zip(menus.iter()).enumerate() {
+        if ctxt.is_cursor_hovering() {
+            ctxt.set_cursor_hovered_widget();
+
+            if ctxt.cursor_was_pressed() {
+                clicked_inside = true;
+                state.open = if state.open == Some(num) { None } else { Some(num) };
+            }
+        }
+
+        let ctxt = ctxt.uniform_margin(0.15, 0.15, 0.15, 0.15);
+        label::flow(&ctxt, text_style, name, &HorizontalAlignment::Center);
+    }
+
+    match state.open {
+        Some(open) if open < menus.len() && !menus[open].1.is_empty() => {
+            let actions = menus[open].1;
+
+            // The dropdown has the width of the menu and the height of one menu per action.
+            let dropdown = menus_ctxt[open].margin(1.0, 0.0, -(actions.len() as f32), 0.0)
+                                           .clamp_into_parent();
+            image::stretch(&dropdown, dropdown_background);
+
+            if dropdown.is_cursor_hovering() && dropdown.cursor_was_pressed() {
+                clicked_inside = true;
+            }
+
+            for (num, (ctxt, action)) in dropdown.vertical_split(actions.len())
+                                                 .zip(actions.iter()).enumerate()
+            {
+                if ctxt.is_cursor_hovering() && ctxt.cursor_was_pressed() {
+                    result = Some((open, num));
+                }
+
+                let ctxt = ctxt.uniform_margin(0.15, 0.15, 0.15, 0.15);
+                label::flow(&ctxt, text_style, action, &HorizontalAlignment::Left);
+            }
+        },
+        _ => {
+            state.open = None;
+        }
+    }
+
+    if result.is_some() || (draw.cursor_was_pressed() && !clicked_inside) {
+        state.open = None;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use VerticalAlignment;
+
+    use super::draw;
+    use super::MenuBarState;
+
+    #[test]
+    fn open_and_select() {
+        fn frame(state: &mut MenuBarState, cursor: [f32; 2], pressed: bool)
+                 -> Option<(usize, usize)>
+        {
+            let menus: &[(&str, &[&str])] = &[("File", &["New", "Open"]),
+                                              ("Edit", &["Copy", "Paste"])];
+
+            mock::frame(1000.0, 1000.0, Some(cursor), pressed, false, UNIX_EPOCH, |ctxt| {
+                let ctxt = ctxt.vertical_rescale(0.1, &VerticalAlignment::Top);
+                draw(&ctxt, state, menus, "font", "dropdown")
+            }).1
+        }
+
+        let mut state = MenuBarState::default();
+
+        assert_eq!(frame(&mut state, [0.5, 0.9], true), None);
+        assert_eq!(state.open, Some(1));
+        assert_eq!(frame(&mut state, [0.5, 0.7], false), None);
+        assert_eq!(state.open, Some(1));
+
+        assert_eq!(frame(&mut state, [0.5, 0.7], true), Some((1, 0)));
+        assert_eq!(state.open, None);
+
+        // Clicking outside of the menu bar closes the dropdown.
+        assert_eq!(frame(&mut state, [-0.5, 0.9], true), None);
+        assert_eq!(state.open, Some(0));
+        assert_eq!(frame(&mut state, [0.5, -0.5], true), None);
+        assert_eq!(state.open, None);
+    }
+}
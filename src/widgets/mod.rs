@@ -9,13 +9,34 @@
 //!
 //! Each module corresponds to a widget. See the individual module documentations for more info.
 
+use std::time::Duration;
+use std::time::SystemTime;
+
+pub mod badge;
 pub mod circular_progress_bar;
+pub mod dialog;
+pub mod empty_state;
+pub mod gallery;
 pub mod image;
 pub mod image9;
 pub mod image_button;
 pub mod image9_button;
+pub mod knob;
 pub mod label;
+pub mod level;
+pub mod menu_bar;
+pub mod number_input;
+pub mod panel;
 pub mod progress_bar;
+pub mod scrollbar;
+pub mod segmented;
+pub mod separator;
+pub mod skeleton;
+pub mod slider;
+pub mod splitter;
+pub mod tabs;
+pub mod toast;
+pub mod tooltip;
 
 /// Whether the cursor clicked on the widget.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -31,6 +52,7 @@ impl Interaction {
     /// Returns `true` if equal to `Clicked`. This function is useful so that you don't have to
     /// import the enum in scope.
     #[inline]
+    #[allow(clippy::match_like_matches_macro)]
     pub fn clicked(&self) -> bool {
         match self {
             &Interaction::Clicked => true,
@@ -38,3 +60,98 @@ impl Interaction {
         }
     }
 }
+
+/// Generates repeated events while a button is held, like the arrows of a scrollbar.
+///
+/// Call `poll` at each frame with whether the button is held. The first repeat happens after
+/// `initial_delay`, and the next ones every `interval`. The press itself doesn't count as a
+/// repeat. While the button is held, the UI must keep being redrawn, for example with
+/// `DrawContext::request_redraw`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepeatPress {
+    /// Moment when the button was pressed, if it is currently held.
+    start: Option<SystemTime>,
+    /// Number of repeats that have been returned for the current press.
+    fired: u32,
+}
+
+impl RepeatPress {
+    /// Builds a `RepeatPress` for a button that isn't held.
+    #[inline]
+    pub fn new() -> RepeatPress {
+        RepeatPress::default()
+    }
+
+    /// Returns the number of repeats that happened since the previous call.
+    ///
+    /// Releasing the button resets the schedule. An `interval` of zero is treated as an infinite
+    /// interval, in other words a single repeat happens after the delay.
+    pub fn poll(&mut self, is_down: bool, now: SystemTime, initial_delay: Duration,
+                interval: Duration) -> u32
+    {
+        if !is_down {
+            self.start = None;
+            self.fired = 0;
+            return 0;
+        }
+
+        let start = match self.start {
+            Some(start) => start,
+            None => {
+                self.start = Some(now);
+                return 0;
+            },
+        };
+
+        let held = now.duration_since(start).unwrap_or_default();
+        if held < initial_delay {
+            return 0;
+        }
+
+        let after_delay = (held - initial_delay).as_nanos();
+        let total = if interval == Duration::new(0, 0) {
+            1
+        } else {
+            1 + (after_delay / interval.as_nanos()).min(u32::MAX as u128 - 1) as u32
+        };
+
+        let new_fires = total.saturating_sub(self.fired);
+        self.fired = total;
+        new_fires
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use super::RepeatPress;
+
+    #[test]
+    fn repeat_press() {
+        let mut repeat = RepeatPress::new();
+        let delay = Duration::from_millis(500);
+        let interval = Duration::from_millis(100);
+        let mut poll = |is_down: bool, ms: u64| {
+            repeat.poll(is_down, UNIX_EPOCH + Duration::from_millis(ms), delay, interval)
+        };
+
+        assert_eq!(poll(true, 1000), 0);
+        assert_eq!(poll(true, 1300), 0);
+        assert_eq!(poll(true, 1499), 0);
+        assert_eq!(poll(true, 1500), 1);
+        assert_eq!(poll(true, 1550), 0);
+        assert_eq!(poll(true, 1600), 1);
+
+        // Slow frames report all the repeats that were missed.
+        assert_eq!(poll(true, 1950), 3);
+        assert_eq!(poll(true, 2000), 1);
+
+        // Releasing resets the delay.
+        assert_eq!(poll(false, 2010), 0);
+        assert_eq!(poll(true, 2020), 0);
+        assert_eq!(poll(true, 2400), 0);
+        assert_eq!(poll(true, 2520), 1);
+    }
+}
@@ -0,0 +1,398 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A number input is a single line of text in which the user can type a number.
+//!
+//! The user gives the focus to the input by clicking on it. While it has the focus, the input
+//! processes the `KeyboardEvent`s of the `UiState`: it accepts digits, a decimal point and a
+//! sign at the beginning, and ignores the other characters. Clicking somewhere else, or pressing
+//! enter, escape or tab removes the focus. The text is then replaced with the value, clamped
+//! between the minimum and the maximum.
+//!
+//! Only the text is drawn. If you want a background, draw it before the input.
+//!
+//! The `stepper` function also draws a button on each side of the input, that decreases or
+//! increases the value. Holding one of them down repeats the step.
+
+use std::time::Duration;
+
+use AccessibilityRole;
+use Alignment;
+use Draw;
+use DrawContext;
+use HorizontalAlignment;
+use KeyboardEvent;
+use Theme;
+use UiState;
+
+use widgets::image;
+use widgets::RepeatPress;
+use widgets::label;
+
+/// Duration during which a button of a stepper must be held before the step repeats.
+const REPEAT_DELAY_MS: u64 = 400;
+
+/// Duration between two repeats of the step while a button of a stepper is held.
+const REPEAT_INTERVAL_MS: u64 = 100;
+
+/// State of a number input. Must be kept between frames.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NumberInputState {
+    /// Current value of the input. This is the last valid value that has been typed.
+    pub value: f64,
+
+    /// Text that is displayed. While the input has the focus, this is what the user has typed
+    /// and it can be invalid, for example `-`.
+    pub text: String,
+
+    /// True if the input has the focus and processes the keyboard events.
+    pub focused: bool,
+
+    /// If set, the value is raised to this minimum when the input loses the focus.
+    pub min: Option<f64>,
+
+    /// If set, the value is lowered to this maximum when the input loses the focus.
+    pub max: Option<f64>,
+
+    /// Repeats the step while a button of a `stepper` is held.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub repeat: RepeatPress,
+}
+
+impl NumberInputState {
+    /// Builds the state of an input that contains `value` and doesn't have the focus.
+    #[inline]
+    pub fn new(value: f64) -> NumberInputState {
+        NumberInputState {
+            value,
+            text: format!("{}", value),
+            focused: false,
+            min: None,
+            max: None,
+            repeat: RepeatPress::new(),
+        }
+    }
+
+    /// Clamps the value between the minimum and the maximum, and replaces the text with it.
+    fn clamp(&mut self) {
+        if let Some(min) = self.min {
+            if self.value < min { self.value = min; }
+        }
+
+        if let Some(max) = self.max {
+            if self.value > max { self.value = max; }
+        }
+
+        self.text = format!("{}", self.value);
+    }
+
+    /// Same as `clamp`, and also removes the focus.
+    fn blur(&mut self) {
+        self.clamp();
+        self.focused = false;
+    }
+}
+
+/// Draws a number input and processes the keyboard events if it has the focus.
+///
+/// Returns the new value if it has changed during this frame, either because the user typed
+/// something or because it has been clamped when the input lost the focus. Returns `None` if
+/// the value didn't change, which is also the case while the text isn't a valid number.
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                              text_style: &D::TextStyle, state: &mut NumberInputState)
+                              -> Option<f64>
+{
+    let widget_id = draw.reserve_widget_id();
+    let draw = &draw.layout_group("number_input");
+    draw.push_accessibility(AccessibilityRole::TextInput, &state.text);
+
+    let previous_value = state.value;
+
+    if draw.is_cursor_hovering() {
+        draw.note_hover(&widget_id);
+
+        if draw.cursor_was_pressed() {
+            draw.consume_click();
+            state.focused = true;
+        }
+    } else if state.focused && draw.cursor_was_pressed() {
+        state.blur();
+    }
+
+    if state.focused {
+        let mut blur = false;
+
+        ui_state.keyboard_events.retain(|event| {
+            if blur {
+                return true;
+            }
+
+            match *event {
+                KeyboardEvent::Character(c) => {
+                    if is_accepted(&state.text, c) {
+                        state.text.push(c);
+                    }
+                },
+                KeyboardEvent::Backspace => {
+                    state.text.pop();
+                },
+                KeyboardEvent::Enter | KeyboardEvent::Escape | KeyboardEvent::Tab => {
+                    blur = true;
+                },
+                _ => return true,
+            }
+
+            false
+        });
+
+        match state.text.parse::<f64>() {
+            Ok(value) if value.is_finite() => state.value = value,
+            _ => ()
+        }
+
+        if blur {
+            state.blur();
+        }
+    }
+
+    label::flow(draw, text_style, &state.text, &HorizontalAlignment::Left);
+
+    if state.value != previous_value {
+        Some(state.value)
+    } else {
+        None
+    }
+}
+
+/// Draws a number input between a button that subtracts `step` from the value, on the left, and
+/// a button that adds it, on the right. The buttons are squares.
+///
+/// Pressing a button changes the value immediately, and holding it down repeats the change
+/// after a short delay. The value is clamped between the minimum and the maximum after each
+/// change. Returns the new value if it has changed during this frame, like `draw`.
+pub fn stepper<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                 theme: &Theme<D>, state: &mut NumberInputState, step: f64)
+                                 -> Option<f64>
+{
+    let previous_value = state.value;
+
+    let (minus, rest) = draw.split_off_aspect(1.0, &HorizontalAlignment::Left);
+    let (plus, input) = rest.split_off_aspect(1.0, &HorizontalAlignment::Right);
+
+    let mut steps = 0.0;
+    let mut held = false;
+
+    for &(ref button, sign, text) in &[(minus, -1.0, "-"), (plus, 1.0, "+")] {
+        let widget_id = button.reserve_widget_id();
+        let button = &button.layout_group("button");
+        button.push_accessibility(AccessibilityRole::Button, text);
+
+        let hovered = button.is_cursor_hovering();
+        if hovered {
+            button.set_cursor_hovered_widget();
+            button.note_hover(&widget_id);
+
+            if button.cursor_was_pressed() {
+                ui_state.active_widget = Some(widget_id.clone());
+                button.consume_click();
+                steps += sign;
+            }
+        }
+
+        let active = ui_state.active_widget.as_ref() == Some(&widget_id);
+        if active && button.cursor_was_released() {
+            ui_state.active_widget = None;
+        } else if active {
+            held = true;
+            let repeats = state.repeat.poll(true, button.now(),
+                                            Duration::from_millis(REPEAT_DELAY_MS),
+                                            Duration::from_millis(REPEAT_INTERVAL_MS));
+            steps += sign * repeats as f64;
+        }
+
+        let background = if active {
+            theme.button_active
+        } else if hovered {
+            theme.button_hovered
+        } else {
+            theme.button_normal
+        };
+        image::stretch(button, background);
+        label::contain(&button.uniform_margin(0.2, 0.2, 0.2, 0.2), theme.text, text,
+                       &Alignment::center());
+    }
+
+    if held {
+        // The step must keep repeating even if nothing else happens.
+        draw.request_redraw();
+    } else {
+        let _ = state.repeat.poll(false, draw.now(), Duration::new(0, 0), Duration::new(0, 0));
+    }
+
+    if steps != 0.0 {
+        state.value += steps * step;
+        state.clamp();
+    }
+
+    self::draw(&input, ui_state, theme.text, state);
+
+    if state.value != previous_value {
+        Some(state.value)
+    } else {
+        None
+    }
+}
+
+/// Returns true if `c` can be typed after `text`.
+#[inline]
+fn is_accepted(text: &str, c: char) -> bool {
+    match c {
+        '0' ..= '9' => true,
+        '.' => !text.contains('.'),
+        '-' | '+' => text.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use KeyboardEvent;
+    use UiState;
+    use VerticalAlignment;
+
+    use super::draw;
+    use super::stepper;
+    use super::NumberInputState;
+
+    fn frame(ui_state: &mut UiState, state: &mut NumberInputState, cursor: [f32; 2],
+             pressed: bool) -> Option<f64>
+    {
+        mock::frame(100.0, 100.0, Some(cursor), pressed, false, UNIX_EPOCH, |ctxt| {
+            let ctxt = ctxt.vertical_rescale(0.5, &VerticalAlignment::Center);
+            draw(&ctxt, ui_state, "font", state)
+        }).1
+    }
+
+    fn type_char(ui_state: &mut UiState, state: &mut NumberInputState, c: char) -> Option<f64> {
+        ui_state.keyboard_events.push(KeyboardEvent::Character(c));
+        let result = frame(ui_state, state, [0.0, 0.0], false);
+        assert!(ui_state.keyboard_events.is_empty());
+        result
+    }
+
+    #[test]
+    fn type_number() {
+        let mut ui_state = UiState::default();
+        let mut state = NumberInputState::new(0.0);
+        state.text.clear();
+
+        // Typing without the focus does nothing.
+        ui_state.keyboard_events.push(KeyboardEvent::Character('1'));
+        assert_eq!(frame(&mut ui_state, &mut state, [0.0, 0.0], false), None);
+        assert_eq!(state.text, "");
+        ui_state.keyboard_events.clear();
+
+        frame(&mut ui_state, &mut state, [0.0, 0.0], true);
+        assert!(state.focused);
+
+        assert_eq!(type_char(&mut ui_state, &mut state, '1'), Some(1.0));
+        assert_eq!(type_char(&mut ui_state, &mut state, '2'), Some(12.0));
+        assert_eq!(type_char(&mut ui_state, &mut state, '.'), None);
+        assert_eq!(type_char(&mut ui_state, &mut state, '5'), Some(12.5));
+        assert_eq!(state.text, "12.5");
+    }
+
+    #[test]
+    fn reject_invalid() {
+        let mut ui_state = UiState::default();
+        let mut state = NumberInputState::new(1.0);
+        frame(&mut ui_state, &mut state, [0.0, 0.0], true);
+
+        assert_eq!(type_char(&mut ui_state, &mut state, '.'), None);
+        assert_eq!(type_char(&mut ui_state, &mut state, '2'), Some(1.2));
+        assert_eq!(type_char(&mut ui_state, &mut state, '.'), None);
+        assert_eq!(type_char(&mut ui_state, &mut state, 'a'), None);
+        assert_eq!(type_char(&mut ui_state, &mut state, '-'), None);
+        assert_eq!(state.text, "1.2");
+
+        // A lone sign isn't a number.
+        state.text.clear();
+        assert_eq!(type_char(&mut ui_state, &mut state, '-'), None);
+        assert_eq!(state.value, 1.2);
+        assert_eq!(type_char(&mut ui_state, &mut state, '3'), Some(-3.0));
+    }
+
+    #[test]
+    fn clamp_on_blur() {
+        let mut ui_state = UiState::default();
+        let mut state = NumberInputState::new(5.0);
+        state.max = Some(10.0);
+        frame(&mut ui_state, &mut state, [0.0, 0.0], true);
+
+        assert_eq!(type_char(&mut ui_state, &mut state, '0'), Some(50.0));
+        assert_eq!(state.text, "50");
+
+        // Clicking outside of the input removes the focus.
+        assert_eq!(frame(&mut ui_state, &mut state, [0.0, 0.9], true), Some(10.0));
+        assert!(!state.focused);
+        assert_eq!(state.text, "10");
+
+        frame(&mut ui_state, &mut state, [0.0, 0.0], true);
+        ui_state.keyboard_events.push(KeyboardEvent::Backspace);
+        ui_state.keyboard_events.push(KeyboardEvent::Enter);
+        ui_state.keyboard_events.push(KeyboardEvent::Character('7'));
+        assert_eq!(frame(&mut ui_state, &mut state, [0.0, 0.0], false), Some(1.0));
+        assert!(!state.focused);
+        assert_eq!(ui_state.keyboard_events, vec![KeyboardEvent::Character('7')]);
+    }
+
+    #[test]
+    fn stepper_repeat() {
+        // Draws a stepper of 300x100 pixels, whose buttons are the first and the last 100
+        // pixels.
+        fn frame(ui_state: &mut UiState, state: &mut NumberInputState, cursor: [f32; 2],
+                 pressed: bool, released: bool, now_ms: u64) -> Option<f64>
+        {
+            let theme = mock::theme();
+
+            let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+            mock::frame(300.0, 100.0, Some(cursor), pressed, released, now, |ctxt| {
+                stepper(ctxt, ui_state, &theme, state, 0.5)
+            }).1
+        }
+
+        let mut ui_state = UiState::default();
+        let mut state = NumberInputState::new(0.0);
+        state.max = Some(2.0);
+        let plus = [0.8, 0.0];
+        let minus = [-0.8, 0.0];
+
+        // The press steps immediately, then the step repeats after the delay.
+        assert_eq!(frame(&mut ui_state, &mut state, plus, true, false, 0), Some(0.5));
+        assert_eq!(frame(&mut ui_state, &mut state, plus, false, false, 300), None);
+        assert_eq!(frame(&mut ui_state, &mut state, plus, false, false, 400), Some(1.0));
+        assert_eq!(frame(&mut ui_state, &mut state, plus, false, false, 600), Some(2.0));
+        assert_eq!(state.text, "2");
+
+        // The value is clamped.
+        assert_eq!(frame(&mut ui_state, &mut state, plus, false, false, 1000), None);
+        assert_eq!(frame(&mut ui_state, &mut state, plus, false, true, 1100), None);
+        assert_eq!(ui_state.active_widget, None);
+
+        // Releasing resets the delay.
+        assert_eq!(frame(&mut ui_state, &mut state, minus, true, false, 2000), Some(1.5));
+        assert_eq!(frame(&mut ui_state, &mut state, minus, false, false, 2300), None);
+        assert_eq!(frame(&mut ui_state, &mut state, minus, false, true, 2400), None);
+        assert_eq!(state.value, 1.5);
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A panel is a background with some content on top of it. The content can't overflow the
+//! panel.
+//!
+//! Clipping requires support from the implementation of `Draw`, see `Draw::set_clip`.
+
+use Draw;
+use DrawContext;
+
+use widgets::image;
+
+/// Draws the background stretched over the whole context, then calls `content` with a context
+/// that is clipped to the area of the panel.
+///
+/// Everything drawn afterwards with the same context as the panel isn't clipped anymore.
+#[inline]
+pub fn draw<D: ?Sized + Draw, F>(draw: &DrawContext<D>, background: &D::ImageResource, content: F)
+    where F: FnOnce(&DrawContext<D>)
+{
+    let draw = draw.layout_group("panel");
+    image::stretch(&draw, background);
+    content(&draw.with_clip());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+    use mock::MockDraw;
+    use SharedDrawContext;
+    use Alignment;
+    use UiState;
+
+    use widgets::image;
+    use super::draw;
+
+    /// Draws a panel in the center of the frame, then an image over the whole frame.
+    fn frame(shared: &SharedDrawContext) -> MockDraw {
+        mock::frame_in(shared, 800.0, 600.0, None, false, false, UNIX_EPOCH, |ctxt| {
+            let panel = ctxt.rescale(0.5, 0.5, &Alignment::center());
+
+            draw(&panel, "background", |content| {
+                image::stretch(&content.margin(0.0, 0.0, 0.0, 0.5), "content");
+            });
+            image::stretch(ctxt, "after");
+        }).0
+    }
+
+    #[test]
+    fn clips_content() {
+        let mock = frame(&::draw());
+
+        let clips: Vec<_> = mock.calls.iter().map(|c| match *c {
+            Call::Image { ref name, clip, .. } => (&name[..], clip),
+            _ => panic!()
+        }).collect();
+
+        assert_eq!(clips, vec![
+            ("background", None),
+            ("content", Some([-0.5, -0.5, 0.5, 0.5])),
+            ("after", None),
+        ]);
+    }
+
+    #[test]
+    fn layout_tree() {
+        let mut ui_state = UiState::default();
+        let shared = ::draw();
+        shared.enable_layout_recording();
+        frame(&shared);
+        shared.finish(&mut ui_state);
+
+        let kinds: Vec<_> = ui_state.layout.iter()
+            .map(|n| (&n.kind[..], n.children.iter().map(|c| &c.kind[..]).collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(kinds, vec![("panel", vec!["image", "image"]), ("image", vec![])]);
+        assert_eq!(ui_state.layout[0].children[1].bounds, [0.0, -0.5, 0.5, 0.5]);
+    }
+}
@@ -13,12 +13,19 @@
 //! - The widget when full. Since this one is drawn over the previous one, it can also just be the
 //!   difference between empty and full.
 //!
+//! The `marquee` function draws a progress bar for an operation whose progress isn't known. It
+//! requires a `SpinnerState` that must be kept between frames.
+//!
 
+use AccessibilityRole;
 use Alignment;
 use Draw;
 use DrawContext;
 use HorizontalAlignment;
 
+use animations::EaseInOut;
+use animations::Interpolation;
+use widgets::circular_progress_bar::SpinnerState;
 use widgets::image;
 
 /// Draws a progress bar and keeps the aspect ratio of the empty image.
@@ -50,6 +57,8 @@ pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource
 {
     assert!(progress >= 0.0);
     assert!(progress <= 1.0);
+    let draw = &draw.layout_group("progress_bar");
+    draw.push_accessibility(AccessibilityRole::ProgressBar, "");
 
     // Drawing the empty image.
     image::stretch(draw, empty);
@@ -59,3 +68,79 @@ pub fn stretch<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource
     draw.draw().draw_image_uv(full, &draw.matrix(), [0.0, 1.0], [progress, 1.0], [progress, 0.0],
                               [0.0, 0.0]);
 }
+
+/// Draws a progress bar whose progress is unknown, stretching it over the whole area.
+///
+/// A part of the `full` image, whose width is `fill_width` times the width of the bar, goes
+/// back and forth over the `empty` image. It goes from one end to the other and back once per
+/// period of the state.
+pub fn marquee<D: ?Sized + Draw>(draw: &DrawContext<D>, empty: &D::ImageResource,
+                                 full: &D::ImageResource, state: &SpinnerState, fill_width: f32)
+{
+    let fill_width = fill_width.clamp(0.0, 1.0);
+
+    draw.request_redraw();
+    let draw = &draw.layout_group("progress_bar");
+    draw.push_accessibility(AccessibilityRole::ProgressBar, "");
+
+    image::stretch(draw, empty);
+
+    let half_period = state.period / 2;
+    let progress = EaseInOut.alternate_repeat().calculate(draw.now(), state.start, half_period);
+    let left = progress as f32 * (1.0 - fill_width);
+    let right = left + fill_width;
+
+    let fill = draw.margin(0.0, 1.0 - right, 0.0, left);
+    fill.draw().draw_image_uv(full, &fill.matrix(), [left, 1.0], [right, 1.0], [right, 0.0],
+                              [left, 0.0]);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+
+    use widgets::circular_progress_bar::SpinnerState;
+    use super::marquee;
+
+    #[test]
+    fn marquee_sweeps() {
+        let state = SpinnerState { start: UNIX_EPOCH, period: Duration::from_secs(1) };
+
+        let fill_bounds = |now_ms: u64| {
+            let shared = ::draw();
+            let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+            let (mock, _) = mock::frame_in(&shared, 400.0, 40.0, None, false, false, now, |ctxt| {
+                marquee(ctxt, "empty", "full", &state, 0.25)
+            });
+            assert!(shared.needs_redraw());
+
+            match mock.calls[1] {
+                Call::Image { ref name, ref matrix, .. } => {
+                    assert_eq!(name, "full");
+                    let m = matrix.0;
+                    (m[2][0] - m[0][0], m[2][0] + m[0][0])
+                },
+                _ => panic!()
+            }
+        };
+
+        let mut positions = Vec::new();
+        for step in 0 .. 10 {
+            let (left, right) = fill_bounds(step * 100);
+            assert!(left >= -1.0001 && right <= 1.0001);
+            assert!((right - left - 0.5).abs() < 0.0001);
+            positions.push(left);
+        }
+
+        // The fill starts on the left, reaches the right at the middle of the period, and comes
+        // back.
+        assert!((positions[0] + 1.0).abs() < 0.0001);
+        assert!((fill_bounds(500).1 - 1.0).abs() < 0.0001);
+        assert!(positions[2] > positions[1] && positions[8] < positions[7]);
+        assert!((fill_bounds(1000).0 + 1.0).abs() < 0.0001);
+    }
+}
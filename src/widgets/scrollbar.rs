@@ -0,0 +1,179 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A scrollbar is a thumb that the user can drag along a track in order to scroll some content.
+//!
+//! A widget like this is composed of two images:
+//!
+//! - The track, which is stretched over the whole context.
+//! - The thumb, whose length is proportional to the part of the content that is visible.
+//!
+//! The position of the content is stored in a `ScrollState` that must be kept between frames.
+//! You can modify the offset yourself, for example in reaction to the mouse wheel, and the
+//! scrollbar will follow. Vertical and horizontal scrolling use separate states.
+
+use AccessibilityRole;
+use Draw;
+use DrawContext;
+use UiState;
+
+use widgets::image;
+
+/// State of a scrollbar. Must be kept between frames.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScrollState {
+    /// Part of the content that is before the visible area, where `1.0` is the size of the whole
+    /// content. This is between `0.0` and `1.0 - content_ratio`.
+    pub offset: f32,
+
+    /// While the thumb is dragged, position of the cursor relative to the start of the thumb.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    grab: f32,
+}
+
+impl ScrollState {
+    /// Builds a `ScrollState` at the start of the content.
+    #[inline]
+    pub fn new() -> ScrollState {
+        ScrollState::default()
+    }
+}
+
+/// Draws a vertical scrollbar.
+///
+/// `content_ratio` is the size of the visible area divided by the size of the whole content. For
+/// example `0.25` means that a quarter of the content is visible. The offset of the state is
+/// clamped so that it stays within the content.
+#[inline]
+pub fn vertical<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                  track: &D::ImageResource, thumb: &D::ImageResource,
+                                  state: &mut ScrollState, content_ratio: f32)
+{
+    draw_impl(draw, ui_state, track, thumb, state, content_ratio, true)
+}
+
+/// Draws a horizontal scrollbar. See `vertical`.
+#[inline]
+pub fn horizontal<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                    track: &D::ImageResource, thumb: &D::ImageResource,
+                                    state: &mut ScrollState, content_ratio: f32)
+{
+    draw_impl(draw, ui_state, track, thumb, state, content_ratio, false)
+}
+
+fn draw_impl<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                               track: &D::ImageResource, thumb: &D::ImageResource,
+                               state: &mut ScrollState, content_ratio: f32, vertical: bool)
+{
+    let widget_id = draw.reserve_widget_id();
+    let draw = &draw.layout_group("scrollbar");
+    draw.push_accessibility(AccessibilityRole::ScrollBar, "");
+    let draw = draw.animation_stop();
+
+    let ratio = content_ratio.clamp(0.0, 1.0);
+    let max_offset = 1.0 - ratio;
+    let clamp = |offset: f32| if offset < 0.0 { 0.0 } else if offset > max_offset { max_offset }
+                              else { offset };
+    state.offset = clamp(state.offset);
+
+    // Position of the cursor along the track, where `0.0` is the top or the left.
+    let cursor_position = draw.cursor_local_coordinates().map(|cursor| {
+        if vertical { (1.0 - cursor[1]) / 2.0 } else { (cursor[0] + 1.0) / 2.0 }
+    });
+
+    if draw.is_cursor_hovering() {
+        draw.note_hover(&widget_id);
+
+        if draw.cursor_was_pressed() {
+            if let Some(position) = cursor_position {
+                ui_state.active_widget = Some(widget_id.clone());
+                draw.consume_click();
+
+                if position >= state.offset && position <= state.offset + ratio {
+                    state.grab = position - state.offset;
+                } else {
+                    // Clicking on the track moves the center of the thumb under the cursor.
+                    state.grab = ratio / 2.0;
+                }
+            }
+        }
+    }
+
+    if draw.is_dragging_active(ui_state, &widget_id) {
+        if let Some(position) = cursor_position {
+            state.offset = clamp(position - state.grab);
+        }
+
+        if draw.cursor_was_released() {
+            ui_state.active_widget = None;
+        }
+    }
+
+    image::stretch(&draw, track);
+
+    let thumb_ctxt = if vertical {
+        draw.margin(state.offset, 0.0, max_offset - state.offset, 0.0)
+    } else {
+        draw.margin(0.0, max_offset - state.offset, 0.0, state.offset)
+    };
+    image::stretch(&thumb_ctxt, thumb);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use Alignment;
+    use UiState;
+
+    use super::vertical;
+    use super::ScrollState;
+
+    #[test]
+    fn drag_thumb_to_bottom() {
+        fn frame(ui_state: &mut UiState, state: &mut ScrollState, cursor: [f32; 2],
+                 pressed: bool, released: bool)
+        {
+            mock::frame(1000.0, 1000.0, Some(cursor), pressed, released, UNIX_EPOCH, |ctxt| {
+                let ctxt = ctxt.rescale(0.1, 1.0, &Alignment::right());
+                vertical(&ctxt, ui_state, "track", "thumb", state, 0.25);
+            });
+        }
+
+        let mut ui_state = UiState::default();
+        let mut state = ScrollState::new();
+
+        // The thumb covers the top quarter of the track and is grabbed at its center.
+        frame(&mut ui_state, &mut state, [0.95, 0.75], true, false);
+        assert_eq!(state.offset, 0.0);
+        frame(&mut ui_state, &mut state, [0.95, 0.25], false, false);
+        assert!((state.offset - 0.25).abs() < 0.0001);
+        frame(&mut ui_state, &mut state, [0.0, -3.0], false, true);
+        assert_eq!(state.offset, 0.75);
+        assert_eq!(ui_state.active_widget, None);
+
+        // Offsets modified from the outside are clamped.
+        state.offset = 2.0;
+        frame(&mut ui_state, &mut state, [0.0, 0.0], false, false);
+        assert_eq!(state.offset, 0.75);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        use serde_json;
+
+        let state = ScrollState { offset: 0.5, .. ScrollState::new() };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, "{\"offset\":0.5}");
+        assert_eq!(serde_json::from_str::<ScrollState>(&json).unwrap(), state);
+        assert_eq!(serde_json::from_str::<ScrollState>("{}").unwrap(), ScrollState::new());
+    }
+}
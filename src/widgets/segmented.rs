@@ -0,0 +1,197 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A segmented control is a row of labels, one of which is selected.
+//!
+//! A widget like this is composed of two images:
+//!
+//! - The background, which is stretched over the whole control.
+//! - The highlight, which is stretched over the selected segment. When the user selects another
+//!   segment, the highlight slides to it.
+//!
+//! Contrary to a menu bar, the control doesn't open anything. It only updates the index of the
+//! selected segment.
+
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use AccessibilityRole;
+use Alignment;
+use Draw;
+use DrawContext;
+use UiState;
+
+use animations::EaseOut;
+use animations::Translation;
+use widgets::image;
+use widgets::label;
+
+/// State of the animation of a segmented control. Must be kept between frames.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SegmentedState {
+    /// Duration of the slide of the highlight from one segment to another.
+    pub duration: Duration,
+
+    /// Segment that was selected before the current one.
+    from: usize,
+    /// Moment when the current segment was selected.
+    start: SystemTime,
+}
+
+impl SegmentedState {
+    /// Builds a `SegmentedState` object. The highlight doesn't move until a segment is clicked.
+    #[inline]
+    pub fn new(duration: Duration) -> SegmentedState {
+        SegmentedState {
+            duration,
+            from: 0,
+            start: UNIX_EPOCH,
+        }
+    }
+}
+
+impl Default for SegmentedState {
+    #[inline]
+    fn default() -> SegmentedState {
+        SegmentedState::new(Duration::from_millis(200))
+    }
+}
+
+/// Draws a segmented control with one segment per label, and updates `selected` when the user
+/// clicks on a segment.
+///
+/// The segments all have the same width. If `selected` is out of range, it is set to the last
+/// segment. Nothing is drawn if `labels` is empty.
+#[allow(clippy::too_many_arguments)]
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                              background: &D::ImageResource, highlight: &D::ImageResource,
+                              labels: &[&str], text_style: &D::TextStyle, selected: &mut usize,
+                              state: &mut SegmentedState)
+{
+    if labels.is_empty() {
+        return;
+    }
+
+    let draw = draw.animation_stop();
+    if *selected >= labels.len() {
+        *selected = labels.len() - 1;
+    }
+
+    let segments: Vec<_> = draw.horizontal_split(labels.len()).collect();
+
+    for (index, (segment, label)) in segments.iter().zip(labels.iter()).enumerate() {
+        let widget_id = segment.reserve_widget_id();
+        segment.push_accessibility(AccessibilityRole::Button, label);
+        segment.record_layout("segment");
+
+        if !segment.is_cursor_hovering() {
+            continue;
+        }
+
+        segment.set_cursor_hovered_widget();
+        segment.note_hover(&widget_id);
+
+        if segment.cursor_was_pressed() {
+            ui_state.active_widget = Some(widget_id);
+            segment.consume_click();
+
+        } else if ui_state.active_widget.as_ref() == Some(&widget_id) &&
+                  segment.cursor_was_released()
+        {
+            ui_state.active_widget = None;
+
+            if index != *selected {
+                state.from = *selected;
+                state.start = draw.now();
+                *selected = index;
+            }
+        }
+    }
+
+    image::stretch(&draw, background);
+
+    // The highlight starts at the previous segment, whose offset is expressed in widths of the
+    // selected segment.
+    let offset = 2.0 * (state.from as f32 - *selected as f32);
+    let animation = Translation { initial_offset: [offset, 0.0] };
+    let highlighted = segments[*selected].animate(animation, EaseOut::default(), state.start,
+                                                   state.duration);
+    image::stretch(&highlighted, highlight);
+
+    for (segment, text) in segments.iter().zip(labels.iter()) {
+        let text_ctxt = segment.uniform_margin(0.2, 0.2, 0.2, 0.2);
+        label::contain(&text_ctxt, text_style, text, &Alignment::center());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+    use UiState;
+
+    use super::draw;
+    use super::SegmentedState;
+
+    /// Draws a frame and returns the horizontal position of the center of the highlight.
+    fn frame(ui_state: &mut UiState, state: &mut SegmentedState, selected: &mut usize,
+             cursor: [f32; 2], pressed: bool, released: bool, now_ms: u64) -> f32
+    {
+        let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+        let (mock, _) = mock::frame(300.0, 100.0, Some(cursor), pressed, released, now, |ctxt| {
+            draw(ctxt, ui_state, "background", "highlight", &["A", "B", "C"], "font", selected,
+                 state)
+        });
+
+        match mock.calls[1] {
+            Call::Image { ref name, ref matrix, .. } => {
+                assert_eq!(name, "highlight");
+                matrix.0[2][0]
+            },
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn select_last() {
+        let mut ui_state = UiState::default();
+        let mut state = SegmentedState::new(Duration::from_secs(1));
+        let mut selected = 0;
+
+        let x = frame(&mut ui_state, &mut state, &mut selected, [0.8, 0.0], true, false, 0);
+        assert_eq!(selected, 0);
+        assert!((x + 2.0 / 3.0).abs() < 0.0001);
+
+        let x = frame(&mut ui_state, &mut state, &mut selected, [0.8, 0.0], false, true, 1000);
+        assert_eq!(selected, 2);
+        assert_eq!(ui_state.active_widget, None);
+
+        // The highlight slides from the first segment to the last one.
+        assert!((x + 2.0 / 3.0).abs() < 0.0001);
+        let x = frame(&mut ui_state, &mut state, &mut selected, [0.8, 0.0], false, false, 1500);
+        assert!(x > -2.0 / 3.0 && x < 2.0 / 3.0);
+        let x = frame(&mut ui_state, &mut state, &mut selected, [0.8, 0.0], false, false, 2000);
+        assert!((x - 2.0 / 3.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut ui_state = UiState::default();
+        let mut state = SegmentedState::default();
+        let mut selected = 7;
+
+        let x = frame(&mut ui_state, &mut state, &mut selected, [0.0, 0.0], false, false, 1000);
+        assert_eq!(selected, 2);
+        assert!((x - 2.0 / 3.0).abs() < 0.0001);
+    }
+}
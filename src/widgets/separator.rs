@@ -0,0 +1,181 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A separator is a thin line drawn in the middle of the context, in order to separate two
+//! groups of widgets.
+//!
+//! The line is drawn with `Draw::draw_line`. Dashed and dotted lines are drawn with one call
+//! per dash or dot.
+
+use Draw;
+use DrawContext;
+
+/// Maximum number of dashes or dots of a line. A pattern that would need more of them is so
+/// dense that the line is drawn as a solid line instead.
+const MAX_SEGMENTS: f32 = 4096.0;
+
+/// How the line of a separator looks like. All the dimensions are in pixels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LineStyle {
+    /// A continuous line.
+    Solid,
+    /// Dashes of length `dash`, separated with `gap`.
+    Dashed {
+        /// Length of each dash.
+        dash: f32,
+        /// Space between two dashes.
+        gap: f32,
+    },
+    /// Square dots whose side is the thickness of the line, separated with `spacing`.
+    Dotted {
+        /// Space between two dots.
+        spacing: f32,
+    },
+}
+
+/// Draws a horizontal line of `thickness_px` pixels, vertically centered in the context and that
+/// covers its whole width.
+///
+/// The color is in RGBA format and is multiplied with the tint of the context. The thickness is
+/// capped to the height of the context. The first dash or dot starts on the left of the context,
+/// and the last one is cut if it doesn't fit.
+#[inline]
+pub fn horizontal<D: ?Sized + Draw>(draw: &DrawContext<D>, color: [f32; 4], thickness_px: f32,
+                                    style: &LineStyle)
+{
+    draw_impl(draw, color, thickness_px, style, false)
+}
+
+/// Same as `horizontal`, except that the line is vertical and horizontally centered. The first
+/// dash or dot starts at the top of the context.
+#[inline]
+pub fn vertical<D: ?Sized + Draw>(draw: &DrawContext<D>, color: [f32; 4], thickness_px: f32,
+                                  style: &LineStyle)
+{
+    draw_impl(draw, color, thickness_px, style, true)
+}
+
+fn draw_impl<D: ?Sized + Draw>(draw: &DrawContext<D>, color: [f32; 4], thickness_px: f32,
+                               style: &LineStyle, vertical: bool)
+{
+    if draw.is_empty() || thickness_px.is_nan() || thickness_px <= 0.0 {
+        return;
+    }
+
+    let draw = draw.animation_stop();
+    let scale = draw.ui_scale();
+    let thickness_px = thickness_px * scale;
+
+    let (length, breadth) = if vertical { (draw.height(), draw.width()) }
+                            else { (draw.width(), draw.height()) };
+    let thickness = thickness_px.min(breadth);
+
+    let (dash, gap) = match *style {
+        LineStyle::Solid => (length, 0.0),
+        LineStyle::Dashed { dash, gap } => (dash * scale, gap * scale),
+        LineStyle::Dotted { spacing } => (thickness, spacing * scale),
+    };
+
+    // A pattern that doesn't advance would never end, and one that is too dense would produce
+    // a huge number of calls.
+    let valid = dash.is_finite() && gap.is_finite() && dash > 0.0 && gap >= 0.0 &&
+                length / (dash + gap) <= MAX_SEGMENTS;
+    let (dash, gap) = if valid { (dash, gap) } else { (length, 0.0) };
+
+    // Turns a position along the line, in pixels, into a point of the viewport.
+    let matrix = draw.matrix();
+    let point = |pos: f32| {
+        let pos = 2.0 * pos / length - 1.0;
+        let output = if vertical { matrix * [0.0, -pos, 1.0] } else { matrix * [pos, 0.0, 1.0] };
+        [output[0] / output[2], output[1] / output[2]]
+    };
+
+    let mut draw = draw.draw();
+    let mut start = 0.0;
+    while start < length {
+        let end = (start + dash).min(length);
+        draw.draw_line(point(start), point(end), thickness, color);
+        start += dash + gap;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+
+    use super::horizontal;
+    use super::vertical;
+    use super::LineStyle;
+
+    /// Draws a separator 100 pixels long in a viewport 20 pixels wide, and returns the line
+    /// segments that have been drawn, as `[min_x, min_y, max_x, max_y]`.
+    fn segments(style: LineStyle, is_vertical: bool) -> Vec<[f32; 4]> {
+        let (w, h) = if is_vertical { (20.0, 100.0) } else { (100.0, 20.0) };
+        let (mock, _) = mock::frame(w, h, None, false, false, UNIX_EPOCH, |ctxt| {
+            if is_vertical {
+                vertical(ctxt, [1.0, 1.0, 1.0, 1.0], 2.0, &style);
+            } else {
+                horizontal(ctxt, [1.0, 1.0, 1.0, 1.0], 2.0, &style);
+            }
+        });
+
+        mock.calls.iter().map(|call| match *call {
+            Call::Line { from, to, thickness_px, .. } => {
+                assert_eq!(thickness_px, 2.0);
+                [from[0].min(to[0]), from[1].min(to[1]), from[0].max(to[0]), from[1].max(to[1])]
+            },
+            _ => panic!()
+        }).collect()
+    }
+
+    #[test]
+    fn solid() {
+        let parts = segments(LineStyle::Solid, false);
+        assert_eq!(parts.len(), 1);
+        assert!((parts[0][0] + 1.0).abs() < 0.0001);
+        assert!((parts[0][2] - 1.0).abs() < 0.0001);
+        assert!(parts[0][1].abs() < 0.0001);
+        assert!(parts[0][3].abs() < 0.0001);
+    }
+
+    #[test]
+    fn dashed() {
+        // Dashes start every 15 pixels, and the last one ends exactly at the end of the line.
+        let parts = segments(LineStyle::Dashed { dash: 10.0, gap: 5.0 }, false);
+        assert_eq!(parts.len(), 7);
+        assert!((parts[1][0] + 0.7).abs() < 0.0001);
+        assert!((parts[1][2] + 0.5).abs() < 0.0001);
+        assert!((parts[6][2] - 1.0).abs() < 0.0001);
+
+        // The last dash is cut.
+        let parts = segments(LineStyle::Dashed { dash: 14.0, gap: 8.0 }, true);
+        assert_eq!(parts.len(), 5);
+        assert!((parts[0][3] - 1.0).abs() < 0.0001);
+        assert!((parts[4][1] + 1.0).abs() < 0.0001);
+        assert!((parts[4][3] + 0.76).abs() < 0.0001);
+    }
+
+    #[test]
+    fn dotted() {
+        // Dots of 2 pixels every 5 pixels.
+        let parts = segments(LineStyle::Dotted { spacing: 3.0 }, false);
+        assert_eq!(parts.len(), 20);
+        assert!((parts[0][2] - parts[0][0] - 0.04).abs() < 0.0001);
+    }
+
+    #[test]
+    fn dense_pattern() {
+        // Such a pattern would need a hundred million dashes, so a solid line is drawn instead.
+        assert_eq!(segments(LineStyle::Dashed { dash: 1e-6, gap: 0.0 }, false).len(), 1);
+        assert_eq!(segments(LineStyle::Dashed { dash: 0.01, gap: 0.01 }, true).len(), 1);
+        assert_eq!(segments(LineStyle::Dashed { dash: 0.5, gap: 0.5 }, false).len(), 100);
+    }
+}
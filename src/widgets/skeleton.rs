@@ -0,0 +1,139 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A skeleton is a placeholder that is drawn where some content will appear once it is loaded.
+//!
+//! A widget like this is composed of two images:
+//!
+//! - The base, which is stretched over the whole placeholder.
+//! - The highlight, usually a soft gradient, which is stretched over a band that regularly
+//!   sweeps across the placeholder.
+//!
+//! The position of the band is driven by a `SpinnerState` that must be kept between frames. The
+//! band crosses the placeholder once per period of the state.
+//!
+//! Clipping the band when it enters and leaves the placeholder requires support from the
+//! implementation of `Draw`, see `Draw::set_clip`.
+
+use AccessibilityRole;
+use Draw;
+use DrawContext;
+
+use animations::Interpolation;
+use animations::Linear;
+use widgets::circular_progress_bar::SpinnerState;
+use widgets::image;
+
+/// Width of the highlight band, as a fraction of the length of the placeholder.
+const BAND_WIDTH: f32 = 0.4;
+
+/// Direction in which the highlight band of a skeleton moves.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ShimmerDirection {
+    /// The band moves from the left to the right.
+    LeftToRight,
+    /// The band moves from the right to the left.
+    RightToLeft,
+    /// The band moves from the top to the bottom.
+    TopToBottom,
+    /// The band moves from the bottom to the top.
+    BottomToTop,
+}
+
+/// Draws a placeholder over the whole context, with a highlight band that sweeps across it in
+/// the given direction.
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, base: &D::ImageResource,
+                              highlight: &D::ImageResource, state: &SpinnerState,
+                              direction: &ShimmerDirection)
+{
+    if draw.is_empty() {
+        return;
+    }
+
+    let draw = draw.animation_stop();
+
+    // The band must keep moving even if nothing else happens.
+    draw.request_redraw();
+    let draw = &draw.layout_group("skeleton");
+    draw.push_accessibility(AccessibilityRole::ProgressBar, "");
+
+    image::stretch(draw, base);
+
+    // The band starts entirely before the placeholder and ends entirely after it.
+    let progress = Linear.repeat().calculate(draw.now(), state.start, state.period) as f32;
+    let start = -BAND_WIDTH + progress * (1.0 + BAND_WIDTH);
+    let end = start + BAND_WIDTH;
+
+    let clipped = draw.with_clip();
+    let band = match *direction {
+        ShimmerDirection::LeftToRight => clipped.margin(0.0, 1.0 - end, 0.0, start),
+        ShimmerDirection::RightToLeft => clipped.margin(0.0, start, 0.0, 1.0 - end),
+        ShimmerDirection::TopToBottom => clipped.margin(start, 0.0, 1.0 - end, 0.0),
+        ShimmerDirection::BottomToTop => clipped.margin(1.0 - end, 0.0, start, 0.0),
+    };
+
+    image::stretch(&band, highlight);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+
+    use widgets::circular_progress_bar::SpinnerState;
+    use super::draw;
+    use super::ShimmerDirection;
+
+    /// Draws a skeleton whose period is one second, and returns the center of the band and the
+    /// clip rectangle.
+    fn band(direction: ShimmerDirection, now_ms: u64) -> ([f32; 2], Option<[f32; 4]>) {
+        let state = SpinnerState { start: UNIX_EPOCH, period: Duration::from_secs(1) };
+        let shared = ::draw();
+        let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+        let (mock, _) = mock::frame_in(&shared, 400.0, 40.0, None, false, false, now, |ctxt| {
+            draw(ctxt, "base", "highlight", &state, &direction)
+        });
+        assert!(shared.needs_redraw());
+
+        assert_eq!(mock.images(), vec!["base", "highlight"]);
+        match mock.calls[1] {
+            Call::Image { ref matrix, clip, .. } => ([matrix.0[2][0], matrix.0[2][1]], clip),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn sweep_and_wrap() {
+        let (center, clip) = band(ShimmerDirection::LeftToRight, 0);
+        assert!((center[0] + 1.4).abs() < 0.0001);
+        assert_eq!(clip, Some([-1.0, -1.0, 1.0, 1.0]));
+
+        let mut previous = center[0];
+        for step in 1 .. 10 {
+            let (center, _) = band(ShimmerDirection::LeftToRight, step * 100);
+            assert!(center[0] > previous);
+            previous = center[0];
+        }
+
+        // The band has left the placeholder on the right, and starts again on the left.
+        assert!((band(ShimmerDirection::LeftToRight, 999).0[0] - 1.4).abs() < 0.01);
+        assert!((band(ShimmerDirection::LeftToRight, 1000).0[0] + 1.4).abs() < 0.0001);
+        assert!((band(ShimmerDirection::LeftToRight, 1500).0[0]).abs() < 0.0001);
+    }
+
+    #[test]
+    fn directions() {
+        assert!(band(ShimmerDirection::RightToLeft, 250).0[0] > 0.0);
+        assert!(band(ShimmerDirection::TopToBottom, 250).0[1] > 0.0);
+        assert!(band(ShimmerDirection::BottomToTop, 250).0[1] < 0.0);
+        assert_eq!(band(ShimmerDirection::TopToBottom, 250).0[0], 0.0);
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A slider is a handle that the user can drag along a horizontal track in order to choose a
+//! value between `0.0` and `1.0`.
+//!
+//! A widget like this is composed of two images:
+//!
+//! - The track, which is stretched over the whole context.
+//! - The handle, which is a square whose size is the height of the context.
+//!
+//! Once the user has pressed the slider, the value keeps following the cursor until the button
+//! is released, even if the cursor leaves the slider.
+//!
+//! The `stepped` function draws a slider whose value snaps to evenly spaced positions, and the
+//! `range` function draws a slider with two handles that delimit a range.
+
+use AccessibilityRole;
+use Draw;
+use DrawContext;
+use UiState;
+
+use widgets::image;
+
+/// Draws a slider and returns the new value.
+///
+/// The `value` is clamped between `0.0` and `1.0`.
+#[inline]
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                              track: &D::ImageResource, handle: &D::ImageResource, value: f32)
+                              -> f32
+{
+    draw_impl(draw, ui_state, track, handle, value, None)
+}
+
+/// Draws a slider whose value can only be one of `steps` evenly spaced positions, and returns the
+/// new value.
+///
+/// For example with 5 steps the possible values are `0.0`, `0.25`, `0.5`, `0.75` and `1.0`. The
+/// `value` is snapped to the nearest position. If `steps` is `0` or `1`, the value is always
+/// `0.0`.
+#[inline]
+pub fn stepped<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                 track: &D::ImageResource, handle: &D::ImageResource, value: f32,
+                                 steps: u32) -> f32
+{
+    draw_impl(draw, ui_state, track, handle, value, Some(steps))
+}
+
+/// Snaps a value between `0.0` and `1.0` to the nearest of `steps` evenly spaced positions.
+fn snap(value: f32, steps: u32) -> f32 {
+    if steps <= 1 {
+        return 0.0;
+    }
+
+    let intervals = (steps - 1) as f32;
+    (value * intervals).round() / intervals
+}
+
+fn draw_impl<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                               track: &D::ImageResource, handle: &D::ImageResource, value: f32,
+                               steps: Option<u32>) -> f32
+{
+    let widget_id = draw.reserve_widget_id();
+    let draw = &draw.layout_group("slider");
+    draw.push_accessibility(AccessibilityRole::Slider, "");
+    let draw = draw.animation_stop();
+
+    let handle_width = handle_width(&draw);
+    let mut value = value.clamp(0.0, 1.0);
+
+    if draw.is_cursor_hovering() {
+        draw.note_hover(&widget_id);
+
+        if draw.cursor_was_pressed() {
+            ui_state.active_widget = Some(widget_id.clone());
+            draw.consume_click();
+        }
+    }
+
+    if draw.is_dragging_active(ui_state, &widget_id) {
+        if let Some(new_value) = cursor_value(&draw, handle_width) {
+            value = new_value;
+        }
+
+        if draw.cursor_was_released() {
+            ui_state.active_widget = None;
+        }
+    }
+
+    if let Some(steps) = steps {
+        value = snap(value, steps);
+    }
+
+    image::stretch(&draw, track);
+    draw_handle(&draw, handle, handle_width, value);
+
+    value
+}
+
+/// Draws a slider with two handles that delimit a range, and updates `low` and `high`.
+///
+/// Each handle can be dragged independently. When the user presses the slider, the handle that
+/// is the closest to the cursor starts being dragged. The low handle can't go above the high
+/// handle and vice versa. The part of the track between the two handles is covered with the
+/// `highlight` image.
+pub fn range<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                               track: &D::ImageResource, highlight: &D::ImageResource,
+                               handle: &D::ImageResource, low: &mut f32, high: &mut f32)
+{
+    let low_id = draw.reserve_widget_id();
+    let high_id = draw.reserve_widget_id();
+    let draw = &draw.layout_group("slider");
+    draw.push_accessibility(AccessibilityRole::Slider, "");
+    let draw = draw.animation_stop();
+
+    let handle_width = handle_width(&draw);
+    *low = low.clamp(0.0, 1.0);
+    *high = high.clamp(0.0, 1.0);
+    if *low > *high {
+        *low = *high;
+    }
+
+    if draw.is_cursor_hovering() && draw.cursor_was_pressed() {
+        if let Some(value) = cursor_value(&draw, handle_width) {
+            let low_dist = (value - *low).abs();
+            let high_dist = (value - *high).abs();
+
+            let pick_low = if low_dist == high_dist { value < *low } else { low_dist < high_dist };
+            let id = if pick_low { low_id.clone() } else { high_id.clone() };
+            ui_state.active_widget = Some(id);
+            draw.consume_click();
+        }
+    }
+
+    for &(ref id, is_low) in &[(low_id, true), (high_id, false)] {
+        if draw.is_cursor_hovering() {
+            draw.note_hover(id);
+        }
+
+        if !draw.is_dragging_active(ui_state, id) {
+            continue;
+        }
+
+        if let Some(value) = cursor_value(&draw, handle_width) {
+            if is_low {
+                *low = if value > *high { *high } else { value };
+            } else {
+                *high = if value < *low { *low } else { value };
+            }
+        }
+
+        if draw.cursor_was_released() {
+            ui_state.active_widget = None;
+        }
+    }
+
+    image::stretch(&draw, track);
+
+    let low_center = *low * (1.0 - handle_width) + handle_width / 2.0;
+    let high_center = *high * (1.0 - handle_width) + handle_width / 2.0;
+    let highlight_ctxt = draw.margin(0.0, 1.0 - high_center, 0.0, low_center);
+    draw.draw().draw_image(highlight, &highlight_ctxt.matrix());
+
+    draw_handle(&draw, handle, handle_width, *low);
+    draw_handle(&draw, handle, handle_width, *high);
+}
+
+/// Returns the width of the handle, as a percentage of the width of the context.
+fn handle_width<D: ?Sized + Draw>(draw: &DrawContext<D>) -> f32 {
+    let handle_width = 1.0 / draw.width_per_height();
+    if handle_width > 1.0 { 1.0 } else { handle_width }
+}
+
+/// Returns the value that corresponds to the position of the cursor, if any.
+fn cursor_value<D: ?Sized + Draw>(draw: &DrawContext<D>, handle_width: f32) -> Option<f32> {
+    if handle_width >= 1.0 {
+        return None;
+    }
+
+    draw.cursor_local_coordinates().map(|cursor| {
+        ((cursor[0] + 1.0 - handle_width) / (2.0 - 2.0 * handle_width)).clamp(0.0, 1.0)
+    })
+}
+
+/// Draws the handle at the position that corresponds to the value.
+fn draw_handle<D: ?Sized + Draw>(draw: &DrawContext<D>, handle: &D::ImageResource,
+                                 handle_width: f32, value: f32)
+{
+    let handle_ctxt = draw.margin(0.0, (1.0 - value) * (1.0 - handle_width), 0.0,
+                                  value * (1.0 - handle_width));
+    draw.draw().draw_image(handle, &handle_ctxt.matrix());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use Alignment;
+    use UiState;
+
+    use super::draw;
+    use super::range;
+    use super::stepped;
+
+    #[test]
+    fn drag_outside_of_bounds() {
+        fn frame(ui_state: &mut UiState, value: f32, cursor: [f32; 2], pressed: bool,
+                 released: bool) -> f32
+        {
+            mock::frame(1000.0, 1000.0, Some(cursor), pressed, released, UNIX_EPOCH, |ctxt| {
+                let ctxt = ctxt.rescale(1.0, 0.1, &Alignment::center());
+                draw(&ctxt, ui_state, "track", "handle", value)
+            }).1
+        }
+
+        let mut ui_state = UiState::default();
+
+        let value = frame(&mut ui_state, 0.2, [0.0, 0.0], true, false);
+        assert!((value - 0.5).abs() < 0.0001);
+        assert!(ui_state.active_widget.is_some());
+
+        // The cursor is now above the slider.
+        let value = frame(&mut ui_state, value, [0.45, 0.5], false, false);
+        assert!((value - 0.75).abs() < 0.0001);
+        let value = frame(&mut ui_state, value, [2.0, 0.5], false, false);
+        assert_eq!(value, 1.0);
+        assert!(ui_state.active_widget.is_some());
+
+        let value = frame(&mut ui_state, value, [-0.45, -0.5], false, true);
+        assert!((value - 0.25).abs() < 0.0001);
+        assert_eq!(ui_state.active_widget, None);
+
+        // The slider is no longer active.
+        let value = frame(&mut ui_state, value, [0.45, 0.5], false, false);
+        assert!((value - 0.25).abs() < 0.0001);
+    }
+
+    #[test]
+    fn stepped_snaps() {
+        mock::frame(1000.0, 1000.0, Some([0.144, 0.0]), true, false, UNIX_EPOCH, |ctxt| {
+            let ctxt = ctxt.rescale(1.0, 0.1, &Alignment::center());
+            let mut ui_state = UiState::default();
+
+            assert_eq!(stepped(&ctxt, &mut ui_state, "track", "handle", 0.0, 5), 0.5);
+            assert_eq!(stepped(&ctxt, &mut ui_state, "track", "handle", 0.3, 1), 0.0);
+            assert_eq!(stepped(&ctxt, &mut ui_state, "track", "handle", 0.3, 0), 0.0);
+        });
+    }
+
+    #[test]
+    fn range_handles_dont_cross() {
+        fn frame(ui_state: &mut UiState, low: &mut f32, high: &mut f32, cursor: [f32; 2],
+                 pressed: bool, released: bool)
+        {
+            mock::frame(1000.0, 1000.0, Some(cursor), pressed, released, UNIX_EPOCH, |ctxt| {
+                let ctxt = ctxt.rescale(1.0, 0.1, &Alignment::center());
+                range(&ctxt, ui_state, "track", "highlight", "handle", low, high);
+            });
+        }
+
+        let mut ui_state = UiState::default();
+        let (mut low, mut high) = (0.2, 0.6);
+
+        // Grabbing the low handle and dragging it past the high handle.
+        frame(&mut ui_state, &mut low, &mut high, [-0.54, 0.0], true, false);
+        assert!((low - 0.2).abs() < 0.0001);
+        frame(&mut ui_state, &mut low, &mut high, [0.9, 0.0], false, false);
+        assert!((low - 0.6).abs() < 0.0001);
+        assert!((high - 0.6).abs() < 0.0001);
+        frame(&mut ui_state, &mut low, &mut high, [0.9, 0.0], false, true);
+        assert_eq!(ui_state.active_widget, None);
+
+        // Both handles are at the same position, so dragging to the left moves the low handle.
+        frame(&mut ui_state, &mut low, &mut high, [-0.9, 0.0], true, false);
+        assert!(low.abs() < 0.0001);
+        assert!((high - 0.6).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A splitter is a divider between two panels that the user can drag in order to resize them.
+//!
+//! The position of the divider is a ratio between `0.0` and `1.0` that must be kept between
+//! frames. Once the user has pressed the divider, it keeps following the cursor until the button
+//! is released, even if the cursor leaves the divider.
+
+use Draw;
+use DrawContext;
+use UiState;
+
+use widgets::image;
+
+/// Draws a vertical divider and returns the contexts of the panels on its left and on its right.
+///
+/// `ratio` is the horizontal position of the center of the divider, where `0.0` is the left of
+/// the context and `1.0` the right. It is updated when the user drags the divider, and is always
+/// kept between `min` and `max` so that no panel vanishes. `handle_width` is the width of the
+/// divider as a percentage of the width of the context.
+pub fn vertical<'b, D: ?Sized + Draw>(draw: &DrawContext<'b, D>, ui_state: &mut UiState,
+                                      handle: &D::ImageResource, handle_width: f32,
+                                      ratio: &mut f32, min: f32, max: f32)
+                                      -> (DrawContext<'b, D>, DrawContext<'b, D>)
+{
+    let widget_id = draw.reserve_widget_id();
+    let clamp = |value: f32| if value < min { min } else if value > max { max } else { value };
+    *ratio = clamp(*ratio);
+
+    {
+        let handle_ctxt = handle_context(draw, *ratio, handle_width);
+        if handle_ctxt.is_cursor_hovering() {
+            handle_ctxt.note_hover(&widget_id);
+
+            if handle_ctxt.cursor_was_pressed() {
+                ui_state.active_widget = Some(widget_id.clone());
+                handle_ctxt.consume_click();
+            }
+        }
+    }
+
+    if draw.is_dragging_active(ui_state, &widget_id) {
+        if let Some(cursor) = draw.cursor_local_coordinates() {
+            *ratio = clamp((cursor[0] + 1.0) / 2.0);
+        }
+
+        if draw.cursor_was_released() {
+            ui_state.active_widget = None;
+        }
+    }
+
+    image::stretch(&handle_context(draw, *ratio, handle_width).animation_stop(), handle);
+
+    let left = draw.margin(0.0, 1.0 - *ratio + handle_width / 2.0, 0.0, 0.0);
+    let right = draw.margin(0.0, 0.0, 0.0, *ratio + handle_width / 2.0);
+    (left, right)
+}
+
+/// Returns the context of the divider.
+fn handle_context<'b, D: ?Sized + Draw>(draw: &DrawContext<'b, D>, ratio: f32, handle_width: f32)
+                                        -> DrawContext<'b, D>
+{
+    draw.margin(0.0, 1.0 - ratio - handle_width / 2.0, 0.0, ratio - handle_width / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use UiState;
+
+    use super::vertical;
+
+    #[test]
+    fn drag_within_bounds() {
+        fn frame(ui_state: &mut UiState, ratio: &mut f32, cursor: [f32; 2], pressed: bool,
+                 released: bool) -> f32
+        {
+            mock::frame(1000.0, 1000.0, Some(cursor), pressed, released, UNIX_EPOCH, |ctxt| {
+                let (left, _) = vertical(ctxt, ui_state, "handle", 0.02, ratio, 0.2, 0.8);
+                left.width()
+            }).1
+        }
+
+        let mut ui_state = UiState::default();
+        let mut ratio = 0.5;
+
+        // Pressing next to the divider does nothing.
+        frame(&mut ui_state, &mut ratio, [0.5, 0.0], true, false);
+        assert_eq!(ui_state.active_widget, None);
+        assert_eq!(ratio, 0.5);
+
+        frame(&mut ui_state, &mut ratio, [0.0, 0.0], true, false);
+        assert!(ui_state.active_widget.is_some());
+        frame(&mut ui_state, &mut ratio, [0.2, 0.5], false, false);
+        assert!((ratio - 0.6).abs() < 0.0001);
+
+        let left_width = frame(&mut ui_state, &mut ratio, [1.5, 0.5], false, true);
+        assert_eq!(ratio, 0.8);
+        assert!((left_width - 790.0).abs() < 0.01);
+        assert_eq!(ui_state.active_widget, None);
+    }
+}
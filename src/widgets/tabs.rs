@@ -0,0 +1,161 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A tab strip is a row of tabs, one of which is selected.
+//!
+//! The tabs of `closable` also have a small close button on their right, like in code editors.
+//! The widget doesn't store the list of tabs: it reports which tab has been selected or closed,
+//! and the caller updates its own list accordingly.
+//!
+//! The images and the text style of the tabs are taken from the theme. The selected tab uses the
+//! image of an active button.
+
+use AccessibilityRole;
+use Alignment;
+use Draw;
+use DrawContext;
+use HorizontalAlignment;
+use Theme;
+use UiState;
+
+use widgets::image;
+use widgets::image_button;
+use widgets::label;
+
+/// What the user did with a tab strip.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum TabAction {
+    /// Nothing happened.
+    None,
+    /// The tab with the given index has been clicked and is now selected.
+    Selected(usize),
+    /// The close button of the tab with the given index has been clicked. The caller is
+    /// responsible for removing the tab.
+    Closed(usize),
+}
+
+/// Draws a row of tabs of the same width, each with a label and a close button.
+///
+/// Clicking on a tab updates `selected` and returns `Selected`, and clicking on the close button
+/// of a tab returns `Closed` without modifying `selected`. If `selected` is out of range, it is
+/// set to the last tab. Nothing is drawn if `labels` is empty.
+pub fn closable<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                  theme: &Theme<D>, labels: &[&str], selected: &mut usize)
+                                  -> TabAction
+{
+    if labels.is_empty() {
+        return TabAction::None;
+    }
+
+    let draw = draw.animation_stop();
+    if *selected >= labels.len() {
+        *selected = labels.len() - 1;
+    }
+
+    let mut action = TabAction::None;
+
+    for (index, (tab, text)) in draw.horizontal_split(labels.len()).zip(labels.iter()).enumerate() {
+        let widget_id = tab.reserve_widget_id();
+        let tab = &tab.layout_group("tab");
+        tab.push_accessibility(AccessibilityRole::Button, text);
+
+        // The close button is a square on the right of the tab, and the rest is the body.
+        let (close, body) = tab.split_off_aspect(1.0, &HorizontalAlignment::Right);
+
+        let hovered = body.is_cursor_hovering();
+        if hovered {
+            body.set_cursor_hovered_widget();
+            body.note_hover(&widget_id);
+
+            if body.cursor_was_pressed() {
+                ui_state.active_widget = Some(widget_id.clone());
+                body.consume_click();
+
+            } else if ui_state.active_widget.as_ref() == Some(&widget_id) &&
+                      body.cursor_was_released()
+            {
+                ui_state.active_widget = None;
+                *selected = index;
+                action = TabAction::Selected(index);
+            }
+        }
+
+        let background = if index == *selected || ui_state.active_widget == Some(widget_id) {
+            theme.button_active
+        } else if hovered {
+            theme.button_hovered
+        } else {
+            theme.button_normal
+        };
+        image::stretch(tab, background);
+
+        let text_ctxt = body.uniform_margin(0.2, 0.1, 0.2, 0.2);
+        label::contain(&text_ctxt, theme.text, text, &Alignment::left());
+
+        let close = close.uniform_margin(0.2, 0.2, 0.2, 0.2);
+        if image_button::stretch_themed(&close, ui_state, theme).clicked() {
+            action = TabAction::Closed(index);
+        }
+        label::contain(&close.uniform_margin(0.2, 0.2, 0.2, 0.2), theme.text, "x",
+                       &Alignment::center());
+    }
+
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use UiState;
+
+    use super::closable;
+    use super::TabAction;
+
+    /// Draws three tabs that are 200x40 pixels, and returns the action.
+    fn frame(ui_state: &mut UiState, selected: &mut usize, cursor: [f32; 2], pressed: bool,
+             released: bool) -> TabAction
+    {
+        let theme = mock::theme();
+
+        mock::frame(600.0, 40.0, Some(cursor), pressed, released, UNIX_EPOCH, |ctxt| {
+            closable(ctxt, ui_state, &theme, &["a.rs", "b.rs", "c.rs"], selected)
+        }).1
+    }
+
+    #[test]
+    fn select_and_close() {
+        let mut ui_state = UiState::default();
+        let mut selected = 0;
+
+        // The body of the second tab.
+        let body = [-0.1, 0.0];
+        assert_eq!(frame(&mut ui_state, &mut selected, body, true, false), TabAction::None);
+        assert_eq!(frame(&mut ui_state, &mut selected, body, false, true),
+                   TabAction::Selected(1));
+        assert_eq!(selected, 1);
+
+        // The close button of the second tab, which is the last 40 pixels of the tab.
+        let close = [0.2667, 0.0];
+        assert_eq!(frame(&mut ui_state, &mut selected, close, true, false), TabAction::None);
+        assert_eq!(frame(&mut ui_state, &mut selected, close, false, true),
+                   TabAction::Closed(1));
+        assert_eq!(selected, 1);
+        assert_eq!(ui_state.active_widget, None);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut ui_state = UiState::default();
+        let mut selected = 5;
+        assert_eq!(frame(&mut ui_state, &mut selected, [0.0, 0.0], false, false),
+                   TabAction::None);
+        assert_eq!(selected, 2);
+    }
+}
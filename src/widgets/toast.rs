@@ -0,0 +1,208 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Toasts are short messages that are stacked in a corner of the context, and that disappear
+//! after some time.
+//!
+//! The toasts are stored in a `ToastStack` that must be kept between frames. Push a toast in the
+//! stack when something happens, and draw the stack at each frame with `draw_stack`. The toasts
+//! slide and fade in when they appear, and are removed from the stack once their duration has
+//! elapsed or when the user clicks on them.
+//!
+//! The images and the text style of the toasts are taken from the theme.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use Alignment;
+use Draw;
+use DrawContext;
+use HorizontalAlignment;
+use Theme;
+use UiState;
+use VerticalAlignment;
+
+use animations::EaseOut;
+use animations::FullAnimation;
+use animations::Translation;
+use widgets::image_button;
+use widgets::label;
+
+/// Duration of the animation of a toast that appears.
+const ENTRANCE_DURATION_MS: u64 = 200;
+
+/// A message of a `ToastStack`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    /// The text that is displayed.
+    pub message: String,
+    /// Moment when the toast has been pushed.
+    pub created_at: SystemTime,
+    /// Duration after which the toast is removed.
+    pub duration: Duration,
+}
+
+/// List of toasts that are displayed. Must be kept between frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToastStack {
+    /// The toasts, from the oldest to the most recent.
+    pub toasts: Vec<Toast>,
+
+    /// Corner of the context where the toasts are drawn. The most recent toast is the closest to
+    /// the corner, and the older ones are pushed towards the center.
+    pub corner: Alignment,
+
+    /// Width and height of a toast in pixels.
+    pub toast_size: [f32; 2],
+}
+
+impl ToastStack {
+    /// Builds an empty stack whose toasts are drawn in the given corner.
+    #[inline]
+    pub fn new(corner: Alignment) -> ToastStack {
+        ToastStack {
+            toasts: Vec::new(),
+            corner,
+            toast_size: [300.0, 60.0],
+        }
+    }
+
+    /// Adds a toast that is removed after `duration`.
+    ///
+    /// `now` should be the same clock as the one that is passed to `SharedDrawContext::draw`.
+    #[inline]
+    pub fn push(&mut self, message: &str, now: SystemTime, duration: Duration) {
+        self.toasts.push(Toast {
+            message: message.to_owned(),
+            created_at: now,
+            duration,
+        });
+    }
+}
+
+/// Removes the toasts that have expired, then draws the other ones.
+///
+/// Clicking on a toast removes it from the stack. The toasts that don't fit in the context are
+/// not drawn.
+pub fn draw_stack<D: ?Sized + Draw>(draw: &DrawContext<D>, ui_state: &mut UiState,
+                                    stack: &mut ToastStack, theme: &Theme<D>)
+{
+    let now = draw.now();
+    stack.toasts.retain(|toast| {
+        now.duration_since(toast.created_at).map(|elapsed| elapsed < toast.duration)
+                                            .unwrap_or(true)
+    });
+
+    if stack.toasts.is_empty() || draw.is_empty() {
+        return;
+    }
+
+    // The toasts must disappear even if nothing else happens.
+    draw.request_redraw();
+
+    let draw = draw.animation_stop();
+
+    let toast_width = stack.toast_size[0] * draw.ui_scale();
+    let width = if toast_width > draw.width() { 1.0 } else { toast_width / draw.width() };
+    let height = stack.toast_size[1] * draw.ui_scale() / draw.height();
+    let column = draw.horizontal_rescale(width, &stack.corner.horizontal);
+
+    let initial_offset = match stack.corner.horizontal {
+        HorizontalAlignment::Left => [-2.0, 0.0],
+        _ => [2.0, 0.0],
+    };
+    let animation = FullAnimation::new(Translation { initial_offset }, 0.0);
+
+    let mut dismissed = None;
+
+    for (position, index) in (0 .. stack.toasts.len()).rev().enumerate() {
+        let start = position as f32 * height;
+        let end = start + height;
+        if end > 1.0 {
+            break;
+        }
+
+        let slot = match stack.corner.vertical {
+            VerticalAlignment::Bottom => column.margin(1.0 - end, 0.0, start, 0.0),
+            _ => column.margin(start, 0.0, 1.0 - end, 0.0),
+        };
+
+        let toast = &stack.toasts[index];
+        let slot = slot.animate_full(&animation, EaseOut::default(), toast.created_at,
+                                     Duration::from_millis(ENTRANCE_DURATION_MS));
+
+        if image_button::stretch_themed(&slot, ui_state, theme).clicked() {
+            dismissed = Some(index);
+        }
+
+        let text = slot.uniform_margin(0.2, 0.1, 0.2, 0.1);
+        label::contain(&text, theme.text, &toast.message, &Alignment::center());
+    }
+
+    if let Some(index) = dismissed {
+        stack.toasts.remove(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use Alignment;
+    use UiState;
+
+    use super::draw_stack;
+    use super::ToastStack;
+
+    /// Draws the stack in a 600x300 viewport, and returns the text that has been drawn.
+    fn frame(ui_state: &mut UiState, stack: &mut ToastStack, cursor: [f32; 2], pressed: bool,
+             released: bool, now_ms: u64) -> String
+    {
+        let theme = mock::theme();
+
+        let now = UNIX_EPOCH + Duration::from_millis(now_ms);
+        mock::frame(600.0, 300.0, Some(cursor), pressed, released, now, |ctxt| {
+            draw_stack(ctxt, ui_state, stack, &theme)
+        }).0.text()
+    }
+
+    #[test]
+    fn expire() {
+        let mut ui_state = UiState::default();
+        let mut stack = ToastStack::new(Alignment::bottom_right());
+        stack.push("a", UNIX_EPOCH, Duration::from_secs(2));
+        stack.push("b", UNIX_EPOCH + Duration::from_secs(1), Duration::from_secs(2));
+
+        // The most recent toast is drawn first, closest to the corner.
+        assert_eq!(frame(&mut ui_state, &mut stack, [0.0, 0.0], false, false, 1500), "ba");
+        assert_eq!(stack.toasts.len(), 2);
+
+        assert_eq!(frame(&mut ui_state, &mut stack, [0.0, 0.0], false, false, 2000), "b");
+        assert_eq!(stack.toasts.len(), 1);
+        assert_eq!(stack.toasts[0].message, "b");
+
+        assert_eq!(frame(&mut ui_state, &mut stack, [0.0, 0.0], false, false, 3000), "");
+        assert!(stack.toasts.is_empty());
+    }
+
+    #[test]
+    fn click_dismisses() {
+        let mut ui_state = UiState::default();
+        let mut stack = ToastStack::new(Alignment::bottom_right());
+        stack.push("a", UNIX_EPOCH, Duration::from_secs(10));
+        stack.push("b", UNIX_EPOCH, Duration::from_secs(10));
+
+        // The second slot from the bottom-right hand corner contains the oldest toast.
+        let cursor = [0.5, -0.4];
+        frame(&mut ui_state, &mut stack, cursor, true, false, 1000);
+        frame(&mut ui_state, &mut stack, cursor, false, true, 1000);
+        assert_eq!(stack.toasts.len(), 1);
+        assert_eq!(stack.toasts[0].message, "b");
+    }
+}
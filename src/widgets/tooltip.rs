@@ -0,0 +1,161 @@
+// Copyright 2016 immi Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A tooltip is a short text drawn next to the cursor, usually while it hovers another widget.
+//!
+//! The tooltip is normally drawn below and to the right of the cursor. If it doesn't fit there,
+//! it is flipped to the other side of the cursor, and if it doesn't fit either it is moved so
+//! that it stays within the context. Its size is calculated from the dimensions of the text.
+//!
+//! Since the tooltip must be able to go anywhere, you should pass a context that covers the whole
+//! viewport, and draw the tooltip after the rest of the UI.
+
+use Alignment;
+use Draw;
+use DrawContext;
+
+use widgets::image;
+use widgets::label;
+
+/// Space between the cursor and the tooltip, in pixels.
+const CURSOR_OFFSET_PX: f32 = 12.0;
+
+/// Space between the border of the tooltip and its text, as a multiple of the height of the text.
+const PADDING: f32 = 0.25;
+
+/// Draws a tooltip next to the cursor. Does nothing if there is no cursor.
+///
+/// `em_px` is the height of the text in pixels. The `background` image is stretched behind the
+/// text. The tooltip never extends past the edges of the context, and is shrunk if it is larger
+/// than the context.
+pub fn draw<D: ?Sized + Draw>(draw: &DrawContext<D>, background: &D::ImageResource,
+                              text_style: &D::TextStyle, text: &str, em_px: f32)
+{
+    if draw.is_empty() || text.is_empty() || em_px.is_nan() || em_px <= 0.0 {
+        return;
+    }
+
+    let cursor = match draw.cursor_local_coordinates() {
+        Some(c) => c,
+        None => return,
+    };
+
+    let draw = draw.animation_stop().suppress_hover();
+    let em_px = em_px * draw.ui_scale();
+    let offset_px = CURSOR_OFFSET_PX * draw.ui_scale();
+
+    let padding_px = em_px * PADDING;
+    let width_px = label::measure_width(&draw, text_style, text) * em_px + 2.0 * padding_px;
+    let height_px = em_px + 2.0 * padding_px;
+
+    // Everything below is in the coordinates of the context, between `-1.0` and `1.0`.
+    let width = (2.0 * width_px / draw.width()).min(2.0);
+    let height = (2.0 * height_px / draw.height()).min(2.0);
+
+    let left = place(cursor[0], width, 2.0 * offset_px / draw.width(), true);
+    let bottom = place(cursor[1], height, 2.0 * offset_px / draw.height(), false);
+
+    let tooltip = draw.margin((1.0 - bottom - height) / 2.0, (1.0 - left - width) / 2.0,
+                              (1.0 + bottom) / 2.0, (1.0 + left) / 2.0);
+    image::stretch(&tooltip, background);
+
+    let vertical_padding = padding_px / tooltip.height();
+    let horizontal_padding = padding_px / tooltip.width();
+    let text_ctxt = tooltip.margin(vertical_padding, horizontal_padding, vertical_padding,
+                                   horizontal_padding);
+    label::contain(&text_ctxt, text_style, text, &Alignment::left());
+}
+
+/// Returns the lowest coordinate of a segment of length `size` that is placed on one axis at
+/// `offset` from the cursor.
+///
+/// The segment is placed after the cursor if `after` is true, and before it otherwise. If it
+/// doesn't fit between `-1.0` and `1.0` on that side, it is placed on the other side. If it
+/// doesn't fit either, it is moved back within the bounds.
+fn place(cursor: f32, size: f32, offset: f32, after: bool) -> f32 {
+    let after_pos = cursor + offset;
+    let before_pos = cursor - offset - size;
+
+    let fits_after = after_pos + size <= 1.0;
+    let fits_before = before_pos >= -1.0;
+
+    let (preferred, fits_preferred, other, fits_other) = if after {
+        (after_pos, fits_after, before_pos, fits_before)
+    } else {
+        (before_pos, fits_before, after_pos, fits_after)
+    };
+
+    let pos = if !fits_preferred && fits_other { other } else { preferred };
+
+    if pos + size > 1.0 {
+        1.0 - size
+    } else if pos < -1.0 {
+        -1.0
+    } else {
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use mock;
+    use mock::Call;
+
+    use super::draw;
+
+    /// Draws a tooltip whose text is 20 pixels high in a 800x600 viewport, and returns the
+    /// bounds of the background.
+    fn bounds(cursor: [f32; 2]) -> [f32; 4] {
+        let (mock, _) = mock::frame(800.0, 600.0, Some(cursor), false, false, UNIX_EPOCH, |ctxt| {
+            draw(ctxt, "background", "font", "tooltip", 20.0)
+        });
+
+        match mock.calls[0] {
+            Call::Image { ref name, ref matrix, .. } => {
+                assert_eq!(name, "background");
+                let m = matrix.0;
+                [m[2][0] - m[0][0], m[2][1] - m[1][1], m[2][0] + m[0][0], m[2][1] + m[1][1]]
+            },
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn below_right() {
+        // The text is 7 ems long, so the tooltip is 150x30 pixels.
+        let b = bounds([0.0, 0.0]);
+        assert!((b[0] - 0.03).abs() < 0.0001);
+        assert!((b[2] - 0.405).abs() < 0.0001);
+        assert!((b[3] + 0.04).abs() < 0.0001);
+        assert!((b[1] + 0.14).abs() < 0.0001);
+    }
+
+    #[test]
+    fn flip_near_edges() {
+        let b = bounds([0.9, -0.95]);
+        assert!(b[2] < 0.9);
+        assert!((b[2] - 0.87).abs() < 0.0001);
+        assert!(b[1] > -0.95);
+        assert!((b[1] + 0.91).abs() < 0.0001);
+    }
+
+    #[test]
+    fn clamp_into_viewport() {
+        // The tooltip is wider than the viewport, and fits on neither side of the cursor.
+        let (mock, _) = mock::frame(100.0, 600.0, Some([0.0, 0.0]), false, false, UNIX_EPOCH,
+                                    |ctxt| draw(ctxt, "background", "font", "tooltip", 20.0));
+        match mock.calls[0] {
+            Call::Image { ref matrix, .. } => {
+                assert!((matrix.0[2][0]).abs() < 0.0001);
+                assert!((matrix.0[0][0] - 1.0).abs() < 0.0001);
+            },
+            _ => panic!()
+        }
+    }
+}